@@ -0,0 +1,145 @@
+use core::{
+    assertions::runner::AssertionOutcome,
+    client::{create_client, create_cookie_store, send_request, Response},
+    http::{
+        collection::Collection, environment::EnvironmentChain, request::Request, CollectionKey,
+        VarMap,
+    },
+    persistence::{
+        collections::open_collection, settings::load_settings, REQUESTS, TOML_EXTENSION,
+        TOML_SUFFIX,
+    },
+    scripting::{console::ConsoleEntry, send::ScriptContext},
+    transformers::request::transform_request,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+
+use crate::script::run_post_script;
+
+/// A collection opened from disk along with the options passed on the command line
+pub struct CollectionContext {
+    pub collection: Collection,
+    client: reqwest::Client,
+    /// Variables passed with `--var`, these take precedence over everything else
+    vars: Arc<VarMap>,
+}
+
+impl CollectionContext {
+    pub async fn open(
+        root: PathBuf,
+        env: Option<String>,
+        vars: Vec<(String, String)>,
+    ) -> anyhow::Result<Self> {
+        let mut collection = open_collection(root.clone(), CollectionKey::new())
+            .await
+            .with_context(|| format!("Failed to open collection at {}", root.display()))?;
+
+        if let Some(name) = env {
+            let key = collection
+                .environments
+                .find_by_name(&name)
+                .with_context(|| format!("Environment {} not found", name))?;
+            collection.active_environment = Some(key);
+        }
+
+        // Collections without their own proxy use the proxy of the app, if it was configured
+        let vars: Arc<VarMap> = Arc::new(vars.into_iter().collect());
+        let proxy = match &collection.proxy {
+            Some(proxy) => {
+                let env = collection.env_chain().with_layer(Arc::clone(&vars));
+                proxy.resolve(&env)
+            }
+            None => load_settings().await.map(|s| s.proxy).unwrap_or_default(),
+        };
+
+        // Runs start with an empty jar instead of the cookies saved by the app
+        let client = create_client(collection.disable_ssl, Some(create_cookie_store()), &proxy)?;
+
+        Ok(Self {
+            collection,
+            client,
+            vars,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.collection.path
+    }
+
+    /// Directory of the request files, request paths on the command line are relative to it
+    pub fn requests_dir(&self) -> PathBuf {
+        self.root().join(REQUESTS)
+    }
+
+    /// Path of a request file under `requests`, the `.toml` extension can be left out
+    pub fn request_path(&self, request: &Path) -> PathBuf {
+        let path = self.requests_dir().join(request);
+        if path.extension().is_some_and(|ext| ext == TOML_SUFFIX) {
+            return path;
+        }
+        let mut path = path.into_os_string();
+        path.push(TOML_EXTENSION);
+        PathBuf::from(path)
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        self.client.clone()
+    }
+
+    /// Environment for the next request, optionally with a row of iteration data
+    pub fn env_chain(&self, data: Option<Arc<VarMap>>) -> EnvironmentChain {
+        let env = self.collection.env_chain();
+        let env = match data {
+            Some(data) => env.with_layer(data),
+            None => env,
+        };
+        env.with_layer(Arc::clone(&self.vars))
+    }
+
+    /// Reads a request file and applies the collection headers, auth and scripts
+    pub async fn read_request(&self, path: &Path) -> anyhow::Result<Request> {
+        ScriptContext::for_collection(&self.collection)
+            .load_request(path, None)
+            .await
+    }
+
+    /// Sends the request and runs its post-request script, returning the script test results
+    /// and console output
+    pub async fn send(
+        &self,
+        mut req: Request,
+        env: EnvironmentChain,
+    ) -> anyhow::Result<(Response, Option<AssertionOutcome>, Vec<ConsoleEntry>)> {
+        let runtime = &self.collection.runtime_vars;
+        let post_request = req.post_request.take();
+        let script_context = ScriptContext::for_collection(&self.collection);
+
+        let mut req = transform_request(
+            self.client.clone(),
+            req,
+            env,
+            &self.collection.auth_state,
+            &script_context,
+        )
+        .await?;
+        runtime.extend(req.variables.clone());
+        *req.request.timeout_mut() = Some(self.collection.timeout);
+
+        let response = send_request(self.client.clone(), req).await?;
+        let tests = run_post_script(post_request.as_deref(), &response, runtime, &script_context);
+
+        Ok((response, tests, script_context.console().entries()))
+    }
+}
+
+pub fn parse_var(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.trim().to_owned(), value.to_owned()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("Invalid variable {}, expected key=value", s))
+}
@@ -0,0 +1,13 @@
+use core::curl::generate_curl_command;
+use std::path::PathBuf;
+
+use crate::context::CollectionContext;
+
+pub async fn curl(ctx: CollectionContext, req: PathBuf) -> anyhow::Result<()> {
+    let path = ctx.request_path(&req);
+    let req = ctx.read_request(&path).await?;
+
+    println!("{}", generate_curl_command(&req, ctx.env_chain(None)));
+
+    Ok(())
+}
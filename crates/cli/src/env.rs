@@ -0,0 +1,25 @@
+use crate::color::{color, Color};
+use crate::context::CollectionContext;
+
+pub fn list(ctx: CollectionContext) {
+    let collection = &ctx.collection;
+    let mut envs = collection.environments.entries().collect::<Vec<_>>();
+    envs.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+    if envs.is_empty() {
+        println!("{}", color("No environments", Color::DARKGRAY));
+        return;
+    }
+
+    for (key, env) in envs {
+        if collection.active_environment == Some(*key) {
+            println!(
+                "{} {}",
+                color(&env.name, Color::LIGHTGREEN),
+                color("(active)", Color::DARKGRAY)
+            );
+        } else {
+            println!("{}", env.name);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use core::persistence::collections::import_postman_collection;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+pub async fn postman(file: PathBuf, output: PathBuf) -> anyhow::Result<()> {
+    let current_dir = std::env::current_dir()?;
+    let file = current_dir.join(file);
+    let output = current_dir.join(output);
+
+    let collection = import_postman_collection(file.clone(), output)
+        .await
+        .with_context(|| format!("Failed to import {}", file.display()))?;
+
+    println!(
+        "Imported {} into {}",
+        collection.name,
+        collection.path.display()
+    );
+
+    Ok(())
+}
@@ -1,11 +1,21 @@
 pub mod color;
+pub mod context;
+pub mod curl;
+pub mod env;
+pub mod import;
+pub mod perf;
+pub mod report;
 pub mod run;
+pub mod script;
 pub mod test;
 
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use core::perf::Threshold;
+use std::{path::PathBuf, process::ExitCode};
 
-use clap::{arg, command};
+use crate::context::{parse_var, CollectionContext};
+use crate::perf::PerfOptions;
+use crate::report::Reporter;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -15,6 +25,14 @@ struct Cli {
     #[arg(short, long, value_name = "PATH", default_value = ".")]
     path: PathBuf,
 
+    /// Name of the environment to use, defaults to the collection's default environment
+    #[arg(short, long, value_name = "NAME", global = true)]
+    env: Option<String>,
+
+    /// Set a variable, overriding the environment, can be repeated
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var, global = true)]
+    vars: Vec<(String, String)>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -24,7 +42,7 @@ enum Commands {
     /// Run a request file
     #[command(arg_required_else_help = true)]
     Run {
-        /// Path to request file
+        /// Request file, relative to the requests of the collection
         request: PathBuf,
 
         /// Run in verbose mode
@@ -33,21 +51,149 @@ enum Commands {
         #[arg(short, long)]
         verbose: bool,
     },
-    /// Run a request file
+    /// Run assertions of a request file or directory
     Test {
-        /// Path to test specific file or directory
-        /// If not provided, all tests are run
+        /// Request file or directory to test, relative to the requests of the collection
+        /// If not provided, all requests are tested
         #[arg(value_name = "PATH")]
         path: Option<PathBuf>,
+
+        /// CSV or JSON file with iteration data
+        /// Tests are run once per row, with the columns available as variables
+        #[arg(short, long, value_name = "FILE")]
+        data: Option<PathBuf>,
+
+        /// Format of the test report
+        #[arg(short, long, value_enum, default_value_t = Reporter::Pretty)]
+        reporter: Reporter,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Load test request files, failing when a threshold is not met
+    Perf {
+        /// Request files sent in order by each virtual user, relative to the requests of the
+        /// collection
+        /// If not provided, the requests of the perf config are used
+        #[arg(value_name = "REQUEST")]
+        requests: Vec<PathBuf>,
+
+        /// TOML file with the load profile, requests and thresholds
+        #[arg(short, long, value_name = "FILE")]
+        config: Option<PathBuf>,
+
+        /// Number of virtual users, or the requests in flight with --rate
+        #[arg(short, long)]
+        users: Option<usize>,
+
+        /// Start this many iterations per second instead of running virtual users
+        #[arg(short, long)]
+        rate: Option<u32>,
+
+        /// Length of the test in seconds, the ramping profiles use the stages of the perf config
+        #[arg(short, long, value_name = "SECONDS")]
+        duration: Option<u64>,
+
+        /// Condition the run has to meet, such as "p95 < 300ms", "error_rate < 1%" or
+        /// "rps > 200", can be repeated
+        #[arg(short, long = "threshold", value_name = "EXPR")]
+        thresholds: Vec<Threshold>,
+    },
+    /// Print a request file as a curl command
+    #[command(arg_required_else_help = true)]
+    Curl {
+        /// Request file, relative to the requests of the collection
+        request: PathBuf,
+    },
+    /// Manage environments of the collection
+    #[command(subcommand)]
+    Env(EnvCommands),
+    /// Import a collection from another tool
+    #[command(subcommand)]
+    Import(ImportCommands),
+}
+
+#[derive(Debug, Subcommand)]
+enum EnvCommands {
+    /// List environments, marking the active one
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum ImportCommands {
+    /// Import a Postman v2.1 collection
+    #[command(arg_required_else_help = true)]
+    Postman {
+        /// Path to the exported Postman collection JSON file
+        file: PathBuf,
+
+        /// Directory to create the collection in
+        output: PathBuf,
     },
 }
 
 #[tokio::main]
-pub async fn main() -> anyhow::Result<()> {
+pub async fn main() -> anyhow::Result<ExitCode> {
     let cli = Cli::parse();
 
+    if let Commands::Import(ImportCommands::Postman { file, output }) = cli.command {
+        import::postman(file, output).await?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let root = std::env::current_dir()?.join(cli.path);
+    let ctx = CollectionContext::open(root, cli.env, cli.vars).await?;
+
     match cli.command {
-        Commands::Run { request, verbose } => run::run(cli.path, request, verbose).await,
-        Commands::Test { path } => test::test(cli.path, path.unwrap_or_default()).await,
+        Commands::Run { request, verbose } => {
+            run::run(ctx, request, verbose).await?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Test {
+            path,
+            data,
+            reporter,
+            output,
+        } => {
+            let passed = test::test(ctx, path, data, reporter, output).await?;
+            Ok(if passed {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+        Commands::Perf {
+            requests,
+            config,
+            users,
+            rate,
+            duration,
+            thresholds,
+        } => {
+            let options = PerfOptions {
+                requests,
+                config,
+                users,
+                rate,
+                duration,
+                thresholds,
+            };
+            let passed = perf::perf(ctx, options).await?;
+            Ok(if passed {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            })
+        }
+        Commands::Curl { request } => {
+            curl::curl(ctx, request).await?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Env(EnvCommands::List) => {
+            env::list(ctx);
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Import(_) => unreachable!("handled before opening the collection"),
     }
 }
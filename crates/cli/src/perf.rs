@@ -0,0 +1,164 @@
+use core::{
+    perf::{LoadProfile, PerfConfig, PerfRunner, PerfStats, ScenarioStep, Threshold},
+    persistence::perf::{read_perf_config, EncodedPerfConfig},
+    scripting::send::ScriptContext,
+    utils::fmt_duration,
+};
+use std::{fmt::Write, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{bail, Context};
+use tokio::sync::mpsc;
+
+use crate::color::{color, Color};
+use crate::context::CollectionContext;
+
+/// Options passed on the command line, these take precedence over the perf config file
+pub struct PerfOptions {
+    pub requests: Vec<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub users: Option<usize>,
+    pub rate: Option<u32>,
+    pub duration: Option<u64>,
+    pub thresholds: Vec<Threshold>,
+}
+
+/// Runs the load test and prints its summary, returns false if any threshold failed
+pub async fn perf(ctx: CollectionContext, options: PerfOptions) -> anyhow::Result<bool> {
+    let current_dir = std::env::current_dir()?;
+    let mut encoded = match &options.config {
+        Some(path) => read_perf_config(&current_dir.join(path)).await?,
+        None => EncodedPerfConfig::default(),
+    };
+
+    let requests = if options.requests.is_empty() {
+        std::mem::take(&mut encoded.requests)
+    } else {
+        options.requests
+    };
+    if requests.is_empty() {
+        bail!("No requests to run, pass request files or list them in the perf config");
+    }
+
+    let mut config = PerfConfig::from(encoded);
+    if let Some(users) = options.users {
+        config.concurrency = users;
+    }
+    if let Some(rate) = options.rate {
+        config.profile = LoadProfile::ConstantRate;
+        config.rate = rate;
+    }
+    if let Some(duration) = options.duration {
+        config.duration = Duration::from_secs(duration);
+    }
+    config.thresholds.extend(options.thresholds);
+
+    let mut steps = Vec::with_capacity(requests.len());
+    for request in requests {
+        let path = ctx.request_path(&request);
+        let req = ctx
+            .read_request(&path)
+            .await
+            .with_context(|| format!("Failed to load request {}", request.display()))?;
+        steps.push(ScenarioStep::new(
+            request.to_string_lossy().to_string(),
+            req,
+        ));
+    }
+
+    let msg = format!(
+        "Running {} for {}s",
+        config.profile,
+        config.total_duration().as_secs()
+    );
+    eprintln!("{}", color(&msg, Color::DARKGRAY));
+
+    let runner = PerfRunner::new(ctx.client(), config)
+        .with_auth_state(Arc::clone(&ctx.collection.auth_state))
+        .with_script_context(ScriptContext::for_collection(&ctx.collection));
+
+    // Progress is only shown by the app
+    let (sender, mut receiver) = mpsc::channel(100);
+    let progress = tokio::spawn(async move { while receiver.recv().await.is_some() {} });
+    let result = runner.run(steps, ctx.env_chain(None), sender).await;
+    progress.abort();
+
+    let stats = result?.calculate_stats();
+    print!("{}", summary(&stats));
+
+    Ok(stats.thresholds_passed())
+}
+
+fn summary(stats: &PerfStats) -> String {
+    let mut out = String::new();
+    let label = |name: &str| color(&format!("{:<12}", name), Color::DARKGRAY);
+    let latency = |duration: Option<Duration>| duration.map_or("N/A".to_string(), fmt_duration);
+
+    let _ = writeln!(
+        out,
+        "{} {} ({} failed, {:.2}%)",
+        label("Requests"),
+        stats.total_requests,
+        stats.failed_requests,
+        stats.error_rate() * 100.0
+    );
+    let _ = writeln!(
+        out,
+        "{} {:.2}s",
+        label("Duration"),
+        stats.total_duration.as_secs_f64()
+    );
+    let _ = writeln!(
+        out,
+        "{} {:.1} req/s",
+        label("Throughput"),
+        stats.requests_per_second
+    );
+    let _ = writeln!(
+        out,
+        "{} avg {}  p50 {}  p95 {}  p99 {}  max {}",
+        label("Latency"),
+        latency(stats.avg),
+        latency(stats.p50),
+        latency(stats.p95),
+        latency(stats.p99),
+        latency(stats.max)
+    );
+
+    if stats.steps.len() > 1 {
+        let _ = writeln!(out, "\n{}", color("Steps", Color::SKYBLUE));
+        for (idx, step) in stats.steps.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  {}. {}  {} requests, {} failed, p95 {}",
+                idx + 1,
+                step.name,
+                step.total_requests,
+                step.failed_requests,
+                latency(step.p95)
+            );
+        }
+    }
+
+    if !stats.thresholds.is_empty() {
+        let _ = writeln!(out, "\n{}", color("Thresholds", Color::SKYBLUE));
+        for result in &stats.thresholds {
+            let (mark, clr) = if result.passed {
+                ("✓", Color::LIGHTGREEN)
+            } else {
+                ("✗", Color::RED)
+            };
+            let msg = format!("{} {}", mark, result.threshold);
+            let _ = writeln!(
+                out,
+                "  {} {}",
+                color(&msg, clr),
+                color(
+                    &format!("(actual {})", result.actual_label()),
+                    Color::DARKGRAY
+                )
+            );
+        }
+    }
+
+    out
+}
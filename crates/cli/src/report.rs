@@ -0,0 +1,457 @@
+use core::assertions::runner::{AssertionOutcome, Description, MatcherResult};
+use std::{fmt::Write, time::Duration};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::color::{color, Color};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Reporter {
+    #[default]
+    Pretty,
+    Junit,
+    Json,
+    Tap,
+}
+
+/// Result of running a single request file
+pub struct TestCase {
+    /// Path of the request file relative to the collection
+    pub name: String,
+    /// Iteration data row the request ran with, if any
+    pub iteration: Option<usize>,
+    pub duration: Duration,
+    pub result: Result<Vec<AssertionOutcome>, String>,
+}
+
+impl TestCase {
+    fn title(&self) -> String {
+        match self.iteration {
+            Some(iteration) => format!("{} [{}]", self.name, iteration + 1),
+            None => self.name.clone(),
+        }
+    }
+
+    fn failures(&self) -> impl Iterator<Item = (&str, &str, &Description)> {
+        let outcomes: &[AssertionOutcome] = match &self.result {
+            Ok(outcomes) => outcomes,
+            Err(_) => &[],
+        };
+
+        outcomes.iter().flat_map(|assertion| {
+            assertion
+                .results
+                .iter()
+                .filter_map(|cond| match &cond.result {
+                    MatcherResult::Failed(des) => {
+                        Some((assertion.name.as_str(), cond.name.as_str(), des))
+                    }
+                    MatcherResult::Passed => None,
+                })
+        })
+    }
+
+    pub fn passed(&self) -> bool {
+        self.result.is_ok() && self.failures().next().is_none()
+    }
+}
+
+/// Renders the report, `colored` adds terminal colors to the pretty report
+pub fn render(reporter: Reporter, cases: &[TestCase], colored: bool) -> String {
+    match reporter {
+        Reporter::Pretty => pretty(cases, colored),
+        Reporter::Junit => junit(cases),
+        Reporter::Json => json(cases),
+        Reporter::Tap => tap(cases),
+    }
+}
+
+fn actual(des: &Description) -> String {
+    des.actual.as_ref().unwrap_or(&Value::Null).to_string()
+}
+
+fn pretty(cases: &[TestCase], colored: bool) -> String {
+    let color = |text: &str, clr: Color| {
+        if colored {
+            color(text, clr)
+        } else {
+            text.to_string()
+        }
+    };
+    let mut out = String::new();
+
+    for case in cases {
+        let outcomes = match &case.result {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                let msg = format!("{} - error: {}", case.title(), e);
+                let _ = writeln!(out, "{}", color(&msg, Color::RED));
+                continue;
+            }
+        };
+        let _ = writeln!(out, "{} - {} assertions", case.title(), outcomes.len());
+
+        let indent = Indent::new();
+        for assertion in outcomes {
+            let indent = indent.inc();
+            let _ = writeln!(out, "{:id$}Assert {}", "", assertion.name, id = indent.v);
+
+            for cond in assertion.results.iter() {
+                let indent = indent.inc();
+                let msg = format!("{:id$}{}", "", cond.name, id = indent.v);
+                match &cond.result {
+                    MatcherResult::Passed => {
+                        let _ = writeln!(out, "{}", color(&msg, Color::LIGHTGREEN));
+                    }
+                    MatcherResult::Failed(des) => {
+                        let _ = writeln!(out, "{}", color(&msg, Color::RED));
+
+                        let indent = indent.inc();
+                        let msg = format!("{:id$}Summary: {}", "", des.summary, id = indent.v);
+                        let _ = writeln!(out, "{}", color(&msg, Color::YELLOW));
+                        let _ = writeln!(
+                            out,
+                            "{:id$}Actual: {}",
+                            "",
+                            color(&actual(des), Color::RED),
+                            id = indent.v
+                        );
+                        let _ = writeln!(
+                            out,
+                            "{:id$}Expected: {}",
+                            "",
+                            color(&des.expected.to_string(), Color::LIGHTGREEN),
+                            id = indent.v
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    let passed = cases.iter().filter(|c| c.passed()).count();
+    let summary = format!("{} passed, {} failed", passed, cases.len() - passed);
+    let clr = if passed == cases.len() {
+        Color::LIGHTGREEN
+    } else {
+        Color::RED
+    };
+    let _ = writeln!(out, "\n{}", color(&summary, clr));
+
+    out
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn junit(cases: &[TestCase]) -> String {
+    let failures = cases
+        .iter()
+        .filter(|c| c.result.is_ok() && !c.passed())
+        .count();
+    let errors = cases.iter().filter(|c| c.result.is_err()).count();
+    let time = cases.iter().map(|c| c.duration).sum::<Duration>();
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        out,
+        r#"<testsuites name="sanchaar" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+        cases.len(),
+        failures,
+        errors,
+        time.as_secs_f64()
+    );
+    let _ = writeln!(
+        out,
+        r#"  <testsuite name="sanchaar" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+        cases.len(),
+        failures,
+        errors,
+        time.as_secs_f64()
+    );
+
+    for case in cases {
+        let _ = writeln!(
+            out,
+            r#"    <testcase name="{}" classname="sanchaar" time="{:.3}">"#,
+            escape_xml(&case.title()),
+            case.duration.as_secs_f64()
+        );
+        if let Err(e) = &case.result {
+            let _ = writeln!(
+                out,
+                r#"      <error message="{}" type="error"/>"#,
+                escape_xml(e)
+            );
+        }
+        for (assertion, condition, des) in case.failures() {
+            let _ = writeln!(
+                out,
+                r#"      <failure message="{}" type="assertion">Assert {} {}&#10;Expected: {}&#10;Actual: {}</failure>"#,
+                escape_xml(&des.summary),
+                escape_xml(assertion),
+                escape_xml(condition),
+                escape_xml(&des.expected.to_string()),
+                escape_xml(&actual(des)),
+            );
+        }
+        let _ = writeln!(out, "    </testcase>");
+    }
+
+    let _ = writeln!(out, "  </testsuite>");
+    let _ = writeln!(out, "</testsuites>");
+    out
+}
+
+fn json(cases: &[TestCase]) -> String {
+    let tests = cases
+        .iter()
+        .map(|case| {
+            let status = match (&case.result, case.passed()) {
+                (Err(_), _) => "error",
+                (Ok(_), true) => "passed",
+                (Ok(_), false) => "failed",
+            };
+            let assertions = case.result.as_ref().map(|outcomes| {
+                outcomes
+                    .iter()
+                    .map(|assertion| {
+                        let conditions = assertion
+                            .results
+                            .iter()
+                            .map(|cond| match &cond.result {
+                                MatcherResult::Passed => json!({
+                                    "name": cond.name,
+                                    "passed": true,
+                                }),
+                                MatcherResult::Failed(des) => json!({
+                                    "name": cond.name,
+                                    "passed": false,
+                                    "summary": des.summary,
+                                    "expected": des.expected,
+                                    "actual": des.actual,
+                                }),
+                            })
+                            .collect::<Vec<_>>();
+                        json!({ "name": assertion.name, "conditions": conditions })
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            json!({
+                "name": case.name,
+                "iteration": case.iteration,
+                "status": status,
+                "duration_ms": case.duration.as_millis() as u64,
+                "error": case.result.as_ref().err(),
+                "assertions": assertions.unwrap_or_default(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let passed = cases.iter().filter(|c| c.passed()).count();
+    let report = json!({
+        "summary": {
+            "tests": cases.len(),
+            "passed": passed,
+            "failed": cases.len() - passed,
+        },
+        "tests": tests,
+    });
+
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+fn tap(cases: &[TestCase]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "TAP version 13");
+    let _ = writeln!(out, "1..{}", cases.len());
+
+    for (index, case) in cases.iter().enumerate() {
+        let status = if case.passed() { "ok" } else { "not ok" };
+        let _ = writeln!(out, "{} {} - {}", status, index + 1, case.title());
+
+        if case.passed() {
+            continue;
+        }
+
+        let _ = writeln!(out, "  ---");
+        match &case.result {
+            Err(e) => {
+                let _ = writeln!(out, "  error: {}", Value::from(e.as_str()));
+            }
+            Ok(_) => {
+                let _ = writeln!(out, "  failures:");
+                for (assertion, condition, des) in case.failures() {
+                    let name = format!("{} {}", assertion, condition);
+                    let _ = writeln!(out, "    - condition: {}", Value::from(name));
+                    let _ = writeln!(out, "      summary: {}", Value::from(des.summary.as_str()));
+                    let _ = writeln!(out, "      expected: {}", des.expected);
+                    let _ = writeln!(out, "      actual: {}", actual(des));
+                }
+            }
+        }
+        let _ = writeln!(out, "  ...");
+    }
+
+    out
+}
+
+struct Indent {
+    v: usize,
+}
+
+impl Indent {
+    fn new() -> Self {
+        Self { v: 0 }
+    }
+
+    fn inc(&self) -> Self {
+        Self { v: self.v + 2 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::assertions::runner::ConditionResult;
+
+    use super::*;
+
+    fn case(name: &str, result: Result<Vec<AssertionOutcome>, String>) -> TestCase {
+        TestCase {
+            name: name.to_string(),
+            iteration: None,
+            duration: Duration::from_millis(120),
+            result,
+        }
+    }
+
+    fn assertion(name: &str, condition: &str, result: MatcherResult) -> AssertionOutcome {
+        AssertionOutcome {
+            name: name.to_string(),
+            results: vec![ConditionResult {
+                name: condition.to_string(),
+                result,
+            }],
+        }
+    }
+
+    fn cases() -> Vec<TestCase> {
+        let failed = MatcherResult::Failed(Description {
+            summary: "expected <404> & got \"200\"".to_string(),
+            expected: json!(404),
+            actual: Some(json!(200)),
+        });
+
+        vec![
+            case(
+                "users/list.toml",
+                Ok(vec![assertion("status", "eq", MatcherResult::Passed)]),
+            ),
+            case(
+                "users/<get> & 'one'.toml",
+                Ok(vec![assertion("status", "eq", failed)]),
+            ),
+            case(
+                "users/delete.toml",
+                Err("Connection refused <localhost>".to_string()),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_junit_escapes_names_and_messages() {
+        let xml = render(Reporter::Junit, &cases(), false);
+
+        assert!(xml.contains(r#"<testcase name="users/&lt;get&gt; &amp; &apos;one&apos;.toml""#));
+        assert!(xml.contains(
+            r#"<failure message="expected &lt;404&gt; &amp; got &quot;200&quot;" type="assertion">"#
+        ));
+        assert!(
+            xml.contains(r#"<error message="Connection refused &lt;localhost&gt;" type="error"/>"#)
+        );
+    }
+
+    #[test]
+    fn test_junit_counts() {
+        let xml = render(Reporter::Junit, &cases(), false);
+
+        assert!(xml.contains(
+            r#"<testsuite name="sanchaar" tests="3" failures="1" errors="1" time="0.360">"#
+        ));
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+    }
+
+    #[test]
+    fn test_tap_plan_and_numbering() {
+        let tap = render(Reporter::Tap, &cases(), false);
+        let lines: Vec<_> = tap.lines().filter(|l| !l.starts_with(' ')).collect();
+
+        assert_eq!(
+            lines,
+            [
+                "TAP version 13",
+                "1..3",
+                "ok 1 - users/list.toml",
+                "not ok 2 - users/<get> & 'one'.toml",
+                "not ok 3 - users/delete.toml",
+            ]
+        );
+        assert!(tap.contains("  error: \"Connection refused <localhost>\""));
+        assert!(tap.contains("    - condition: \"status eq\""));
+    }
+
+    #[test]
+    fn test_pretty_colors() {
+        let plain = render(Reporter::Pretty, &cases(), false);
+        let colored = render(Reporter::Pretty, &cases(), true);
+
+        assert!(!plain.contains('\x1B'));
+        assert!(plain.ends_with("\n1 passed, 2 failed\n"));
+        assert!(colored.contains("\x1B["));
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let mut cases = cases();
+        cases[0].iteration = Some(1);
+        let report: Value = serde_json::from_str(&render(Reporter::Json, &cases, false)).unwrap();
+
+        assert_eq!(
+            report["summary"],
+            json!({ "tests": 3, "passed": 1, "failed": 2 })
+        );
+
+        let tests = report["tests"].as_array().unwrap();
+        let status: Vec<_> = tests.iter().map(|t| t["status"].clone()).collect();
+        assert_eq!(status, [json!("passed"), json!("failed"), json!("error")]);
+        assert_eq!(tests[0]["iteration"], json!(1));
+        assert_eq!(tests[0]["duration_ms"], json!(120));
+        assert_eq!(
+            tests[1]["assertions"][0]["conditions"][0],
+            json!({
+                "name": "eq",
+                "passed": false,
+                "summary": "expected <404> & got \"200\"",
+                "expected": 404,
+                "actual": 200,
+            })
+        );
+        assert_eq!(tests[2]["error"], json!("Connection refused <localhost>"));
+        assert_eq!(tests[2]["assertions"], json!([]));
+    }
+}
@@ -1,27 +1,46 @@
 use colored_json::prelude::ToColoredJson;
 use core::{
-    client::{create_client, send_request, ContentType, Response},
-    http::environment::EnvironmentChain,
-    persistence::request::read_request,
-    transformers::request::transform_request,
+    assertions::runner::MatcherResult,
+    client::{ContentType, Response},
+    scripting::console::ConsoleLevel,
     utils::fmt_duration,
 };
-use std::{env, path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 use humansize::{format_size, BINARY};
 
 use crate::color::{color, Color};
+use crate::context::CollectionContext;
 
-pub async fn run(root: PathBuf, req: PathBuf, verbose: bool) -> anyhow::Result<()> {
-    let current_dir = env::current_dir()?;
-    let root = current_dir.join(root);
+pub async fn run(ctx: CollectionContext, req: PathBuf, verbose: bool) -> anyhow::Result<()> {
+    let path = ctx.request_path(&req);
+    let req = ctx.read_request(&path).await?;
+    let (response, tests, console) = ctx.send(req, ctx.env_chain(None)).await?;
 
-    let path = root.join(req);
-    let req = read_request(&path).await?;
+    if verbose && !console.is_empty() {
+        println!("{}", color("Console:", Color::DARKGRAY));
+        for entry in console.iter() {
+            let clr = match entry.level {
+                ConsoleLevel::Log | ConsoleLevel::Info => Color::LIGHTGRAY,
+                ConsoleLevel::Warn => Color::YELLOW,
+                ConsoleLevel::Error => Color::RED,
+            };
+            let prefix = format!("[{}] {}", entry.script, entry.level);
+            println!(
+                "  {} {}",
+                color(&prefix, Color::DARKGRAY),
+                color(&entry.message, clr)
+            );
+        }
+        println!();
+    }
 
-    let client = create_client(false, Default::default());
-    let req = transform_request(client.clone(), req, EnvironmentChain::new()).await?;
-    let response = send_request(client, req).await?;
+    for cond in tests.iter().flat_map(|outcome| outcome.results.iter()) {
+        if let MatcherResult::Failed(des) = &cond.result {
+            let msg = format!("Test failed: {} - {}", cond.name, des.summary);
+            eprintln!("{}", color(&msg, Color::RED));
+        }
+    }
 
     let Response {
         status,
@@ -64,7 +83,7 @@ pub async fn run(root: PathBuf, req: PathBuf, verbose: bool) -> anyhow::Result<(
             let json = String::from_utf8(data)?;
             println!("{}", json.to_colored_json_auto()?);
         }
-        ContentType::Text => {
+        ContentType::Text | ContentType::XML | ContentType::Html => {
             let text = String::from_utf8(data)?;
             println!("{}", text);
         }
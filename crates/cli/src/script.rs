@@ -0,0 +1,23 @@
+use core::{
+    assertions::runner::{script_outcome, AssertionOutcome},
+    client::Response,
+    http::environment::RuntimeVariables,
+    scripting::{runner::run_post_request_script, send::ScriptContext},
+};
+/// Runs the post-request script, keeping the variables it sets for later requests
+/// Returns the results of the tests in the script
+pub fn run_post_script(
+    script: Option<&str>,
+    response: &Response,
+    runtime: &RuntimeVariables,
+    context: &ScriptContext,
+) -> Option<AssertionOutcome> {
+    let script = script.filter(|s| !s.trim().is_empty())?;
+
+    let tests =
+        run_post_request_script(script, response, None, context).map(|(variables, tests)| {
+            runtime.extend(variables.all());
+            tests
+        });
+    script_outcome(tests)
+}
@@ -1,119 +1,124 @@
 use core::{
-    assertions::{self, runner::MatcherResult},
-    client::{create_client, send_request},
-    http::environment::EnvironmentChain,
-    persistence::request::read_request,
-    transformers::request::transform_request,
+    assertions,
+    http::VarMap,
+    persistence::{FOLDER_ROOT_FILE, TOML_SUFFIX},
+    runner::load_iterations,
+};
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
 };
-use std::path::PathBuf;
-
-use anyhow::Context;
-use hcl::Value;
-
-use crate::color::{color, Color};
 
-pub async fn test(root: PathBuf, path: PathBuf) -> anyhow::Result<()> {
+use crate::context::CollectionContext;
+use crate::report::{render, Reporter, TestCase};
+
+/// Runs the tests and reports them, returns false if any test failed or errored
+pub async fn test(
+    ctx: CollectionContext,
+    path: Option<PathBuf>,
+    data: Option<PathBuf>,
+    reporter: Reporter,
+    output: Option<PathBuf>,
+) -> anyhow::Result<bool> {
     let current_dir = std::env::current_dir()?;
-    let root = current_dir.join(root);
-
-    let path = root.join(path);
-
-    let client = create_client(false, Default::default());
+    // Without a path every request of the collection is tested
+    let path = match path {
+        Some(path) if ctx.requests_dir().join(&path).is_dir() => ctx.requests_dir().join(path),
+        Some(path) => ctx.request_path(&path),
+        None => ctx.requests_dir(),
+    };
+
+    let iterations = match &data {
+        Some(data) => load_iterations(&current_dir.join(data)).await?,
+        None => vec![VarMap::new()],
+    };
 
     let file = tokio::fs::File::open(&path).await?;
-    if file.metadata().await?.is_dir() {
-        walk_dir(client, &path).await?;
-    } else {
-        test_file(client, &path).await?;
-    }
-
-    Ok(())
-}
-
-async fn walk_dir(client: reqwest::Client, path: &PathBuf) -> anyhow::Result<()> {
-    let mut entries = tokio::fs::read_dir(path).await?;
-
-    while let Some(entry) = entries.next_entry().await? {
-        let entry_path = entry.path();
-        let file = tokio::fs::File::open(&entry_path).await?;
-        if file.metadata().await?.is_dir() {
-            Box::pin(walk_dir(client.clone(), &entry_path)).await?;
+    let is_dir = file.metadata().await?.is_dir();
+
+    let mut cases = Vec::new();
+    for (index, row) in iterations.into_iter().enumerate() {
+        let iteration = data.is_some().then_some(index);
+        let row = Arc::new(row);
+        if is_dir {
+            walk_dir(&ctx, &path, &row, iteration, &mut cases).await?;
         } else {
-            test_file(client.clone(), &entry_path).await?;
+            cases.push(test_file(&ctx, &path, &row, iteration).await);
         }
     }
 
-    Ok(())
-}
-
-async fn test_file(client: reqwest::Client, path: &PathBuf) -> anyhow::Result<()> {
-    let file_name = path
-        .file_name()
-        .context("Invalid path")?
-        .to_str()
-        .context("Invalid file name")?;
-
-    let req = read_request(path).await?;
-
-    let assertions = req.assertions.clone();
-
-    let req = transform_request(client.clone(), req, EnvironmentChain::new()).await?;
-    let response = send_request(client, req).await?;
-
-    let result = assertions::run(&response, &assertions);
+    // Colors only when the report goes to a terminal
+    let colored = output.is_none() && std::io::stdout().is_terminal();
+    let report = render(reporter, &cases, colored);
+    match output {
+        Some(output) => tokio::fs::write(current_dir.join(output), report).await?,
+        None => print!("{}", report),
+    }
 
-    println!("{} - {} assertions", file_name, result.len());
+    Ok(cases.iter().all(TestCase::passed))
+}
 
-    let indent = Indent::new();
-    for assertion in result {
-        let indent = indent.inc();
-        println!("{:id$}Assert {}", "", assertion.name, id = indent.v);
+async fn walk_dir(
+    ctx: &CollectionContext,
+    path: &PathBuf,
+    data: &Arc<VarMap>,
+    iteration: Option<usize>,
+    cases: &mut Vec<TestCase>,
+) -> anyhow::Result<()> {
+    // Sorted so the requests, and the variables they pass on, run in the same order everywhere
+    let mut dir = tokio::fs::read_dir(path).await?;
+    let mut entries = Vec::new();
+    while let Some(entry) = dir.next_entry().await? {
+        entries.push(entry);
+    }
+    entries.sort_by_key(|entry| entry.file_name());
 
-        for cond in assertion.results {
-            let indent = indent.inc();
-            match cond.result {
-                MatcherResult::Passed => {
-                    let msg = format!("{:id$}{}", "", cond.name, id = indent.v);
-                    println!("{}", color(&msg, Color::LIGHTGREEN));
-                }
-                MatcherResult::Failed(des) => {
-                    let msg = format!("{:id$}{}", "", cond.name, id = indent.v);
-                    println!("{}", color(&msg, Color::RED));
-                    {
-                        let indent = indent.inc();
-                        let msg = format!("{:id$}Summary: {}", "", des.summary, id = indent.v);
-                        println!("{}", color(&msg, Color::YELLOW));
-                        println!(
-                            "{:id$}Actual: {}",
-                            "",
-                            color(&des.actual.unwrap_or(Value::Null).to_string(), Color::RED),
-                            id = indent.v
-                        );
-                        println!(
-                            "{:id$}Expected: {}",
-                            "",
-                            color(&des.expected.to_string(), Color::LIGHTGREEN),
-                            id = indent.v
-                        );
-                    }
-                }
-            }
+    for entry in entries {
+        let entry_path = entry.path();
+        if tokio::fs::metadata(&entry_path).await?.is_dir() {
+            Box::pin(walk_dir(ctx, &entry_path, data, iteration, cases)).await?;
+        } else if entry.file_name() != FOLDER_ROOT_FILE
+            && entry_path.extension().is_some_and(|ext| ext == TOML_SUFFIX)
+        {
+            cases.push(test_file(ctx, &entry_path, data, iteration).await);
         }
     }
 
     Ok(())
 }
 
-struct Indent {
-    v: usize,
-}
-
-impl Indent {
-    fn new() -> Self {
-        Self { v: 0 }
+async fn test_file(
+    ctx: &CollectionContext,
+    path: &Path,
+    data: &Arc<VarMap>,
+    iteration: Option<usize>,
+) -> TestCase {
+    let name = path
+        .strip_prefix(ctx.requests_dir())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+
+    let start = Instant::now();
+    let result = async {
+        let req = ctx.read_request(path).await?;
+        let assertions = req.assertions.clone();
+
+        let env = ctx.env_chain(Some(Arc::clone(data)));
+        let (response, tests, _) = ctx.send(req, env).await?;
+
+        let mut outcomes = assertions::run(&response, &assertions);
+        outcomes.extend(tests);
+        anyhow::Ok(outcomes)
     }
+    .await;
 
-    fn inc(&self) -> Self {
-        Self { v: self.v + 2 }
+    TestCase {
+        name,
+        iteration,
+        duration: start.elapsed(),
+        result: result.map_err(|e| format!("{:#}", e)),
     }
 }
@@ -82,6 +82,28 @@ pub fn run(response: &Response, assertions: &Assertions) -> Vec<AssertionOutcome
     report
 }
 
+/// Reports the `test` calls of a post-request script as one more assertion,
+/// a script that fails to run is reported as a failed test
+pub fn script_outcome(tests: anyhow::Result<Vec<ConditionResult>>) -> Option<AssertionOutcome> {
+    let results = match tests {
+        Ok(tests) if tests.is_empty() => return None,
+        Ok(tests) => tests,
+        Err(e) => vec![ConditionResult {
+            name: "Post-request script".to_string(),
+            result: MatcherResult::Failed(Description {
+                summary: format!("{:#}", e),
+                expected: Value::Null,
+                actual: None,
+            }),
+        }],
+    };
+
+    Some(AssertionOutcome {
+        name: "Script Tests".to_string(),
+        results,
+    })
+}
+
 fn to_value(value: &toml::Value) -> Value {
     match value {
         toml::Value::Boolean(b) => Value::Bool(*b),
@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use md5::Md5;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, WWW_AUTHENTICATE};
+use reqwest::{Client, Request, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Digest credentials of a request along with the challenges answered in its collection
+#[derive(Debug, Clone)]
+pub struct DigestAuth {
+    pub credentials: DigestCredentials,
+    pub challenges: Arc<DigestChallenges>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedChallenge {
+    challenge: DigestChallenge,
+    /// Times the nonce has been used
+    nc: u32,
+    cnonce: String,
+}
+
+#[derive(Debug, Default)]
+struct ChallengeCache {
+    challenges: HashMap<(String, String), CachedChallenge>,
+    /// Realm each origin last challenged with
+    realms: HashMap<String, String>,
+}
+
+/// Challenges received per origin and realm, so later requests are authorized up front
+/// reusing the nonce instead of a new 401 round trip
+#[derive(Debug, Default)]
+pub struct DigestChallenges {
+    cache: Mutex<ChallengeCache>,
+}
+
+impl DigestChallenges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Challenge to authorize the next request to the origin with, counting the nonce use
+    fn next(&self, origin: &str) -> Option<CachedChallenge> {
+        let mut cache = self.cache.lock().ok()?;
+        let realm = cache.realms.get(origin)?.clone();
+        let cached = cache.challenges.get_mut(&(origin.to_owned(), realm))?;
+        cached.nc += 1;
+        Some(cached.clone())
+    }
+
+    /// Replaces the challenge of the origin and realm, the nonce is used for the first time
+    fn insert(&self, origin: &str, challenge: DigestChallenge) -> CachedChallenge {
+        let cached = CachedChallenge {
+            challenge,
+            nc: 1,
+            cnonce: cnonce(),
+        };
+        if let Ok(mut cache) = self.cache.lock() {
+            let realm = cached.challenge.realm.clone();
+            cache.realms.insert(origin.to_owned(), realm.clone());
+            cache
+                .challenges
+                .insert((origin.to_owned(), realm), cached.clone());
+        }
+        cached
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Self::Md5),
+            "MD5-SESS" => Some(Self::Md5Sess),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-256-SESS" => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_session(&self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+
+    fn strength(&self) -> u8 {
+        match self {
+            Self::Md5 | Self::Md5Sess => 0,
+            Self::Sha256 | Self::Sha256Sess => 1,
+        }
+    }
+
+    fn hash(&self, data: &str) -> String {
+        match self {
+            Self::Md5 | Self::Md5Sess => hex::encode(Md5::digest(data.as_bytes())),
+            Self::Sha256 | Self::Sha256Sess => hex::encode(Sha256::digest(data.as_bytes())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: Algorithm,
+    qop_auth: bool,
+    userhash: bool,
+}
+
+impl DigestChallenge {
+    /// Picks the strongest supported Digest challenge from the response headers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(split_challenges)
+            .filter_map(|challenge| Self::parse(&challenge))
+            .max_by_key(|challenge| challenge.algorithm.strength())
+    }
+
+    fn parse(header: &str) -> Option<Self> {
+        let (scheme, params) = header.trim().split_once(char::is_whitespace)?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let params = parse_params(params);
+        let algorithm = match params.get("algorithm") {
+            Some(algorithm) => Algorithm::parse(algorithm)?,
+            None => Algorithm::Md5,
+        };
+
+        // Only qop=auth is supported, auth-int would need the body hash
+        let qop_auth = match params.get("qop") {
+            Some(qop) => {
+                if !qop
+                    .split(',')
+                    .any(|q| q.trim().eq_ignore_ascii_case("auth"))
+                {
+                    return None;
+                }
+                true
+            }
+            None => false,
+        };
+
+        Some(Self {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce").cloned()?,
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop_auth,
+            userhash: params
+                .get("userhash")
+                .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        })
+    }
+
+    /// Builds the Authorization header value for a request, `nc` is the number of
+    /// times this nonce has been used including this request
+    pub fn authorization(
+        &self,
+        credentials: &DigestCredentials,
+        method: &str,
+        uri: &str,
+        nc: u32,
+        cnonce: &str,
+    ) -> String {
+        let algorithm = self.algorithm;
+        let nc = format!("{:08x}", nc);
+
+        let mut ha1 = algorithm.hash(&format!(
+            "{}:{}:{}",
+            credentials.username, self.realm, credentials.password
+        ));
+        if algorithm.is_session() {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+
+        let response = if self.qop_auth {
+            algorithm.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, self.nonce, nc, cnonce, ha2
+            ))
+        } else {
+            algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        };
+
+        let username = if self.userhash {
+            algorithm.hash(&format!("{}:{}", credentials.username, self.realm))
+        } else {
+            credentials.username.clone()
+        };
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            quote(&username),
+            quote(&self.realm),
+            quote(&self.nonce),
+            quote(uri),
+            algorithm.as_str(),
+            response
+        );
+        if self.qop_auth {
+            header.push_str(&format!(r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, quote(opaque)));
+        }
+        if self.userhash {
+            header.push_str(", userhash=true");
+        }
+
+        header
+    }
+}
+
+/// Sends the request, authorized with the cached challenge of the origin if there is one.
+/// A 401, which includes a stale nonce, replaces the cached challenge and the request is retried.
+pub async fn execute(
+    client: &Client,
+    mut request: Request,
+    auth: &DigestAuth,
+) -> anyhow::Result<reqwest::Response> {
+    let origin = request.url().origin().ascii_serialization();
+    if let Some(cached) = auth.challenges.next(&origin) {
+        authorize(&mut request, &cached, &auth.credentials)?;
+    }
+
+    // Streamed bodies can't be replayed, send as is and let the server decide
+    let Some(mut retry) = request.try_clone() else {
+        return Ok(client.execute(request).await?);
+    };
+
+    let response = client.execute(request).await?;
+    if response.status() != StatusCode::UNAUTHORIZED {
+        return Ok(response);
+    }
+
+    let Some(challenge) = DigestChallenge::from_headers(response.headers()) else {
+        return Ok(response);
+    };
+
+    let cached = auth.challenges.insert(&origin, challenge);
+    authorize(&mut retry, &cached, &auth.credentials)?;
+
+    Ok(client.execute(retry).await?)
+}
+
+fn authorize(
+    request: &mut Request,
+    cached: &CachedChallenge,
+    credentials: &DigestCredentials,
+) -> anyhow::Result<()> {
+    let authorization = cached.challenge.authorization(
+        credentials,
+        request.method().as_str(),
+        &request_uri(request.url()),
+        cached.nc,
+        &cached.cnonce,
+    );
+    request.headers_mut().insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&authorization).context("Invalid digest credentials")?,
+    );
+    Ok(())
+}
+
+fn request_uri(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_owned(),
+    }
+}
+
+fn cnonce() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Splits a header holding several challenges, like `Digest a=1, b=2, Digest c=3`
+fn split_challenges(header: &str) -> Vec<String> {
+    let mut challenges: Vec<String> = Vec::new();
+    for part in split_unquoted(header, ',') {
+        let part = part.trim();
+        let starts_challenge = part
+            .split_once(char::is_whitespace)
+            .is_some_and(|(scheme, rest)| !scheme.contains('=') && rest.contains('='))
+            || (!part.contains('=') && !part.is_empty());
+
+        match challenges.last_mut() {
+            Some(last) if !starts_challenge => {
+                last.push_str(", ");
+                last.push_str(part);
+            }
+            _ => challenges.push(part.to_owned()),
+        }
+    }
+    challenges
+}
+
+fn parse_params(params: &str) -> HashMap<String, String> {
+    split_unquoted(params, ',')
+        .into_iter()
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            let value = value.trim();
+            let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+                Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+                None => value.to_owned(),
+            };
+            Some((key.trim().to_ascii_lowercase(), value))
+        })
+        .collect()
+}
+
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn credentials() -> DigestCredentials {
+        DigestCredentials {
+            username: "Mufasa".to_string(),
+            password: "Circle of Life".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rfc7616_md5() {
+        let header = r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=MD5, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+        let challenge = DigestChallenge::parse(header).unwrap();
+
+        let authorization = challenge.authorization(
+            &credentials(),
+            "GET",
+            "/dir/index.html",
+            1,
+            "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+        );
+
+        assert!(authorization.contains(r#"response="8ca523f5e9506fed4657c9700eebdbec""#));
+        assert!(authorization.contains("qop=auth, nc=00000001"));
+        assert!(authorization.contains(r#"opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#));
+    }
+
+    #[test]
+    fn test_rfc7616_sha256() {
+        let header = r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+        let challenge = DigestChallenge::parse(header).unwrap();
+
+        let authorization = challenge.authorization(
+            &credentials(),
+            "GET",
+            "/dir/index.html",
+            1,
+            "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+        );
+
+        assert!(authorization.contains(
+            r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#
+        ));
+        assert!(authorization.contains("algorithm=SHA-256"));
+    }
+
+    #[test]
+    fn test_prefers_strongest_challenge() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static(
+                r#"Digest realm="api", qop="auth", algorithm=MD5, nonce="abc", Digest realm="api", qop="auth", algorithm=SHA-256, nonce="def""#,
+            ),
+        );
+        headers.append(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_static(r#"Basic realm="api""#),
+        );
+
+        let challenge = DigestChallenge::from_headers(&headers).unwrap();
+        assert_eq!(challenge.algorithm, Algorithm::Sha256);
+        assert_eq!(challenge.nonce, "def");
+    }
+
+    #[test]
+    fn test_without_qop() {
+        let challenge = DigestChallenge::parse(
+            r#"Digest realm="testrealm@host.com", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093""#,
+        )
+        .unwrap();
+
+        let authorization =
+            challenge.authorization(&credentials(), "GET", "/dir/index.html", 1, "0a4f113b");
+
+        assert!(!authorization.contains("qop="));
+        assert!(authorization.contains("algorithm=MD5"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_answers_challenge() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/protected?id=1", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let responses = [
+                "HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Digest realm=\"api\", qop=\"auth\", nonce=\"abc\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            ];
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 8192];
+                let read = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..read]).to_string());
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        let client = Client::new();
+        let auth = DigestAuth {
+            credentials: credentials(),
+            challenges: Arc::new(DigestChallenges::new()),
+        };
+        for _ in 0..2 {
+            let request = client.get(&url).build().unwrap();
+            let response = execute(&client, request, &auth).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let requests = server.await.unwrap();
+        assert!(!requests[0].to_lowercase().contains("authorization:"));
+        assert!(requests[1].contains(
+            r#"Digest username="Mufasa", realm="api", nonce="abc", uri="/protected?id=1""#
+        ));
+        assert!(requests[1].contains("nc=00000001"));
+
+        // The second request reuses the nonce without another challenge
+        assert!(requests[2].contains(r#"nonce="abc""#));
+        assert!(requests[2].contains("nc=00000002"));
+    }
+}
@@ -0,0 +1,16 @@
+pub mod digest;
+pub mod oauth2;
+pub mod sigv4;
+
+use std::sync::Arc;
+
+use digest::DigestChallenges;
+use oauth2::TokenStore;
+
+/// Auth state a collection keeps between its requests
+#[derive(Debug, Default)]
+pub struct AuthState {
+    pub tokens: TokenStore,
+    /// Digest challenges of the collection's servers, shared with the requests answering them
+    pub digest: Arc<DigestChallenges>,
+}
@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::http::environment::EnvironmentChain;
+use crate::http::request::{OAuth2, OAuth2Grant};
+
+const DEFAULT_REDIRECT_URL: &str = "http://127.0.0.1:0/callback";
+const AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(300);
+// Refresh slightly before the server side expiry to avoid racing it
+const EXPIRY_LEEWAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct OAuth2Token {
+    pub access_token: String,
+    pub token_type: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<Instant>,
+}
+
+impl OAuth2Token {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|at| Instant::now() + EXPIRY_LEEWAY >= at)
+    }
+
+    pub fn authorization(&self) -> String {
+        let token_type = match self.token_type.as_str() {
+            "" => "Bearer",
+            t if t.eq_ignore_ascii_case("bearer") => "Bearer",
+            t => t,
+        };
+        format!("{} {}", token_type, self.access_token)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    token_type: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+impl From<TokenResponse> for OAuth2Token {
+    fn from(res: TokenResponse) -> Self {
+        Self {
+            access_token: res.access_token,
+            token_type: res.token_type,
+            refresh_token: res.refresh_token,
+            expires_at: res
+                .expires_in
+                .map(|secs| Instant::now() + Duration::from_secs(secs)),
+        }
+    }
+}
+
+/// Tokens fetched for a collection, keyed by the OAuth2 configuration that produced them
+#[derive(Debug, Default)]
+pub struct TokenStore {
+    tokens: Mutex<HashMap<String, OAuth2Token>>,
+}
+
+impl TokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, config: &OAuth2) -> Option<OAuth2Token> {
+        let tokens = self.tokens.lock().ok()?;
+        tokens.get(&cache_key(config)).cloned()
+    }
+
+    pub fn insert(&self, config: &OAuth2, token: OAuth2Token) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.insert(cache_key(config), token);
+        }
+    }
+
+    pub fn remove(&self, config: &OAuth2) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.remove(&cache_key(config));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.clear();
+        }
+    }
+}
+
+/// Secrets are part of the key as a hash, so changing them fetches a new token
+fn cache_key(config: &OAuth2) -> String {
+    let secrets = Sha256::digest(
+        format!(
+            "{}|{}|{}",
+            config.client_secret, config.password, config.refresh_token
+        )
+        .as_bytes(),
+    );
+    format!(
+        "{:?}|{}|{}|{}|{}|{}",
+        config.grant,
+        config.token_url,
+        config.client_id,
+        config.scope,
+        config.username,
+        hex::encode(secrets)
+    )
+}
+
+pub fn replace_env(config: &OAuth2, env: &EnvironmentChain) -> OAuth2 {
+    OAuth2 {
+        grant: config.grant,
+        token_url: env.replace(&config.token_url),
+        auth_url: env.replace(&config.auth_url),
+        redirect_url: env.replace(&config.redirect_url),
+        client_id: env.replace(&config.client_id),
+        client_secret: env.replace(&config.client_secret),
+        scope: env.replace(&config.scope),
+        username: env.replace(&config.username),
+        password: env.replace(&config.password),
+        refresh_token: env.replace(&config.refresh_token),
+        use_pkce: config.use_pkce,
+    }
+}
+
+/// Returns a valid token for the config, reusing or refreshing the cached one when possible
+pub async fn access_token(
+    client: &reqwest::Client,
+    config: &OAuth2,
+    store: &TokenStore,
+) -> anyhow::Result<OAuth2Token> {
+    if let Some(token) = store.get(config) {
+        if !token.is_expired() {
+            return Ok(token);
+        }
+
+        if let Some(refresh_token) = &token.refresh_token {
+            match refresh(client, config, refresh_token).await {
+                Ok(token) => {
+                    store.insert(config, token.clone());
+                    return Ok(token);
+                }
+                Err(e) => log::warn!("Failed to refresh OAuth2 token: {e:?}"),
+            }
+        }
+        store.remove(config);
+    }
+
+    let token = fetch_token(client, config).await?;
+    store.insert(config, token.clone());
+    Ok(token)
+}
+
+pub async fn fetch_token(client: &reqwest::Client, config: &OAuth2) -> anyhow::Result<OAuth2Token> {
+    match config.grant {
+        OAuth2Grant::ClientCredentials => {
+            let mut form = vec![("grant_type", "client_credentials")];
+            push_scope(&mut form, config);
+            request_token(client, config, form).await
+        }
+        OAuth2Grant::Password => {
+            let mut form = vec![
+                ("grant_type", "password"),
+                ("username", config.username.as_str()),
+                ("password", config.password.as_str()),
+            ];
+            push_scope(&mut form, config);
+            request_token(client, config, form).await
+        }
+        OAuth2Grant::RefreshToken => refresh(client, config, &config.refresh_token).await,
+        OAuth2Grant::AuthorizationCode => authorize(client, config).await,
+    }
+}
+
+fn push_scope<'a>(form: &mut Vec<(&'a str, &'a str)>, config: &'a OAuth2) {
+    if !config.scope.is_empty() {
+        form.push(("scope", config.scope.as_str()));
+    }
+}
+
+async fn refresh(
+    client: &reqwest::Client,
+    config: &OAuth2,
+    refresh_token: &str,
+) -> anyhow::Result<OAuth2Token> {
+    if refresh_token.is_empty() {
+        bail!("Refresh token is empty");
+    }
+
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+    ];
+    push_scope(&mut form, config);
+    let mut token = request_token(client, config, form).await?;
+
+    // Servers are allowed to keep the refresh token unchanged and omit it
+    if token.refresh_token.is_none() {
+        token.refresh_token = Some(refresh_token.to_owned());
+    }
+    Ok(token)
+}
+
+async fn request_token(
+    client: &reqwest::Client,
+    config: &OAuth2,
+    mut form: Vec<(&str, &str)>,
+) -> anyhow::Result<OAuth2Token> {
+    if config.token_url.is_empty() {
+        bail!("OAuth2 token URL is empty");
+    }
+
+    form.push(("client_id", config.client_id.as_str()));
+    if !config.client_secret.is_empty() {
+        form.push(("client_secret", config.client_secret.as_str()));
+    }
+
+    let res = client
+        .post(&config.token_url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .await
+        .context("Failed to reach OAuth2 token endpoint")?;
+
+    let status = res.status();
+    let body = res.text().await?;
+    if !status.is_success() {
+        bail!("OAuth2 token request failed with {}: {}", status, body);
+    }
+
+    let token: TokenResponse =
+        serde_json::from_str(&body).context("Invalid OAuth2 token response")?;
+    Ok(token.into())
+}
+
+/// Authorization code grant, the user is sent to the browser and the code is
+/// received on a loopback redirect URL
+async fn authorize(client: &reqwest::Client, config: &OAuth2) -> anyhow::Result<OAuth2Token> {
+    let redirect = match config.redirect_url.as_str() {
+        "" => DEFAULT_REDIRECT_URL,
+        url => url,
+    };
+    let mut redirect_url = Url::parse(redirect).context("Invalid OAuth2 redirect URL")?;
+    let host = listen_host(&redirect_url)?;
+
+    let port = redirect_url.port_or_known_default().unwrap_or(0);
+    let listener = TcpListener::bind((host.as_str(), port))
+        .await
+        .context("Failed to listen on OAuth2 redirect URL")?;
+    let _ = redirect_url.set_port(Some(listener.local_addr()?.port()));
+
+    let state = random_string();
+    let verifier = random_string();
+
+    let mut auth_url = Url::parse(&config.auth_url).context("Invalid OAuth2 authorization URL")?;
+    {
+        let mut query = auth_url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", redirect_url.as_str())
+            .append_pair("state", &state);
+        if !config.scope.is_empty() {
+            query.append_pair("scope", &config.scope);
+        }
+        if config.use_pkce {
+            query
+                .append_pair("code_challenge", &pkce_challenge(&verifier))
+                .append_pair("code_challenge_method", "S256");
+        }
+    }
+
+    if let Err(e) = open::that_detached(auth_url.as_str()) {
+        log::warn!("Failed to open browser: {e}");
+    }
+    log::info!("Waiting for OAuth2 authorization at {}", auth_url);
+
+    let code = tokio::time::timeout(AUTHORIZE_TIMEOUT, receive_code(&listener, &state))
+        .await
+        .context("Timed out waiting for OAuth2 authorization")??;
+
+    let redirect_uri = redirect_url.to_string();
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+    ];
+    if config.use_pkce {
+        form.push(("code_verifier", verifier.as_str()));
+    }
+    request_token(client, config, form).await
+}
+
+async fn receive_code(listener: &TcpListener, state: &str) -> anyhow::Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+
+        let mut buffer = vec![0; 8192];
+        let read = stream.read(&mut buffer).await?;
+        let request = String::from_utf8_lossy(&buffer[..read]);
+
+        let Some(params) = callback_params(&request) else {
+            // Browsers also ask for things like favicon.ico, ignore them
+            let _ = stream
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            continue;
+        };
+
+        let result = match (params.get("code"), params.get("error")) {
+            (_, Some(error)) => Err(anyhow::anyhow!("OAuth2 authorization failed: {}", error)),
+            (Some(_), _) if params.get("state").map(String::as_str) != Some(state) => {
+                Err(anyhow::anyhow!("OAuth2 state mismatch"))
+            }
+            (Some(code), _) => Ok(code.clone()),
+            (None, None) => Err(anyhow::anyhow!("OAuth2 redirect did not contain a code")),
+        };
+
+        let message = match &result {
+            Ok(_) => "Authorization complete, you can close this window.".to_string(),
+            Err(e) => e.to_string(),
+        };
+        let body = format!(
+            "<html><body><h3>Sanchaar</h3><p>{}</p></body></html>",
+            message
+        );
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+
+        return result;
+    }
+}
+
+fn callback_params(request: &str) -> Option<HashMap<String, String>> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let url = Url::parse(&format!("http://localhost{}", target)).ok()?;
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    (params.contains_key("code") || params.contains_key("error")).then_some(params)
+}
+
+/// Host to listen on for the redirect, IPv6 addresses without the brackets of the URL
+fn listen_host(redirect_url: &Url) -> anyhow::Result<String> {
+    let host = redirect_url
+        .host_str()
+        .context("OAuth2 redirect URL has no host")?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    let is_loopback = host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if !is_loopback {
+        bail!("OAuth2 redirect URL must point to localhost");
+    }
+    Ok(host.to_owned())
+}
+
+fn random_string() -> String {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    bytes[16..].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn token_endpoint(
+        responses: Vec<&'static str>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for body in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 8192];
+                let read = stream.read(&mut buffer).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buffer[..read]).to_string());
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+
+        (url, handle)
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        let verifier = "dBjftJeZ4CVP-mJ92K5vbPMF1G5aI5-Pvj5nRQqWZ9w";
+        assert_eq!(
+            pkce_challenge(verifier),
+            "xsjWZxEZUCYaGz312zreDO1zKBZxAj9RxZUelaw5gdY"
+        );
+    }
+
+    #[test]
+    fn test_cache_key_includes_secrets() {
+        let config = OAuth2 {
+            client_id: "app".to_string(),
+            client_secret: "one".to_string(),
+            ..OAuth2::default()
+        };
+        let rotated = OAuth2 {
+            client_secret: "two".to_string(),
+            ..config.clone()
+        };
+
+        assert_ne!(cache_key(&config), cache_key(&rotated));
+        assert!(!cache_key(&config).contains("one"));
+    }
+
+    #[test]
+    fn test_listen_host() {
+        let host = |url: &str| listen_host(&Url::parse(url).unwrap()).ok();
+
+        assert_eq!(host("http://[::1]:8080/callback").as_deref(), Some("::1"));
+        assert_eq!(
+            host("http://127.0.0.1:0/callback").as_deref(),
+            Some("127.0.0.1")
+        );
+        assert_eq!(
+            host("http://localhost/callback").as_deref(),
+            Some("localhost")
+        );
+        assert_eq!(host("http://example.com/callback"), None);
+    }
+
+    #[test]
+    fn test_callback_params() {
+        let request = "GET /callback?code=abc&state=xyz HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let params = callback_params(request).unwrap();
+        assert_eq!(params.get("code").unwrap(), "abc");
+        assert_eq!(params.get("state").unwrap(), "xyz");
+
+        let request = "GET /favicon.ico HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert!(callback_params(request).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_token_is_cached() {
+        let (url, server) = token_endpoint(vec![
+            r#"{"access_token":"token-1","token_type":"bearer","expires_in":3600}"#,
+        ])
+        .await;
+
+        let config = OAuth2 {
+            grant: OAuth2Grant::ClientCredentials,
+            token_url: url,
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            scope: "read".to_string(),
+            ..Default::default()
+        };
+
+        let client = reqwest::Client::new();
+        let store = TokenStore::new();
+
+        let token = access_token(&client, &config, &store).await.unwrap();
+        assert_eq!(token.authorization(), "Bearer token-1");
+
+        let token = access_token(&client, &config, &store).await.unwrap();
+        assert_eq!(token.access_token, "token-1");
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("grant_type=client_credentials"));
+        assert!(requests[0].contains("client_id=client"));
+        assert!(requests[0].contains("scope=read"));
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        let (url, server) = token_endpoint(vec![
+            r#"{"access_token":"new-token","token_type":"Bearer","expires_in":3600}"#,
+        ])
+        .await;
+
+        let config = OAuth2 {
+            grant: OAuth2Grant::Password,
+            token_url: url,
+            client_id: "client".to_string(),
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            ..Default::default()
+        };
+
+        let store = TokenStore::new();
+        store.insert(
+            &config,
+            OAuth2Token {
+                access_token: "old-token".to_string(),
+                token_type: "Bearer".to_string(),
+                refresh_token: Some("refresh-1".to_string()),
+                expires_at: Some(Instant::now()),
+            },
+        );
+
+        let client = reqwest::Client::new();
+        let token = access_token(&client, &config, &store).await.unwrap();
+        assert_eq!(token.access_token, "new-token");
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh-1"));
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].contains("grant_type=refresh_token"));
+        assert!(requests[0].contains("refresh_token=refresh-1"));
+    }
+}
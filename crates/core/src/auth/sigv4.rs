@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use reqwest::Request;
+use reqwest::header::{AUTHORIZATION, HeaderValue};
+use sha2::{Digest, Sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+// Headers that proxies and the http stack are free to rewrite
+const UNSIGNED_HEADERS: [&str; 4] = ["authorization", "user-agent", "expect", "connection"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key: String,
+    pub secret_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+    pub service: String,
+}
+
+/// Signs the request in place, adding the `x-amz-*` headers and `Authorization`
+pub fn sign_request(
+    request: &mut Request,
+    credentials: &AwsCredentials,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let payload_hash = match request.body() {
+        None => hex_sha256(b""),
+        Some(body) => match body.as_bytes() {
+            Some(bytes) => hex_sha256(bytes),
+            // Streamed bodies can't be hashed up front
+            None => UNSIGNED_PAYLOAD.to_owned(),
+        },
+    };
+
+    let headers = request.headers_mut();
+    headers.insert("x-amz-date", HeaderValue::from_str(&amz_date)?);
+    if let Some(token) = &credentials.session_token {
+        headers.insert(
+            "x-amz-security-token",
+            HeaderValue::from_str(token).context("Invalid AWS session token")?,
+        );
+    }
+    if credentials.service == "s3" {
+        headers.insert(
+            "x-amz-content-sha256",
+            HeaderValue::from_str(&payload_hash)?,
+        );
+    }
+
+    let (canonical_headers, signed_headers) = canonical_headers(request)?;
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method().as_str(),
+        canonical_uri(request.url().path(), &credentials.service),
+        canonical_query(request.url()),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, credentials.region, credentials.service
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let key = signing_key(credentials, &date);
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, credentials.access_key, scope, signed_headers, signature
+    );
+    request.headers_mut().insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&authorization).context("Invalid AWS access key")?,
+    );
+
+    Ok(())
+}
+
+fn signing_key(credentials: &AwsCredentials, date: &str) -> Vec<u8> {
+    let secret = format!("AWS4{}", credentials.secret_key);
+    let key = hmac(secret.as_bytes(), date.as_bytes());
+    let key = hmac(&key, credentials.region.as_bytes());
+    let key = hmac(&key, credentials.service.as_bytes());
+    hmac(&key, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn canonical_headers(request: &Request) -> anyhow::Result<(String, String)> {
+    let url = request.url();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_owned(),
+    };
+
+    let mut headers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    headers.insert("host".to_owned(), vec![host]);
+
+    for (name, value) in request.headers() {
+        let name = name.as_str();
+        if UNSIGNED_HEADERS.contains(&name) {
+            continue;
+        }
+        let value = value
+            .to_str()
+            .with_context(|| format!("Header {} is not valid text", name))?;
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        headers.entry(name.to_owned()).or_default().push(value);
+    }
+
+    let canonical = headers
+        .iter()
+        .map(|(name, values)| format!("{}:{}\n", name, values.join(",")))
+        .collect::<String>();
+    let signed = headers
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    Ok((canonical, signed))
+}
+
+// S3 signs the path as sent, every other service expects each segment encoded twice
+fn canonical_uri(path: &str, service: &str) -> String {
+    if path.is_empty() {
+        return "/".to_owned();
+    }
+
+    path.split('/')
+        .map(|segment| {
+            let decoded = urlencoding::decode(segment)
+                .map(|s| s.into_owned())
+                .unwrap_or_else(|_| segment.to_owned());
+            let encoded = uri_encode(&decoded);
+            if service == "s3" {
+                encoded
+            } else {
+                uri_encode(&encoded)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut params = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect::<Vec<_>>();
+    params.sort();
+
+    params
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use reqwest::Client;
+    use reqwest::header::CONTENT_TYPE;
+
+    use super::*;
+
+    // Credentials and timestamp used by AWS's published SigV4 test suite
+    fn credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key: "AKIDEXAMPLE".to_string(),
+            secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+            region: "us-east-1".to_string(),
+            service: "service".to_string(),
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()
+    }
+
+    fn authorization(request: &Request) -> &str {
+        request.headers()[AUTHORIZATION].to_str().unwrap()
+    }
+
+    #[test]
+    fn test_get_vanilla() {
+        let mut request = Client::new()
+            .get("https://example.amazonaws.com/")
+            .build()
+            .unwrap();
+        sign_request(&mut request, &credentials(), now()).unwrap();
+
+        assert_eq!(
+            authorization(&request),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+    }
+
+    #[test]
+    fn test_post_vanilla() {
+        let mut request = Client::new()
+            .post("https://example.amazonaws.com/")
+            .build()
+            .unwrap();
+        sign_request(&mut request, &credentials(), now()).unwrap();
+
+        assert!(authorization(&request).ends_with(
+            "Signature=5da7c1a2acd57cee7505fc6676e4e544621c30862966e37dddb68e92efbe5d6b"
+        ));
+    }
+
+    #[test]
+    fn test_get_vanilla_query_order_key_case() {
+        let mut request = Client::new()
+            .get("https://example.amazonaws.com/?Param2=value2&Param1=value1")
+            .build()
+            .unwrap();
+        sign_request(&mut request, &credentials(), now()).unwrap();
+
+        assert!(authorization(&request).ends_with(
+            "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        ));
+    }
+
+    #[test]
+    fn test_post_x_www_form_urlencoded() {
+        let mut request = Client::new()
+            .post("https://example.amazonaws.com/")
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body("Param1=value1")
+            .build()
+            .unwrap();
+        sign_request(&mut request, &credentials(), now()).unwrap();
+
+        assert_eq!(
+            authorization(&request),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=ff11897932ad3f4e8b18135d722051e5ac45fc38421b1da7b9d196a0fe09473a"
+        );
+    }
+
+    #[test]
+    fn test_session_token_is_signed() {
+        let mut credentials = credentials();
+        credentials.session_token = Some("session".to_string());
+
+        let mut request = Client::new()
+            .get("https://example.amazonaws.com/")
+            .build()
+            .unwrap();
+        sign_request(&mut request, &credentials, now()).unwrap();
+
+        assert_eq!(request.headers()["x-amz-security-token"], "session");
+        assert!(
+            authorization(&request).contains("SignedHeaders=host;x-amz-date;x-amz-security-token")
+        );
+    }
+
+    #[test]
+    fn test_canonical_uri() {
+        assert_eq!(
+            canonical_uri("/example%20space/", "s3"),
+            "/example%20space/"
+        );
+        assert_eq!(
+            canonical_uri("/example%20space/", "execute-api"),
+            "/example%2520space/"
+        );
+    }
+}
@@ -1,7 +1,12 @@
-use std::{fmt::Display, str::FromStr, sync::Arc, time::Duration};
+use std::{collections::HashMap, fmt::Display, str::FromStr, sync::Arc, time::Duration};
 
 use reqwest::{Client, Request, StatusCode, header::HeaderMap};
-use reqwest_cookie_store::CookieStoreRwLock;
+
+use crate::auth::digest::{self, DigestAuth};
+use crate::cookies::CookieJar;
+use crate::http::collection::Collection;
+use crate::http::{CollectionKey, VarMap};
+use crate::proxy::ProxySettings;
 
 fn is_json_content_type(content_type: &str) -> bool {
     let content_type = content_type.to_lowercase();
@@ -94,9 +99,41 @@ pub struct Response {
     pub size_bytes: usize,
 }
 
-pub async fn send_request(client: Client, req: Request) -> anyhow::Result<Response> {
+/// A built request along with the auth state that can only be applied while sending
+#[derive(Debug)]
+pub struct PreparedRequest {
+    pub request: Request,
+    pub digest: Option<DigestAuth>,
+    /// Variables set by the pre-request script
+    pub variables: VarMap,
+}
+
+impl PreparedRequest {
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            request: self.request.try_clone()?,
+            digest: self.digest.clone(),
+            variables: self.variables.clone(),
+        })
+    }
+}
+
+impl From<Request> for PreparedRequest {
+    fn from(request: Request) -> Self {
+        Self {
+            request,
+            digest: None,
+            variables: VarMap::new(),
+        }
+    }
+}
+
+pub async fn send_request(client: Client, req: PreparedRequest) -> anyhow::Result<Response> {
     let start = std::time::Instant::now();
-    let res = client.execute(req).await?;
+    let res = match req.digest {
+        Some(auth) => digest::execute(&client, req.request, &auth).await?,
+        None => client.execute(req.request).await?,
+    };
     let duration = start.elapsed();
     let status = res.status();
     let headers = res.headers().clone();
@@ -133,16 +170,93 @@ pub async fn send_request(client: Client, req: Request) -> anyhow::Result<Respon
     })
 }
 
-pub fn create_cookie_store() -> Arc<CookieStoreRwLock> {
-    Arc::new(CookieStoreRwLock::default())
+pub fn create_cookie_store() -> Arc<CookieJar> {
+    Arc::new(CookieJar::default())
+}
+
+/// Creates a client sending and storing cookies in the store, or without cookies if there is none
+pub fn create_client(
+    disable_verification: bool,
+    store: Option<Arc<CookieJar>>,
+    proxy: &ProxySettings,
+) -> anyhow::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder().danger_accept_invalid_certs(disable_verification);
+    let builder = match store {
+        Some(store) => builder.cookie_provider(store),
+        None => builder,
+    };
+
+    Ok(proxy.apply(builder)?.build()?)
+}
+
+/// Settings a cached client was created with
+#[derive(Debug, PartialEq)]
+struct ClientOptions {
+    disable_ssl: bool,
+    proxy: ProxySettings,
 }
 
-pub fn create_client(disable_verification: bool, store: Arc<CookieStoreRwLock>) -> reqwest::Client {
-    reqwest::Client::builder()
-        .danger_accept_invalid_certs(disable_verification)
-        .cookie_provider(store)
-        .build()
-        .expect("Failed to create client")
+/// Clients for each collection using the cookie jar of the collection, created on first use
+#[derive(Debug)]
+pub struct HttpClients {
+    cookies_enabled: bool,
+    /// Proxy of collections without their own proxy
+    proxy: ProxySettings,
+    /// Cookies of requests not saved in a collection
+    pub default_jar: Arc<CookieJar>,
+    clients: HashMap<Option<CollectionKey>, (ClientOptions, Client)>,
+}
+
+impl HttpClients {
+    pub fn new(cookies_enabled: bool, proxy: ProxySettings) -> Self {
+        Self {
+            cookies_enabled,
+            proxy,
+            default_jar: create_cookie_store(),
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Client for the collection, recreated when the collection settings changed
+    pub fn get(&mut self, collection: Option<&Collection>) -> anyhow::Result<Client> {
+        let key = collection.map(|c| c.key);
+        let options = ClientOptions {
+            disable_ssl: collection.is_some_and(|c| c.disable_ssl),
+            proxy: collection
+                .and_then(|c| c.proxy.as_ref().map(|proxy| proxy.resolve(&c.env_chain())))
+                .unwrap_or_else(|| self.proxy.clone()),
+        };
+        if let Some((cached, client)) = self.clients.get(&key)
+            && *cached == options
+        {
+            return Ok(client.clone());
+        }
+
+        let jar = collection.map_or(&self.default_jar, |c| &c.cookies);
+        let store = self.cookies_enabled.then(|| Arc::clone(jar));
+        let client = create_client(options.disable_ssl, store, &options.proxy)?;
+        self.clients.insert(key, (options, client.clone()));
+        Ok(client)
+    }
+
+    pub fn cookies_enabled(&self) -> bool {
+        self.cookies_enabled
+    }
+
+    pub fn set_cookies_enabled(&mut self, enabled: bool) {
+        self.cookies_enabled = enabled;
+        self.clients.clear();
+    }
+
+    pub fn set_proxy(&mut self, proxy: ProxySettings) {
+        self.proxy = proxy;
+    }
+
+    /// Replaces the jar of requests not saved in a collection with the saved one
+    pub fn set_default_jar(&mut self, jar: Arc<CookieJar>) {
+        self.default_jar = jar;
+        self.clients.remove(&None);
+    }
 }
 
 #[cfg(test)]
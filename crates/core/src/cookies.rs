@@ -0,0 +1,477 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LockResult, RwLockReadGuard, RwLockWriteGuard};
+
+use anyhow::{Context, bail};
+use chrono::{DateTime, Utc};
+use cookie_store::{Cookie, CookieDomain, CookieExpiration, CookieStore};
+use reqwest::Url;
+use reqwest::header::HeaderValue;
+use reqwest_cookie_store::CookieStoreRwLock;
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// File formats cookie jars can be imported from and exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieFormat {
+    /// The `cookies.txt` format used by curl and browsers
+    Netscape,
+    /// The format cookie jars are saved in
+    Json,
+}
+
+impl CookieFormat {
+    /// JSON for `.json` files, `cookies.txt` otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => CookieFormat::Json,
+            _ => CookieFormat::Netscape,
+        }
+    }
+}
+
+/// SameSite attribute of a cookie
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl SameSite {
+    pub const ALL: [SameSite; 3] = [SameSite::Strict, SameSite::Lax, SameSite::None];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|same_site| same_site.as_str().eq_ignore_ascii_case(value))
+    }
+}
+
+/// Attributes of a cookie, used to create or edit cookies by hand
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieFields {
+    pub name: String,
+    pub value: String,
+    /// Host of the cookie, without a leading dot
+    pub domain: String,
+    /// Also send the cookie to subdomains of `domain`
+    pub include_subdomains: bool,
+    pub path: String,
+    /// `None` for session cookies
+    pub expires: Option<DateTime<Utc>>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+}
+
+impl CookieFields {
+    pub fn from_cookie(cookie: &Cookie) -> Self {
+        let expires = match &cookie.expires {
+            CookieExpiration::AtUtc(time) => DateTime::from_timestamp(time.unix_timestamp(), 0),
+            CookieExpiration::SessionEnd => None,
+        };
+        let path: &str = &cookie.path;
+
+        Self {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain: cookie.domain.as_cow().unwrap_or_default().into_owned(),
+            include_subdomains: matches!(cookie.domain, CookieDomain::Suffix(_)),
+            path: path.to_string(),
+            expires,
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+            same_site: cookie
+                .same_site()
+                .and_then(|same_site| SameSite::parse(&same_site.to_string())),
+        }
+    }
+
+    /// Builds the cookie along with the URL it is stored for
+    pub fn to_cookie(&self) -> anyhow::Result<(Cookie<'static>, Url)> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            bail!("Cookie name is required");
+        }
+        let host = self.domain.trim().trim_start_matches('.');
+        if host.is_empty() {
+            bail!("Cookie domain is required");
+        }
+        let path = match self.path.trim() {
+            "" => "/",
+            path => path,
+        };
+
+        let mut cookie = format!("{}={}; Path={}", name, self.value, path);
+        if self.include_subdomains {
+            cookie.push_str(&format!("; Domain={}", host));
+        }
+        if let Some(expires) = self.expires {
+            cookie.push_str(
+                &expires
+                    .format("; Expires=%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            );
+        }
+        if self.secure {
+            cookie.push_str("; Secure");
+        }
+        if self.http_only {
+            cookie.push_str("; HttpOnly");
+        }
+        if let Some(same_site) = self.same_site {
+            cookie.push_str(&format!("; SameSite={}", same_site.as_str()));
+        }
+
+        let url = Url::parse(&format!("https://{}{}", host, path))
+            .with_context(|| format!("Invalid domain {}", self.domain))?;
+        let cookie = Cookie::parse(cookie, &url)?.into_owned();
+        Ok((cookie, url))
+    }
+}
+
+/// Cookie store shared with the client that counts its changes, so only changed jars are saved
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    store: CookieStoreRwLock,
+    generation: AtomicU64,
+    saved: AtomicU64,
+}
+
+impl CookieJar {
+    pub fn new(store: CookieStore) -> Self {
+        Self {
+            store: CookieStoreRwLock::new(store),
+            ..Self::default()
+        }
+    }
+
+    pub fn read(&self) -> LockResult<RwLockReadGuard<'_, CookieStore>> {
+        self.store.read()
+    }
+
+    /// Locks the store for changes, the jar counts as changed from then on
+    pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, CookieStore>> {
+        let store = self.store.write();
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        store
+    }
+
+    /// Changes made so far, read while holding `read` it matches the cookies of the store
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn mark_saved(&self, generation: u64) {
+        self.saved.fetch_max(generation, Ordering::Relaxed);
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.generation() != self.saved.load(Ordering::Relaxed)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        reqwest::cookie::CookieStore::set_cookies(&self.store, cookie_headers, url);
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        reqwest::cookie::CookieStore::cookies(&self.store, url)
+    }
+}
+
+/// Adds the cookie to the store, removing `replaces` first when an existing cookie was edited
+pub fn save_cookie(
+    store: &mut CookieStore,
+    fields: &CookieFields,
+    replaces: Option<&CookieFields>,
+) -> anyhow::Result<()> {
+    let (cookie, url) = fields.to_cookie()?;
+    if let Some(old) = replaces {
+        store.remove(&old.domain, &old.path, &old.name);
+    }
+    store.insert(cookie, &url)?;
+    Ok(())
+}
+
+/// Writes the unexpired cookies of the store, including session cookies
+pub fn export_cookies(store: &CookieStore, format: CookieFormat) -> anyhow::Result<String> {
+    match format {
+        CookieFormat::Netscape => Ok(to_netscape(store)),
+        CookieFormat::Json => {
+            let cookies = store.iter_unexpired().collect::<Vec<_>>();
+            Ok(serde_json::to_string_pretty(&cookies)?)
+        }
+    }
+}
+
+/// Adds the cookies to the store, replacing cookies with the same name, domain and path.
+/// Returns the number of cookies added.
+pub fn import_cookies(
+    store: &mut CookieStore,
+    content: &str,
+    format: CookieFormat,
+) -> anyhow::Result<usize> {
+    let cookies = match format {
+        CookieFormat::Netscape => parse_netscape(content)?,
+        CookieFormat::Json => {
+            serde_json::from_str::<Vec<Cookie<'static>>>(content).context("Invalid cookie JSON")?
+        }
+    };
+
+    let mut count = 0;
+    for cookie in cookies {
+        let url = cookie_url(&cookie)?;
+        if store.insert(cookie, &url).is_ok() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// URL the cookie could have been received from, the store only accepts cookies matching it
+fn cookie_url(cookie: &Cookie) -> anyhow::Result<Url> {
+    let domain = cookie
+        .domain
+        .as_cow()
+        .context("Cookie is missing a domain")?;
+    let path: &str = &cookie.path;
+
+    Url::parse(&format!("https://{}{}", domain, path))
+        .with_context(|| format!("Invalid cookie domain {}", domain))
+}
+
+fn to_netscape(store: &CookieStore) -> String {
+    let mut lines = vec![NETSCAPE_HEADER.to_string(), String::new()];
+
+    for cookie in store.iter_unexpired() {
+        let (domain, subdomains) = match &cookie.domain {
+            CookieDomain::Suffix(domain) => (format!(".{}", domain), true),
+            domain => (domain.as_cow().unwrap_or_default().into_owned(), false),
+        };
+        let prefix = if cookie.http_only().unwrap_or(false) {
+            HTTP_ONLY_PREFIX
+        } else {
+            ""
+        };
+        let expires = match &cookie.expires {
+            CookieExpiration::AtUtc(time) => time.unix_timestamp(),
+            CookieExpiration::SessionEnd => 0,
+        };
+        let path: &str = &cookie.path;
+
+        lines.push(format!(
+            "{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            prefix,
+            domain,
+            netscape_bool(subdomains),
+            path,
+            netscape_bool(cookie.secure().unwrap_or(false)),
+            expires,
+            cookie.name(),
+            cookie.value(),
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn netscape_bool(value: bool) -> &'static str {
+    if value { "TRUE" } else { "FALSE" }
+}
+
+fn parse_netscape(content: &str) -> anyhow::Result<Vec<Cookie<'static>>> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let line = line.trim_end_matches('\r');
+            let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+            let skip = line.trim().is_empty() || line.starts_with('#');
+            (!skip).then(|| {
+                parse_netscape_line(line, http_only)
+                    .with_context(|| format!("Invalid cookie on line {}", index + 1))
+            })
+        })
+        .collect()
+}
+
+fn parse_netscape_line(line: &str, http_only: bool) -> anyhow::Result<Cookie<'static>> {
+    let fields = line.split('\t').collect::<Vec<_>>();
+    let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+        bail!("Expected 7 tab separated fields, found {}", fields.len());
+    };
+
+    let expires = match expires.parse::<i64>().context("Invalid expiry")? {
+        0 => None,
+        expires => Some(DateTime::from_timestamp(expires, 0).context("Invalid expiry")?),
+    };
+
+    let fields = CookieFields {
+        name: name.to_string(),
+        value: value.to_string(),
+        domain: domain.to_string(),
+        include_subdomains: subdomains.eq_ignore_ascii_case("TRUE"),
+        path: path.to_string(),
+        expires,
+        secure: secure.eq_ignore_ascii_case("TRUE"),
+        http_only,
+        same_site: None,
+    };
+    Ok(fields.to_cookie()?.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COOKIES_TXT: &str = "# Netscape HTTP Cookie File\n\
+        \n\
+        .example.com\tTRUE\t/\tTRUE\t4102444800\tsession\tabc\n\
+        #HttpOnly_api.example.com\tFALSE\t/v1\tFALSE\t0\ttoken\txyz\n";
+
+    fn store_with(content: &str, format: CookieFormat) -> CookieStore {
+        let mut store = CookieStore::default();
+        import_cookies(&mut store, content, format).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_jar_tracks_changes() {
+        use reqwest::cookie::CookieStore as _;
+
+        let jar = CookieJar::default();
+        assert!(!jar.is_dirty());
+
+        let url = Url::parse("https://example.com/").unwrap();
+        let header = HeaderValue::from_static("session=abc; Path=/");
+        jar.set_cookies(&mut [&header].into_iter(), &url);
+        assert!(jar.is_dirty());
+
+        let generation = jar.generation();
+        jar.mark_saved(generation);
+        assert!(!jar.is_dirty());
+
+        jar.write().unwrap().clear();
+        assert!(jar.is_dirty());
+    }
+
+    #[test]
+    fn test_import_netscape() {
+        let store = store_with(COOKIES_TXT, CookieFormat::Netscape);
+
+        let session = store.get("example.com", "/", "session").unwrap();
+        assert_eq!(session.value(), "abc");
+        assert_eq!(session.secure(), Some(true));
+        assert!(matches!(session.domain, CookieDomain::Suffix(_)));
+
+        let token = store.get("api.example.com", "/v1", "token").unwrap();
+        assert_eq!(token.http_only(), Some(true));
+        assert_eq!(token.expires, CookieExpiration::SessionEnd);
+    }
+
+    #[test]
+    fn test_netscape_round_trip() {
+        let store = store_with(COOKIES_TXT, CookieFormat::Netscape);
+        let exported = export_cookies(&store, CookieFormat::Netscape).unwrap();
+        let imported = store_with(&exported, CookieFormat::Netscape);
+
+        assert_eq!(imported.iter_unexpired().count(), 2);
+        assert!(exported.contains(".example.com\tTRUE\t/\tTRUE\t4102444800\tsession\tabc"));
+        assert!(exported.contains("#HttpOnly_api.example.com\tFALSE\t/v1\tFALSE\t0\ttoken\txyz"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let store = store_with(COOKIES_TXT, CookieFormat::Netscape);
+        let exported = export_cookies(&store, CookieFormat::Json).unwrap();
+        let imported = store_with(&exported, CookieFormat::Json);
+
+        let token = imported.get("api.example.com", "/v1", "token").unwrap();
+        assert_eq!(token.value(), "xyz");
+        assert_eq!(imported.iter_unexpired().count(), 2);
+    }
+
+    #[test]
+    fn test_invalid_netscape_line() {
+        let mut store = CookieStore::default();
+        let error =
+            import_cookies(&mut store, "example.com\tTRUE\n", CookieFormat::Netscape).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid cookie on line 1");
+    }
+
+    fn fields() -> CookieFields {
+        CookieFields {
+            name: "session".to_string(),
+            value: "abc".to_string(),
+            domain: "example.com".to_string(),
+            include_subdomains: true,
+            path: "/api".to_string(),
+            expires: DateTime::from_timestamp(4102444800, 0),
+            secure: true,
+            http_only: true,
+            same_site: Some(SameSite::Lax),
+        }
+    }
+
+    #[test]
+    fn test_save_cookie() {
+        let mut store = CookieStore::default();
+        save_cookie(&mut store, &fields(), None).unwrap();
+
+        let cookie = store.get("example.com", "/api", "session").unwrap();
+        assert_eq!(CookieFields::from_cookie(cookie), fields());
+        let url = Url::parse("https://api.example.com/api").unwrap();
+        assert_eq!(store.matches(&url).len(), 1);
+    }
+
+    #[test]
+    fn test_save_edited_cookie() {
+        let mut store = CookieStore::default();
+        let original = fields();
+        save_cookie(&mut store, &original, None).unwrap();
+
+        let edited = CookieFields {
+            name: "token".to_string(),
+            path: String::new(),
+            expires: None,
+            same_site: None,
+            ..fields()
+        };
+        save_cookie(&mut store, &edited, Some(&original)).unwrap();
+
+        assert!(store.get("example.com", "/api", "session").is_none());
+        let cookie = store.get("example.com", "/", "token").unwrap();
+        assert_eq!(cookie.value(), "abc");
+        assert_eq!(cookie.expires, CookieExpiration::SessionEnd);
+        assert_eq!(cookie.same_site(), None);
+    }
+
+    #[test]
+    fn test_save_cookie_requires_name() {
+        let mut store = CookieStore::default();
+        let fields = CookieFields {
+            name: " ".to_string(),
+            ..fields()
+        };
+
+        let error = save_cookie(&mut store, &fields, None).unwrap_err();
+        assert_eq!(error.to_string(), "Cookie name is required");
+        assert_eq!(store.iter_any().count(), 0);
+    }
+}
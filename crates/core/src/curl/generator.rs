@@ -7,7 +7,7 @@
 //! - Headers: -H, --header
 //! - Data: -d, --data (for JSON, XML, Text bodies)
 //! - Form data: -F, --form (with file uploads using @filepath)
-//! - Authentication: -u/--user (Basic), --bearer (Bearer token)
+//! - Authentication: -u/--user (Basic), --digest (Digest), --aws-sigv4 (AWS), --bearer (Bearer token)
 //! - Query parameters: merged into URL
 //! - Path parameters: substituted in URL
 //! - Shell escaping: proper quoting for bash/sh compatibility
@@ -60,6 +60,14 @@ pub fn generate_curl_command(request: &Request, env: EnvironmentChain) -> String
                 shell_quote(&format!("{}:{}", username, password))
             ));
         }
+        Auth::Digest { username, password } => {
+            let username = env.replace(username);
+            let password = env.replace(password);
+            lines.push(format!(
+                "  --digest -u {}",
+                shell_quote(&format!("{}:{}", username, password))
+            ));
+        }
         Auth::Bearer { token } => {
             let token = env.replace(token);
             lines.push(format!(
@@ -104,7 +112,32 @@ pub fn generate_curl_command(request: &Request, env: EnvironmentChain) -> String
                 ));
             }
         }
-        Auth::None => {}
+        // Tokens are only known after talking to the token endpoint at send time
+        Auth::OAuth2(_) => {}
+        Auth::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        } => {
+            let provider = format!("aws:amz:{}:{}", env.replace(region), env.replace(service));
+            let keys = format!("{}:{}", env.replace(access_key), env.replace(secret_key));
+            lines.push(format!(
+                "  --aws-sigv4 {} -u {}",
+                shell_quote(&provider),
+                shell_quote(&keys)
+            ));
+
+            let session_token = env.replace(session_token);
+            if !session_token.is_empty() {
+                lines.push(format!(
+                    "  -H {}",
+                    shell_quote(&format!("x-amz-security-token: {}", session_token))
+                ));
+            }
+        }
+        Auth::None | Auth::Inherit => {}
     }
 
     // Add headers (skip disabled ones)
@@ -866,6 +899,42 @@ mod tests {
         assert!(cmd.contains("-u 'user@domain.com:p@ss:word!'"));
     }
 
+    #[test]
+    fn test_digest_auth() {
+        let req = Request {
+            method: Method::GET,
+            url: "https://api.example.com".to_string(),
+            auth: Auth::Digest {
+                username: "user".to_string(),
+                password: "pass".to_string(),
+            },
+            ..Default::default()
+        };
+        let env = EnvironmentChain::new();
+        let cmd = generate_curl_command(&req, env);
+        assert!(cmd.contains("--digest -u 'user:pass'"));
+    }
+
+    #[test]
+    fn test_aws_sigv4_auth() {
+        let req = Request {
+            method: Method::GET,
+            url: "https://api.example.com".to_string(),
+            auth: Auth::AwsSigV4 {
+                access_key: "AKID".to_string(),
+                secret_key: "secret".to_string(),
+                session_token: "token".to_string(),
+                region: "us-east-1".to_string(),
+                service: "execute-api".to_string(),
+            },
+            ..Default::default()
+        };
+        let env = EnvironmentChain::new();
+        let cmd = generate_curl_command(&req, env);
+        assert!(cmd.contains("--aws-sigv4 'aws:amz:us-east-1:execute-api' -u 'AKID:secret'"));
+        assert!(cmd.contains("-H 'x-amz-security-token: token'"));
+    }
+
     #[test]
     fn test_json_with_quotes() {
         let req = Request {
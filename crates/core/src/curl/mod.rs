@@ -2,4 +2,4 @@ mod generator;
 mod parser;
 
 pub use self::generator::generate_curl_command;
-pub use self::parser::parse_curl_command;
+pub use self::parser::{CurlCommand, parse_curl, parse_curl_command};
@@ -8,8 +8,9 @@
 //! - Headers: -H, --header
 //! - Data: -d, --data, --data-raw, --data-binary
 //! - Form data: -F, --form (with file uploads using @filepath)
-//! - Authentication: -u/--user (Basic), --bearer (Bearer token)
+//! - Authentication: -u/--user (Basic), --digest (Digest), --aws-sigv4 (AWS), --bearer (Bearer token)
 //! - Common flags: -k/--insecure, -L/--location, --compressed
+//! - Proxy: -x/--proxy, -U/--proxy-user, returned apart from the request
 //!
 //! # Example
 //! ```
@@ -27,6 +28,7 @@ use crate::http::{
     KeyFileList, KeyValList, KeyValue,
     request::{Auth, Method, Request, RequestBody},
 };
+use crate::proxy::{ProxyMode, ProxySettings};
 
 #[derive(Debug, Default)]
 struct CurlParser {
@@ -40,9 +42,20 @@ struct CurlParser {
     form_files: Vec<(String, PathBuf)>,
     user: Option<String>,
     bearer: Option<String>,
+    digest: bool,
+    aws_sigv4: Option<String>,
     compressed: bool,
     insecure: bool,
     location: bool,
+    proxy: Option<String>,
+    proxy_user: Option<String>,
+}
+
+/// A parsed curl command, the proxy is a setting of the collection instead of the request
+#[derive(Debug)]
+pub struct CurlCommand {
+    pub request: Request,
+    pub proxy: Option<ProxySettings>,
 }
 
 impl CurlParser {
@@ -120,6 +133,18 @@ impl CurlParser {
                     self.user = Some(args[i].clone());
                 }
 
+                "--digest" => {
+                    self.digest = true;
+                }
+
+                "--aws-sigv4" => {
+                    i += 1;
+                    if i >= args.len() {
+                        return Err(anyhow!("Missing value for {}", arg));
+                    }
+                    self.aws_sigv4 = Some(args[i].clone());
+                }
+
                 "--bearer" => {
                     i += 1;
                     if i >= args.len() {
@@ -155,9 +180,17 @@ impl CurlParser {
                     i += 1;
                 }
 
-                // Proxy options (ignored but consume the next arg)
-                "-x" | "--proxy" => {
+                "-x" | "--proxy" | "-U" | "--proxy-user" => {
                     i += 1;
+                    if i >= args.len() {
+                        return Err(anyhow!("Missing value for {}", arg));
+                    }
+                    let value = Some(args[i].clone());
+                    if matches!(arg.as_str(), "-x" | "--proxy") {
+                        self.proxy = value;
+                    } else {
+                        self.proxy_user = value;
+                    }
                 }
 
                 // Referrer and User-Agent (add as headers)
@@ -281,7 +314,20 @@ impl CurlParser {
         Ok(())
     }
 
-    fn build(self) -> Result<Request> {
+    fn build(self) -> Result<CurlCommand> {
+        let proxy = self.proxy.as_ref().map(|url| {
+            let (username, password) = match self.proxy_user.as_deref() {
+                Some(user) => user.split_once(':').unwrap_or((user, "")),
+                None => ("", ""),
+            };
+            ProxySettings {
+                mode: ProxyMode::Manual,
+                url: url.clone(),
+                username: username.to_string(),
+                password: password.to_string(),
+                no_proxy: String::new(),
+            }
+        });
         let url = self.url.context("No URL provided")?;
 
         // Determine method
@@ -343,16 +389,28 @@ impl CurlParser {
         let auth = if let Some(token) = self.bearer {
             Auth::Bearer { token }
         } else if let Some(user) = self.user {
-            if let Some(colon_idx) = user.find(':') {
-                Auth::Basic {
-                    username: user[..colon_idx].to_string(),
-                    password: user[colon_idx + 1..].to_string(),
+            let (username, password) = match user.find(':') {
+                Some(colon_idx) => (
+                    user[..colon_idx].to_string(),
+                    user[colon_idx + 1..].to_string(),
+                ),
+                None => (user, String::new()),
+            };
+
+            if let Some(provider) = self.aws_sigv4 {
+                // Provider is formatted as `aws:amz:<region>:<service>`
+                let mut parts = provider.split(':').skip(2);
+                Auth::AwsSigV4 {
+                    access_key: username,
+                    secret_key: password,
+                    session_token: String::new(),
+                    region: parts.next().unwrap_or_default().to_string(),
+                    service: parts.next().unwrap_or_default().to_string(),
                 }
+            } else if self.digest {
+                Auth::Digest { username, password }
             } else {
-                Auth::Basic {
-                    username: user,
-                    password: String::new(),
-                }
+                Auth::Basic { username, password }
             }
         } else {
             Auth::None
@@ -368,7 +426,7 @@ impl CurlParser {
             });
         }
 
-        Ok(Request {
+        let request = Request {
             description: "Imported from curl".to_string(),
             method,
             url,
@@ -380,12 +438,20 @@ impl CurlParser {
             assertions: Default::default(),
             pre_request: None,
             post_request: None,
-        })
+            abort_on_script_error: None,
+        };
+
+        Ok(CurlCommand { request, proxy })
     }
 }
 
 /// Parse a curl command string into a Request
 pub fn parse_curl_command(command: &str) -> Result<Request> {
+    parse_curl(command).map(|command| command.request)
+}
+
+/// Parse a curl command string into a Request along with its proxy
+pub fn parse_curl(command: &str) -> Result<CurlCommand> {
     let args = tokenize_command(command)?;
     let mut parser = CurlParser::new();
     parser.parse_args(&args)?;
@@ -528,6 +594,34 @@ mod tests {
         assert_eq!(req.url, "https://api.example.com/users");
     }
 
+    #[test]
+    fn test_proxy() {
+        let cmd = "curl -x http://proxy.local:3128 -U alice:secret https://api.example.com";
+        let command = parse_curl(cmd).unwrap();
+
+        assert_eq!(command.request.url, "https://api.example.com");
+        assert_eq!(
+            command.proxy,
+            Some(ProxySettings {
+                mode: ProxyMode::Manual,
+                url: "http://proxy.local:3128".to_string(),
+                username: "alice".to_string(),
+                password: "secret".to_string(),
+                no_proxy: String::new(),
+            })
+        );
+
+        let command = parse_curl("curl --proxy socks5h://localhost:1080 https://example.com");
+        assert_eq!(command.unwrap().proxy.unwrap().username, "");
+        assert!(
+            parse_curl("curl https://example.com")
+                .unwrap()
+                .proxy
+                .is_none()
+        );
+        assert!(parse_curl("curl https://example.com -x").is_err());
+    }
+
     #[test]
     fn test_post_with_json() {
         let cmd = r#"curl -X POST https://api.example.com/users -H "Content-Type: application/json" -d '{"name":"John"}'"#;
@@ -558,6 +652,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_digest_auth() {
+        let cmd = "curl --digest -u user:pass https://api.example.com/protected";
+        let req = parse_curl_command(cmd).unwrap();
+        match req.auth {
+            Auth::Digest { username, password } => {
+                assert_eq!(username, "user");
+                assert_eq!(password, "pass");
+            }
+            _ => panic!("Expected Digest auth"),
+        }
+    }
+
+    #[test]
+    fn test_aws_sigv4_auth() {
+        let cmd =
+            "curl --aws-sigv4 aws:amz:us-east-1:execute-api -u AKID:secret https://api.example.com";
+        let req = parse_curl_command(cmd).unwrap();
+        match req.auth {
+            Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                region,
+                service,
+                ..
+            } => {
+                assert_eq!(access_key, "AKID");
+                assert_eq!(secret_key, "secret");
+                assert_eq!(region, "us-east-1");
+                assert_eq!(service, "execute-api");
+            }
+            _ => panic!("Expected AWS SigV4 auth"),
+        }
+    }
+
     #[test]
     fn test_bearer_token() {
         let cmd = "curl --bearer mytoken123 https://api.example.com/data";
@@ -1,7 +1,11 @@
 use super::KeyValList;
-use super::environment::{Environment, EnvironmentChain, EnvironmentKey};
+use super::environment::{Environment, EnvironmentChain, EnvironmentKey, RuntimeVariables};
+use crate::auth::AuthState;
+use crate::cookies::CookieJar;
+use crate::http::request::Auth;
 use crate::http::{CollectionKey, VarMap};
 use crate::new_id_type;
+use crate::proxy::ProxySettings;
 use crate::{
     http::environment::Environments,
     persistence::{JS_EXTENSION, REQUESTS, SCRIPTS, TOML_EXTENSION},
@@ -23,6 +27,7 @@ pub struct Folder {
     pub entries: Vec<Entry>,
     pub path: PathBuf,
     pub expanded: bool,
+    pub auth: Auth,
 }
 
 #[derive(Debug, Clone)]
@@ -65,6 +70,15 @@ pub struct Collection {
     pub dotenv: Arc<VarMap>,
     pub disable_ssl: bool,
     pub timeout: Duration,
+    /// Abort the send when a pre-request script fails instead of sending the original request
+    pub abort_on_script_error: bool,
+    /// Overrides the proxy of the app settings when set
+    pub proxy: Option<ProxySettings>,
+    pub auth: Auth,
+    pub auth_state: Arc<AuthState>,
+    pub runtime_vars: Arc<RuntimeVariables>,
+    /// Cookies of requests sent in this collection
+    pub cookies: Arc<CookieJar>,
 }
 
 impl Collection {
@@ -109,6 +123,13 @@ impl Collection {
         recurse(&mut self.entries.iter_mut(), id)
     }
 
+    pub fn folder(&self, id: FolderId) -> Option<&Folder> {
+        self.iter().find_map(|entry| match entry {
+            Entry::Folder(folder) if folder.id == id => Some(folder),
+            _ => None,
+        })
+    }
+
     pub fn folder_path(&self, id: FolderId) -> Vec<&Folder> {
         fn recurse<'a>(
             entries: impl Iterator<Item = &'a Entry>,
@@ -130,6 +151,50 @@ impl Collection {
         recurse(self.entries.iter(), id, Vec::new())
     }
 
+    /// Folders containing the request, outermost first
+    pub fn request_folders(&self, id: RequestId) -> Vec<&Folder> {
+        fn recurse<'a>(entries: &'a [Entry], id: RequestId, path: &mut Vec<&'a Folder>) -> bool {
+            for entry in entries {
+                match entry {
+                    Entry::Item(item) if item.id == id => return true,
+                    Entry::Folder(folder) => {
+                        path.push(folder);
+                        if recurse(&folder.entries, id, path) {
+                            return true;
+                        }
+                        path.pop();
+                    }
+                    Entry::Item(_) => {}
+                }
+            }
+            false
+        }
+
+        let mut path = Vec::new();
+        recurse(&self.entries, id, &mut path);
+        path
+    }
+
+    /// Replaces `Auth::Inherit` with the auth of the closest folder or the collection
+    pub fn resolve_auth(&self, id: RequestId, auth: Auth) -> Auth {
+        if auth != Auth::Inherit {
+            return auth;
+        }
+
+        let inherited = self
+            .request_folders(id)
+            .into_iter()
+            .rev()
+            .map(|folder| &folder.auth)
+            .find(|auth| **auth != Auth::Inherit)
+            .unwrap_or(&self.auth);
+
+        match inherited {
+            Auth::Inherit => Auth::None,
+            auth => auth.clone(),
+        }
+    }
+
     pub fn get_active_environment(&self) -> Option<&Environment> {
         self.active_environment
             .and_then(|key| self.environments.get(key))
@@ -238,6 +303,7 @@ impl Collection {
                     entries: Vec::new(),
                     expanded: true,
                     path: path.clone(),
+                    auth: Auth::Inherit,
                 }),
                 path,
             )
@@ -365,7 +431,10 @@ impl Collection {
             .map(|e| e.vars())
             .unwrap_or_default();
 
-        EnvironmentChain::from_iter(Arc::clone(&self.dotenv), [env])
+        EnvironmentChain::from_iter(
+            Arc::clone(&self.dotenv),
+            [self.runtime_vars.snapshot(), env],
+        )
     }
 
     pub fn collection_env_chain(&self) -> EnvironmentChain {
@@ -374,7 +443,10 @@ impl Collection {
             .map(|e| e.vars())
             .unwrap_or_default();
 
-        EnvironmentChain::from_iter(Arc::clone(&self.dotenv), [env])
+        EnvironmentChain::from_iter(
+            Arc::clone(&self.dotenv),
+            [self.runtime_vars.snapshot(), env],
+        )
     }
 
     pub fn dotenv_env_chain(&self) -> EnvironmentChain {
@@ -398,6 +470,12 @@ impl Default for Collection {
             dotenv: Default::default(),
             disable_ssl: false,
             timeout: Duration::from_secs(300),
+            abort_on_script_error: false,
+            proxy: None,
+            auth: Auth::None,
+            auth_state: Default::default(),
+            runtime_vars: Default::default(),
+            cookies: Default::default(),
         }
     }
 }
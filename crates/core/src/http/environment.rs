@@ -1,6 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{Arc, RwLock},
 };
 
 use crate::new_id_type;
@@ -106,6 +106,31 @@ impl Environment {
     }
 }
 
+/// Variables set by scripts at runtime, layered above the active environment
+#[derive(Debug, Default)]
+pub struct RuntimeVariables {
+    vars: RwLock<Arc<VarMap>>,
+}
+
+impl RuntimeVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> Arc<VarMap> {
+        Arc::clone(&self.vars.read().expect("Runtime variables lock poisoned"))
+    }
+
+    pub fn extend(&self, vars: impl IntoIterator<Item = (String, String)>) {
+        let mut lock = self.vars.write().expect("Runtime variables lock poisoned");
+        Arc::make_mut(&mut lock).extend(vars);
+    }
+
+    pub fn clear(&self) {
+        *self.vars.write().expect("Runtime variables lock poisoned") = Default::default();
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct EnvironmentChain {
     dotenv: Arc<VarMap>,
@@ -130,6 +155,12 @@ impl EnvironmentChain {
         }
     }
 
+    /// Adds a layer that takes precedence over all existing ones
+    pub fn with_layer(mut self, vars: Arc<VarMap>) -> Self {
+        self.vars.insert(0, vars);
+        self
+    }
+
     fn get_named(name: &str, vars: &VarMap) -> Option<String> {
         vars.get(name).map(|s| s.to_owned())
     }
@@ -66,9 +66,36 @@ impl From<&JwtAlgorithm> for Algorithm {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuth2Grant {
+    #[default]
+    ClientCredentials,
+    Password,
+    AuthorizationCode,
+    RefreshToken,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct OAuth2 {
+    pub grant: OAuth2Grant,
+    pub token_url: String,
+    pub auth_url: String,
+    pub redirect_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: String,
+    pub username: String,
+    pub password: String,
+    pub refresh_token: String,
+    pub use_pkce: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Auth {
     None,
+    /// Uses the auth of the closest parent folder, falling back to the collection
+    Inherit,
     Basic {
         username: String,
         password: String,
@@ -76,6 +103,10 @@ pub enum Auth {
     Bearer {
         token: String,
     },
+    Digest {
+        username: String,
+        password: String,
+    },
     APIKey {
         key: String,
         value: String,
@@ -87,6 +118,14 @@ pub enum Auth {
         payload: String,
         add_to: AuthIn,
     },
+    OAuth2(OAuth2),
+    AwsSigV4 {
+        access_key: String,
+        secret_key: String,
+        session_token: String,
+        region: String,
+        service: String,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, VariantArray, Display, Default)]
@@ -116,6 +155,8 @@ pub struct Request {
     pub assertions: Assertions,
     pub pre_request: Option<String>,
     pub post_request: Option<String>,
+    /// Overrides the collection `abort_on_script_error` setting when set
+    pub abort_on_script_error: Option<bool>,
 }
 
 impl Request {
@@ -138,6 +179,7 @@ impl Default for Request {
             assertions: Assertions::default(),
             pre_request: None,
             post_request: None,
+            abort_on_script_error: None,
         }
     }
 }
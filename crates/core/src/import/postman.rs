@@ -16,8 +16,10 @@ use crate::http::request::{
     RequestBody as SanchaarRequestBody,
 };
 use crate::http::{KeyValList, KeyValue};
-use crate::persistence::collections::{EncodedCollection, save_collection};
-use crate::persistence::request::{encode_request, save_req_to_file};
+use crate::persistence::collections::{
+    EncodedCollection, EncodedFolder, encode_folder_auth, save_collection, save_folder,
+};
+use crate::persistence::request::{encode_auth, encode_request, save_req_to_file};
 use crate::persistence::{REQUESTS, TOML_EXTENSION};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -69,14 +71,22 @@ pub async fn import_postman_collection(postman_path: &Path, output_dir: &Path) -
         }
     }
 
+    let collection_auth = match extract_auth(postman_collection.auth) {
+        SanchaarAuth::Inherit => None,
+        auth => encode_auth(auth),
+    };
+
     // TODO: Implement collection variable extraction
     let encoded_collection = EncodedCollection {
         name: collection_name.clone(),
         version: crate::persistence::Version::V1,
         disable_cert_verification: false,
         timeout: std::time::Duration::from_secs(300),
+        abort_on_script_error: false,
         default_environment: None,
         headers: vec![],
+        auth: collection_auth,
+        proxy: None,
     };
 
     save_collection(output_dir.to_path_buf(), encoded_collection).await?;
@@ -141,12 +151,25 @@ async fn process_folder(folder: ItemGroup, base_path: &Path) -> Result<Entry> {
         }
     }
 
+    let auth = extract_auth(folder.auth);
+    if let Some(encoded) = encode_folder_auth(auth.clone()) {
+        save_folder(
+            &folder_path,
+            EncodedFolder {
+                auth: Some(encoded),
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to save folder settings: {:?}", folder_path))?;
+    }
+
     Ok(Entry::Folder(Folder {
         id: FolderId::new(),
         name: sanitized_name,
         entries,
         path: folder_path,
         expanded: false,
+        auth,
     }))
 }
 
@@ -180,6 +203,7 @@ fn convert_request(request: Request, description: Option<&Description>) -> Resul
                 assertions: Default::default(),
                 pre_request: None,
                 post_request: None,
+                abort_on_script_error: None,
             })
         }
         Request::String(url) => Ok(SanchaarRequest {
@@ -190,10 +214,11 @@ fn convert_request(request: Request, description: Option<&Description>) -> Resul
             body: SanchaarRequestBody::None,
             query_params: KeyValList::new(),
             path_params: KeyValList::new(),
-            auth: SanchaarAuth::None,
+            auth: SanchaarAuth::Inherit,
             assertions: Default::default(),
             pre_request: None,
             post_request: None,
+            abort_on_script_error: None,
         }),
     }
 }
@@ -327,9 +352,47 @@ fn extract_auth(auth: Option<Auth>) -> SanchaarAuth {
                     .to_string();
                 SanchaarAuth::Bearer { token }
             }
+            AuthType::Digest => {
+                let username = auth
+                    .digest
+                    .iter()
+                    .find(|a| a.key == "username")
+                    .and_then(|a| a.value.as_ref())
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let password = auth
+                    .digest
+                    .iter()
+                    .find(|a| a.key == "password")
+                    .and_then(|a| a.value.as_ref())
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                SanchaarAuth::Digest { username, password }
+            }
+            AuthType::Awsv4 => {
+                let attr = |key: &str| {
+                    auth.awsv4
+                        .iter()
+                        .find(|a| a.key == key)
+                        .and_then(|a| a.value.as_ref())
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                SanchaarAuth::AwsSigV4 {
+                    access_key: attr("accessKey"),
+                    secret_key: attr("secretKey"),
+                    session_token: attr("sessionToken"),
+                    region: attr("region"),
+                    service: attr("service"),
+                }
+            }
             _ => SanchaarAuth::None,
         },
-        None => SanchaarAuth::None,
+        // Postman items without auth inherit it from their parent
+        None => SanchaarAuth::Inherit,
     }
 }
 
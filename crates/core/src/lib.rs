@@ -1,12 +1,16 @@
 #![allow(mismatched_lifetime_syntaxes)]
 pub mod assertions;
+pub mod auth;
 pub mod client;
+pub mod cookies;
 pub mod curl;
 pub mod http;
 pub mod ids;
 pub mod import;
 pub mod perf;
 pub mod persistence;
+pub mod proxy;
+pub mod runner;
 pub mod scripting;
 pub mod transformers;
 pub mod utils;
@@ -1,186 +1,179 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
-/// Histogram bucket for efficient percentile calculation
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Histogram {
-    /// Bucket boundaries in milliseconds
-    buckets: Vec<f64>,
-    /// Count of values in each bucket
-    counts: Vec<AtomicU64>,
-    /// Total count of all values
-    total_count: AtomicU64,
-    /// Sum of all values (for average calculation)
-    sum_millis: AtomicU64,
-    /// Minimum value seen
-    min_millis: AtomicU64,
-    /// Maximum value seen
-    max_millis: AtomicU64,
+use super::threshold::{Threshold, ThresholdResult, evaluate_thresholds};
+
+/// Responses with a latency up to the bound of the bucket, above the previous bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// Upper bound of the bucket, `None` for the last bucket holding all slower responses
+    pub le: Option<Duration>,
+    pub count: u64,
 }
 
-impl Clone for Histogram {
-    fn clone(&self) -> Self {
-        let buckets = self.buckets.clone();
-        let counts = self
-            .counts
-            .iter()
-            .map(|c| AtomicU64::new(c.load(Ordering::Relaxed)))
-            .collect();
+/// Upper bounds in milliseconds of the buckets reported in `PerfStats::latency_buckets`
+const REPORT_BUCKETS: [f64; 28] = [
+    1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0, 200.0, 300.0, 500.0, 700.0, 900.0, 1200.0, 1500.0,
+    2000.0, 2500.0, 3000.0, 4000.0, 5000.0, 7000.0, 10000.0, 15000.0, 20000.0, 25000.0, 30000.0,
+    40000.0, 50000.0, 60000.0,
+];
 
-        Self {
-            buckets,
-            counts,
-            total_count: AtomicU64::new(self.total_count.load(Ordering::Relaxed)),
-            sum_millis: AtomicU64::new(self.sum_millis.load(Ordering::Relaxed)),
-            min_millis: AtomicU64::new(self.min_millis.load(Ordering::Relaxed)),
-            max_millis: AtomicU64::new(self.max_millis.load(Ordering::Relaxed)),
-        }
-    }
-}
+/// 128 buckets per power of two keep the width of a bucket under 1% of its values
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKETS: u64 = 1 << SUB_BUCKET_BITS;
 
-impl Default for Histogram {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Log-linear latency histogram in microseconds, only the buckets with values are stored
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    counts: BTreeMap<u32, u64>,
+    total_count: u64,
+    sum_micros: u64,
+    min_micros: Option<u64>,
+    max_micros: Option<u64>,
 }
 
 impl Histogram {
     pub fn new() -> Self {
-        let buckets = vec![
-            1.0,
-            2.0,
-            5.0,
-            10.0,
-            25.0,
-            50.0,
-            100.0,
-            200.0,
-            300.0,
-            500.0,
-            700.0,
-            900.0,
-            1200.0,
-            1500.0,
-            2000.0,
-            2500.0,
-            3000.0,
-            4000.0,
-            5000.0,
-            7000.0,
-            10000.0,
-            15000.0,
-            20000.0,
-            25000.0,
-            30000.0,
-            40000.0,
-            50000.0,
-            60000.0,
-            f64::INFINITY,
-        ];
-        let counts = buckets.iter().map(|_| AtomicU64::new(0)).collect();
+        Self::default()
+    }
 
-        Self {
-            buckets,
-            counts,
-            total_count: AtomicU64::new(0),
-            sum_millis: AtomicU64::new(0),
-            min_millis: AtomicU64::new(u64::MAX),
-            max_millis: AtomicU64::new(0),
+    /// Values below `SUB_BUCKETS` get a bucket each, larger values are split in `SUB_BUCKETS`
+    /// buckets per power of two
+    fn index(micros: u64) -> u32 {
+        if micros < SUB_BUCKETS {
+            return micros as u32;
         }
+        let shift = 63 - micros.leading_zeros() - SUB_BUCKET_BITS;
+        let mantissa = micros >> shift;
+        (shift as u64 * SUB_BUCKETS + mantissa) as u32
     }
 
-    fn observe(&self, duration: Duration) {
-        let millis = duration.as_millis() as u64;
-        let millis_f64 = duration.as_secs_f64() * 1000.0;
-
-        for (i, &boundary) in self.buckets.iter().enumerate() {
-            if millis_f64 <= boundary {
-                self.counts[i].fetch_add(1, Ordering::Relaxed);
-                break;
-            }
+    /// Lowest value and width of the bucket
+    fn bounds(index: u32) -> (u64, u64) {
+        let index = index as u64;
+        if index < SUB_BUCKETS {
+            return (index, 1);
         }
+        let shift = index / SUB_BUCKETS - 1;
+        let mantissa = index - shift * SUB_BUCKETS;
+        (mantissa << shift, 1 << shift)
+    }
 
-        self.total_count.fetch_add(1, Ordering::Relaxed);
-        self.sum_millis.fetch_add(millis, Ordering::Relaxed);
-
-        let mut current_min = self.min_millis.load(Ordering::Relaxed);
-        while millis < current_min {
-            match self.min_millis.compare_exchange(
-                current_min,
-                millis,
-                Ordering::Relaxed,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => break,
-                Err(x) => current_min = x,
-            }
-        }
+    fn observe(&mut self, duration: Duration) {
+        let micros = duration.as_micros().min(u64::MAX as u128) as u64;
 
-        let mut current_max = self.max_millis.load(Ordering::Relaxed);
-        while millis > current_max {
-            match self.max_millis.compare_exchange(
-                current_max,
-                millis,
-                Ordering::Relaxed,
-                Ordering::Relaxed,
-            ) {
-                Ok(_) => break,
-                Err(x) => current_max = x,
-            }
-        }
+        *self.counts.entry(Self::index(micros)).or_insert(0) += 1;
+        self.total_count += 1;
+        self.sum_micros = self.sum_micros.saturating_add(micros);
+        self.min_micros = Some(self.min_micros.map_or(micros, |min| min.min(micros)));
+        self.max_micros = Some(self.max_micros.map_or(micros, |max| max.max(micros)));
     }
 
+    /// Value below which `p` of the values fall, interpolated within its bucket
     fn percentile(&self, p: f64) -> Option<Duration> {
-        let total = self.total_count.load(Ordering::Relaxed);
-        if total == 0 {
-            return None;
-        }
+        let (min, max) = (self.min_micros?, self.max_micros?);
 
-        let target = (total as f64 * p) as u64;
+        // Rank of the value, the single value of a histogram is every percentile
+        let rank = ((self.total_count as f64 * p).ceil() as u64).clamp(1, self.total_count);
         let mut cumulative = 0u64;
 
-        for (i, count) in self.counts.iter().enumerate() {
-            cumulative += count.load(Ordering::Relaxed);
-            if cumulative >= target {
-                let millis = self.buckets[i];
-                return Some(Duration::from_millis(millis as u64));
+        for (&index, &count) in &self.counts {
+            if cumulative + count >= rank {
+                let (low, width) = Self::bounds(index);
+                let fraction = (rank - cumulative) as f64 / count as f64;
+                let micros = (low as f64 + width as f64 * fraction) as u64;
+                return Some(Duration::from_micros(micros.clamp(min, max)));
             }
+            cumulative += count;
         }
 
         None
     }
 
-    fn min(&self) -> Option<Duration> {
-        let min_val = self.min_millis.load(Ordering::Relaxed);
-        if min_val == u64::MAX {
-            None
-        } else {
-            Some(Duration::from_millis(min_val))
+    /// Counts of the report buckets, values are counted in the bucket of their histogram bucket
+    fn buckets(&self) -> Vec<HistogramBucket> {
+        let mut counts = vec![0u64; REPORT_BUCKETS.len() + 1];
+        for (&index, &count) in &self.counts {
+            let (low, _) = Self::bounds(index);
+            let millis = low as f64 / 1000.0;
+            let bucket = REPORT_BUCKETS
+                .iter()
+                .position(|&bound| millis <= bound)
+                .unwrap_or(REPORT_BUCKETS.len());
+            counts[bucket] += count;
         }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| HistogramBucket {
+                le: REPORT_BUCKETS
+                    .get(i)
+                    .map(|&bound| Duration::from_secs_f64(bound / 1000.0)),
+                count,
+            })
+            .collect()
+    }
+
+    fn min(&self) -> Option<Duration> {
+        self.min_micros.map(Duration::from_micros)
     }
 
     fn max(&self) -> Option<Duration> {
-        let max_val = self.max_millis.load(Ordering::Relaxed);
-        if max_val == 0 && self.total_count.load(Ordering::Relaxed) == 0 {
-            None
-        } else {
-            Some(Duration::from_millis(max_val))
-        }
+        self.max_micros.map(Duration::from_micros)
     }
 
     fn average(&self) -> Option<Duration> {
-        let total = self.total_count.load(Ordering::Relaxed);
-        if total == 0 {
-            return None;
-        }
-
-        let sum = self.sum_millis.load(Ordering::Relaxed);
-        Some(Duration::from_millis(sum / total))
+        (self.total_count > 0).then(|| Duration::from_micros(self.sum_micros / self.total_count))
     }
 }
 
+/// Requests completed during one second of the run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub requests: u64,
+    pub failures: u64,
+    #[serde(skip, default = "Histogram::new")]
+    histogram: Histogram,
+}
+
+/// Throughput and latency of one second of the run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelinePoint {
+    /// Seconds since the start of the run
+    pub second: u64,
+    /// Requests completed in the second, the throughput in requests per second
+    pub requests: u64,
+    /// Share of the requests that failed, from 0 to 1
+    pub error_rate: f64,
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+    pub p99: Option<Duration>,
+}
+
+/// Outcomes of one step of the scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepMetrics {
+    pub name: String,
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    #[serde(skip, default = "Histogram::new")]
+    histogram: Histogram,
+}
+
+/// Request counts and latencies of one step of the scenario
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepStats {
+    pub name: String,
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+    pub p99: Option<Duration>,
+    pub avg: Option<Duration>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerfMetrics {
     pub total_requests: u64,
@@ -191,6 +184,15 @@ pub struct PerfMetrics {
     histogram: Histogram,
     pub status_codes: HashMap<u16, u64>,
     pub errors: HashMap<String, u64>,
+    /// Buckets for each second of the run, indexed by the second requests completed in
+    #[serde(default)]
+    pub timeline: Vec<TimelineBucket>,
+    /// Breakdown by scenario step, in the order of the steps
+    #[serde(default)]
+    pub steps: Vec<StepMetrics>,
+    /// Thresholds evaluated with the stats, set once the run completed
+    #[serde(default)]
+    pub thresholds: Vec<Threshold>,
 }
 
 impl PerfMetrics {
@@ -203,20 +205,103 @@ impl PerfMetrics {
             histogram: Histogram::new(),
             status_codes: HashMap::new(),
             errors: HashMap::new(),
+            timeline: Vec::new(),
+            steps: Vec::new(),
+            thresholds: Vec::new(),
+        }
+    }
+
+    /// Metrics of a scenario with the given step names
+    pub fn with_steps(names: impl IntoIterator<Item = String>) -> Self {
+        let steps = names
+            .into_iter()
+            .map(|name| StepMetrics {
+                name,
+                total_requests: 0,
+                failed_requests: 0,
+                histogram: Histogram::new(),
+            })
+            .collect();
+        Self {
+            steps,
+            ..Self::new()
+        }
+    }
+
+    /// Bucket of the second `elapsed` falls in, adding buckets for the seconds without requests
+    fn bucket(&mut self, elapsed: Duration) -> &mut TimelineBucket {
+        let second = elapsed.as_secs() as usize;
+        if self.timeline.len() <= second {
+            self.timeline
+                .resize_with(second + 1, TimelineBucket::default);
         }
+        &mut self.timeline[second]
     }
 
-    pub fn record_success(&mut self, duration: Duration, status_code: u16) {
+    /// Records a response to the request of `step` received `elapsed` into the run.
+    /// Responses with an error status count as failed requests.
+    pub fn record_response(
+        &mut self,
+        step: usize,
+        duration: Duration,
+        status_code: u16,
+        elapsed: Duration,
+    ) {
+        let failed = status_code >= 400;
         self.total_requests += 1;
-        self.successful_requests += 1;
+        if failed {
+            self.failed_requests += 1;
+        } else {
+            self.successful_requests += 1;
+        }
         self.histogram.observe(duration);
         *self.status_codes.entry(status_code).or_insert(0) += 1;
+
+        if let Some(step) = self.steps.get_mut(step) {
+            step.total_requests += 1;
+            step.failed_requests += u64::from(failed);
+            step.histogram.observe(duration);
+        }
+
+        let bucket = self.bucket(elapsed);
+        bucket.requests += 1;
+        bucket.failures += u64::from(failed);
+        bucket.histogram.observe(duration);
     }
 
-    pub fn record_failure(&mut self, error: String) {
+    /// Records a request of `step` that failed `elapsed` into the run
+    pub fn record_failure(&mut self, step: usize, error: String, elapsed: Duration) {
         self.total_requests += 1;
         self.failed_requests += 1;
         *self.errors.entry(error).or_insert(0) += 1;
+
+        if let Some(step) = self.steps.get_mut(step) {
+            step.total_requests += 1;
+            step.failed_requests += 1;
+        }
+
+        let bucket = self.bucket(elapsed);
+        bucket.requests += 1;
+        bucket.failures += 1;
+    }
+
+    pub fn timeline(&self) -> Vec<TimelinePoint> {
+        self.timeline
+            .iter()
+            .enumerate()
+            .map(|(second, bucket)| TimelinePoint {
+                second: second as u64,
+                requests: bucket.requests,
+                error_rate: if bucket.requests > 0 {
+                    bucket.failures as f64 / bucket.requests as f64
+                } else {
+                    0.0
+                },
+                p50: bucket.histogram.percentile(0.50),
+                p95: bucket.histogram.percentile(0.95),
+                p99: bucket.histogram.percentile(0.99),
+            })
+            .collect()
     }
 
     pub fn calculate_stats(&self) -> PerfStats {
@@ -233,7 +318,7 @@ impl PerfMetrics {
             0.0
         };
 
-        PerfStats {
+        let mut stats = PerfStats {
             total_requests: self.total_requests,
             successful_requests: self.successful_requests,
             failed_requests: self.failed_requests,
@@ -247,7 +332,25 @@ impl PerfMetrics {
             status_codes: self.status_codes.clone(),
             errors: self.errors.clone(),
             total_duration: self.total_duration,
-        }
+            timeline: self.timeline(),
+            latency_buckets: self.histogram.buckets(),
+            steps: self
+                .steps
+                .iter()
+                .map(|step| StepStats {
+                    name: step.name.clone(),
+                    total_requests: step.total_requests,
+                    failed_requests: step.failed_requests,
+                    p50: step.histogram.percentile(0.50),
+                    p95: step.histogram.percentile(0.95),
+                    p99: step.histogram.percentile(0.99),
+                    avg: step.histogram.average(),
+                })
+                .collect(),
+            thresholds: Vec::new(),
+        };
+        stats.thresholds = evaluate_thresholds(&self.thresholds, &stats);
+        stats
     }
 }
 
@@ -272,4 +375,139 @@ pub struct PerfStats {
     pub status_codes: HashMap<u16, u64>,
     pub errors: HashMap<String, u64>,
     pub total_duration: Duration,
+    #[serde(default)]
+    pub timeline: Vec<TimelinePoint>,
+    /// Latency histogram of the received responses
+    #[serde(default)]
+    pub latency_buckets: Vec<HistogramBucket>,
+    #[serde(default)]
+    pub steps: Vec<StepStats>,
+    #[serde(default)]
+    pub thresholds: Vec<ThresholdResult>,
+}
+
+impl PerfStats {
+    /// Share of the requests that failed, from 0 to 1
+    pub fn error_rate(&self) -> f64 {
+        if self.total_requests > 0 {
+            self.failed_requests as f64 / self.total_requests as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether every threshold passed, true without thresholds
+    pub fn thresholds_passed(&self) -> bool {
+        self.thresholds.iter().all(|result| result.passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_buckets_by_second() {
+        let mut metrics = PerfMetrics::new();
+        metrics.record_response(
+            0,
+            Duration::from_millis(20),
+            200,
+            Duration::from_millis(100),
+        );
+        metrics.record_response(
+            0,
+            Duration::from_millis(40),
+            200,
+            Duration::from_millis(900),
+        );
+        metrics.record_failure(
+            0,
+            "Request timeout".to_string(),
+            Duration::from_millis(2500),
+        );
+
+        let timeline = metrics.calculate_stats().timeline;
+        assert_eq!(timeline.len(), 3);
+
+        assert_eq!(timeline[0].requests, 2);
+        assert_eq!(timeline[0].error_rate, 0.0);
+        assert!(timeline[0].p50.is_some());
+
+        // Seconds without requests are kept so the chart has no gaps
+        assert_eq!(timeline[1].requests, 0);
+        assert_eq!(timeline[1].p99, None);
+
+        assert_eq!(timeline[2].second, 2);
+        assert_eq!(timeline[2].requests, 1);
+        assert_eq!(timeline[2].error_rate, 1.0);
+        assert_eq!(timeline[2].p95, None);
+    }
+
+    #[test]
+    fn test_timeline_percentiles() {
+        let mut metrics = PerfMetrics::new();
+        for millis in 1..=100 {
+            metrics.record_response(0, Duration::from_millis(millis), 200, Duration::ZERO);
+        }
+
+        let point = &metrics.timeline()[0];
+        assert_eq!(point.requests, 100);
+        assert!(point.p50 <= point.p95 && point.p95 <= point.p99);
+        assert!(point.p99 <= Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_percentiles_within_one_percent() {
+        let mut metrics = PerfMetrics::new();
+        for millis in 1..=1000 {
+            metrics.record_response(0, Duration::from_millis(millis), 200, Duration::ZERO);
+        }
+
+        let stats = metrics.calculate_stats();
+        let near = |actual: Option<Duration>, expected: f64| {
+            let actual = actual.unwrap().as_secs_f64() * 1000.0;
+            (actual - expected).abs() <= expected * 0.01
+        };
+        assert!(near(stats.p50, 500.0));
+        assert!(near(stats.p95, 950.0));
+        assert!(near(stats.p99, 990.0));
+        assert_eq!(stats.min, Some(Duration::from_millis(1)));
+        assert_eq!(stats.max, Some(Duration::from_millis(1000)));
+    }
+
+    #[test]
+    fn test_latency_buckets() {
+        let mut metrics = PerfMetrics::new();
+        metrics.record_response(0, Duration::from_millis(20), 200, Duration::ZERO);
+        metrics.record_response(0, Duration::from_millis(40), 200, Duration::ZERO);
+        metrics.record_response(0, Duration::from_secs(90), 200, Duration::ZERO);
+
+        let buckets = metrics.calculate_stats().latency_buckets;
+        let count = |le: Option<Duration>| buckets.iter().find(|b| b.le == le).unwrap().count;
+        assert_eq!(count(Some(Duration::from_millis(25))), 1);
+        assert_eq!(count(Some(Duration::from_millis(50))), 1);
+        assert_eq!(count(None), 1);
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_step_breakdown() {
+        let mut metrics = PerfMetrics::with_steps(["Login".to_string(), "List".to_string()]);
+        metrics.record_response(0, Duration::from_millis(20), 200, Duration::ZERO);
+        metrics.record_response(1, Duration::from_millis(40), 200, Duration::ZERO);
+        metrics.record_failure(1, "Request timeout".to_string(), Duration::ZERO);
+
+        let stats = metrics.calculate_stats();
+        assert_eq!(stats.total_requests, 3);
+
+        let [login, list] = stats.steps.as_slice() else {
+            panic!("Expected two steps");
+        };
+        assert_eq!(login.name, "Login");
+        assert_eq!((login.total_requests, login.failed_requests), (1, 0));
+        assert_eq!(list.name, "List");
+        assert_eq!((list.total_requests, list.failed_requests), (2, 1));
+        assert_eq!(list.avg, Some(Duration::from_millis(40)));
+    }
 }
@@ -1,5 +1,11 @@
 pub mod metrics;
+pub mod report;
 pub mod runner;
+pub mod scenario;
+pub mod threshold;
 
 pub use metrics::*;
+pub use report::*;
 pub use runner::*;
+pub use scenario::*;
+pub use threshold::*;
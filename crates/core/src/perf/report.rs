@@ -0,0 +1,465 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::metrics::{PerfStats, TimelinePoint};
+use super::runner::PerfConfig;
+
+/// Completed run, saved to compare with later runs and export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerfRun {
+    /// Database id, zero until saved
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub collection_name: Option<String>,
+    /// Names of the requests of the scenario steps, in order
+    pub requests: Vec<String>,
+    pub config: PerfConfig,
+    pub stats: PerfStats,
+}
+
+impl PerfRun {
+    pub fn new(
+        collection_name: Option<String>,
+        requests: Vec<String>,
+        config: PerfConfig,
+        stats: PerfStats,
+    ) -> Self {
+        Self {
+            id: 0,
+            timestamp: Utc::now(),
+            collection_name,
+            requests,
+            config,
+            stats,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.requests.join(" → ")
+    }
+}
+
+/// Value of a metric in two runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delta {
+    pub before: f64,
+    pub after: f64,
+}
+
+impl Delta {
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+
+    /// Change relative to the earlier run, `None` when that was zero
+    pub fn percent(&self) -> Option<f64> {
+        (self.before != 0.0).then(|| self.change() / self.before * 100.0)
+    }
+}
+
+/// Differences between two runs, latencies in milliseconds and error rates in percent
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunComparison {
+    pub requests_per_second: Delta,
+    pub error_rate: Delta,
+    pub avg: Option<Delta>,
+    pub p50: Option<Delta>,
+    pub p95: Option<Delta>,
+    pub p99: Option<Delta>,
+}
+
+impl RunComparison {
+    pub fn new(before: &PerfStats, after: &PerfStats) -> Self {
+        let latency = |before: Option<Duration>, after: Option<Duration>| {
+            before.zip(after).map(|(before, after)| Delta {
+                before: millis(before),
+                after: millis(after),
+            })
+        };
+
+        Self {
+            requests_per_second: Delta {
+                before: before.requests_per_second,
+                after: after.requests_per_second,
+            },
+            error_rate: Delta {
+                before: before.error_rate() * 100.0,
+                after: after.error_rate() * 100.0,
+            },
+            avg: latency(before.avg, after.avg),
+            p50: latency(before.p50, after.p50),
+            p95: latency(before.p95, after.p95),
+            p99: latency(before.p99, after.p99),
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn optional_millis(duration: Option<Duration>) -> String {
+    duration
+        .map(|d| format!("{:.0}", millis(d)))
+        .unwrap_or_default()
+}
+
+/// Formats of exported runs, picked from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension.map(str::to_ascii_lowercase).as_deref() {
+            Some("json") => Ok(ExportFormat::Json),
+            Some("csv") => Ok(ExportFormat::Csv),
+            Some("html" | "htm") => Ok(ExportFormat::Html),
+            _ => bail!("Unsupported export format, expected .json, .csv or .html"),
+        }
+    }
+}
+
+pub fn export_run(run: &PerfRun, format: ExportFormat) -> anyhow::Result<String> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(run)?),
+        ExportFormat::Csv => export_csv(run),
+        ExportFormat::Html => Ok(export_html(run)),
+    }
+}
+
+/// One row for the whole run followed by a row for each scenario step
+fn export_csv(run: &PerfRun) -> anyhow::Result<String> {
+    let stats = &run.stats;
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record([
+        "step",
+        "requests",
+        "failed",
+        "error_rate",
+        "requests_per_second",
+        "min_ms",
+        "avg_ms",
+        "p50_ms",
+        "p95_ms",
+        "p99_ms",
+        "max_ms",
+    ])?;
+
+    writer.write_record([
+        "All".to_string(),
+        stats.total_requests.to_string(),
+        stats.failed_requests.to_string(),
+        format!("{:.4}", stats.error_rate()),
+        format!("{:.2}", stats.requests_per_second),
+        optional_millis(stats.min),
+        optional_millis(stats.avg),
+        optional_millis(stats.p50),
+        optional_millis(stats.p95),
+        optional_millis(stats.p99),
+        optional_millis(stats.max),
+    ])?;
+
+    if stats.steps.len() > 1 {
+        for step in &stats.steps {
+            let error_rate = if step.total_requests > 0 {
+                step.failed_requests as f64 / step.total_requests as f64
+            } else {
+                0.0
+            };
+            writer.write_record([
+                step.name.clone(),
+                step.total_requests.to_string(),
+                step.failed_requests.to_string(),
+                format!("{:.4}", error_rate),
+                String::new(),
+                String::new(),
+                optional_millis(step.avg),
+                optional_millis(step.p50),
+                optional_millis(step.p95),
+                optional_millis(step.p99),
+                String::new(),
+            ])?;
+        }
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_latency(duration: Option<Duration>) -> String {
+    match duration {
+        Some(d) if d < Duration::from_secs(1) => format!("{}ms", d.as_millis()),
+        Some(d) => format!("{:.2}s", d.as_secs_f64()),
+        None => "N/A".to_string(),
+    }
+}
+
+/// Line chart of one value per second as an inline SVG
+fn svg_chart(timeline: &[TimelinePoint], value: impl Fn(&TimelinePoint) -> f64) -> String {
+    const WIDTH: f64 = 600.0;
+    const HEIGHT: f64 = 120.0;
+
+    let max = timeline.iter().map(&value).fold(0.0, f64::max).max(1.0);
+    let points: Vec<String> = timeline
+        .iter()
+        .enumerate()
+        .map(|(second, point)| {
+            let x = WIDTH * (second as f64 + 0.5) / timeline.len() as f64;
+            let y = HEIGHT * (1.0 - value(point) / max);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+
+    format!(
+        r#"<svg viewBox="0 0 {WIDTH} {HEIGHT}" width="{WIDTH}" height="{HEIGHT}"><polyline fill="none" stroke="steelblue" stroke-width="2" points="{}"/></svg><div class="muted">max {:.0}</div>"#,
+        points.join(" "),
+        max
+    )
+}
+
+fn export_html(run: &PerfRun) -> String {
+    let stats = &run.stats;
+    let mut html = String::new();
+    let title = escape_html(&run.name());
+
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #222; }}
+table {{ border-collapse: collapse; margin-bottom: 1.5em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 10px; text-align: left; }}
+th {{ background: #f3f3f3; }}
+.muted {{ color: #777; font-size: 0.85em; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p class="muted">{} · {} · {}</p>
+"#,
+        run.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        escape_html(run.collection_name.as_deref().unwrap_or("No collection")),
+        run.config.profile,
+    );
+
+    let summary = [
+        ("Total Requests", stats.total_requests.to_string()),
+        ("Successful", stats.successful_requests.to_string()),
+        (
+            "Failed",
+            format!(
+                "{} ({:.1}%)",
+                stats.failed_requests,
+                stats.error_rate() * 100.0
+            ),
+        ),
+        ("Requests/sec", format!("{:.2}", stats.requests_per_second)),
+        (
+            "Duration",
+            format!("{:.2}s", stats.total_duration.as_secs_f64()),
+        ),
+        ("Min", format_latency(stats.min)),
+        ("Average", format_latency(stats.avg)),
+        ("p50", format_latency(stats.p50)),
+        ("p95", format_latency(stats.p95)),
+        ("p99", format_latency(stats.p99)),
+        ("Max", format_latency(stats.max)),
+    ];
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    for (label, value) in summary {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, value);
+    }
+    html.push_str("</table>\n");
+
+    if !stats.thresholds.is_empty() {
+        html.push_str("<h2>Thresholds</h2>\n<table>\n<tr><th>Threshold</th><th>Actual</th><th>Result</th></tr>\n");
+        for result in &stats.thresholds {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&result.threshold.to_string()),
+                result.actual_label(),
+                if result.passed { "Pass" } else { "Fail" },
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if stats.steps.len() > 1 {
+        html.push_str("<h2>Steps</h2>\n<table>\n<tr><th>Step</th><th>Requests</th><th>Failed</th><th>p50</th><th>p95</th><th>p99</th></tr>\n");
+        for step in &stats.steps {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&step.name),
+                step.total_requests,
+                step.failed_requests,
+                format_latency(step.p50),
+                format_latency(step.p95),
+                format_latency(step.p99),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !stats.timeline.is_empty() {
+        html.push_str("<h2>Requests per Second</h2>\n");
+        html.push_str(&svg_chart(&stats.timeline, |p| p.requests as f64));
+        html.push_str("<h2>p95 Latency (ms)</h2>\n");
+        html.push_str(&svg_chart(&stats.timeline, |p| {
+            p.p95.map(millis).unwrap_or_default()
+        }));
+    }
+
+    if !stats.status_codes.is_empty() {
+        let mut status_codes: Vec<_> = stats.status_codes.iter().collect();
+        status_codes.sort_by_key(|(code, _)| **code);
+
+        html.push_str("<h2>Status Codes</h2>\n<table>\n");
+        for (code, count) in status_codes {
+            let _ = writeln!(html, "<tr><th>HTTP {}</th><td>{}</td></tr>", code, count);
+        }
+        html.push_str("</table>\n");
+    }
+
+    if !stats.errors.is_empty() {
+        let mut errors: Vec<_> = stats.errors.iter().collect();
+        errors.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+
+        html.push_str("<h2>Errors</h2>\n<table>\n");
+        for (error, count) in errors {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape_html(error),
+                count
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    let buckets = stats.latency_buckets.iter().filter(|b| b.count > 0);
+    html.push_str(
+        "<h2>Latency Histogram</h2>\n<table>\n<tr><th>Up to</th><th>Responses</th></tr>\n",
+    );
+    for bucket in buckets {
+        let bound = bucket
+            .le
+            .map(|le| format_latency(Some(le)))
+            .unwrap_or_else(|| "Slower".to_string());
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", bound, bucket.count);
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perf::PerfMetrics;
+
+    fn sample_run() -> PerfRun {
+        let mut metrics = PerfMetrics::with_steps(["Login".to_string(), "List <all>".to_string()]);
+        metrics.record_response(0, Duration::from_millis(20), 200, Duration::ZERO);
+        metrics.record_response(1, Duration::from_millis(40), 200, Duration::from_secs(1));
+        metrics.record_failure(1, "Request timeout".to_string(), Duration::from_secs(1));
+        metrics.total_duration = Duration::from_secs(2);
+
+        PerfRun::new(
+            Some("Store".to_string()),
+            vec!["Login".to_string(), "List <all>".to_string()],
+            PerfConfig::default(),
+            metrics.calculate_stats(),
+        )
+    }
+
+    #[test]
+    fn test_compare_runs() {
+        let before = sample_run().stats;
+        let mut after = before.clone();
+        after.requests_per_second = 3.0;
+        after.p95 = Some(Duration::from_millis(60));
+
+        let comparison = RunComparison::new(&before, &after);
+        assert_eq!(comparison.requests_per_second.change(), 1.5);
+        assert_eq!(comparison.requests_per_second.percent(), Some(100.0));
+        assert_eq!(before.p95, Some(Duration::from_millis(40)));
+        assert_eq!(comparison.p95.unwrap().change(), 20.0);
+        assert_eq!(comparison.error_rate.change(), 0.0);
+    }
+
+    #[test]
+    fn test_compare_p95_within_bucket() {
+        let stats = |millis: u64| {
+            let mut metrics = PerfMetrics::new();
+            for _ in 0..20 {
+                metrics.record_response(0, Duration::from_millis(millis), 200, Duration::ZERO);
+            }
+            metrics.calculate_stats()
+        };
+
+        // Both used to land in the 300ms bucket and compare as unchanged
+        let comparison = RunComparison::new(&stats(201), &stats(299));
+        assert_eq!(comparison.p95.unwrap().change(), 98.0);
+    }
+
+    #[test]
+    fn test_export_format_from_path() {
+        let format = |path: &str| ExportFormat::from_path(Path::new(path)).ok();
+        assert_eq!(format("run.json"), Some(ExportFormat::Json));
+        assert_eq!(format("run.CSV"), Some(ExportFormat::Csv));
+        assert_eq!(format("run.htm"), Some(ExportFormat::Html));
+        assert_eq!(format("run.txt"), None);
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let csv = export_run(&sample_run(), ExportFormat::Csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("step,requests,failed,error_rate"));
+        assert!(lines[1].starts_with("All,3,1,0.3333,1.50,"));
+        assert!(lines[3].starts_with("List <all>,2,1,0.5000,"));
+    }
+
+    #[test]
+    fn test_export_json_round_trip() {
+        let json = export_run(&sample_run(), ExportFormat::Json).unwrap();
+        let run: PerfRun = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(run.requests, ["Login", "List <all>"]);
+        assert_eq!(run.stats.steps.len(), 2);
+        assert!(run.stats.latency_buckets.iter().any(|b| b.count > 0));
+    }
+
+    #[test]
+    fn test_export_html_escapes_names() {
+        let html = export_run(&sample_run(), ExportFormat::Html).unwrap();
+
+        assert!(html.contains("<title>Login → List &lt;all&gt;</title>"));
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("<all>"));
+    }
+}
@@ -1,108 +1,595 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
 
 use super::metrics::PerfMetrics;
-use crate::client::send_request;
+use super::scenario::ScenarioStep;
+use super::threshold::Threshold;
+use crate::auth::AuthState;
+use crate::client::{PreparedRequest, send_request};
+use crate::http::VarMap;
 use crate::http::environment::EnvironmentChain;
-use crate::http::request::Request;
+use crate::scripting::console::ScriptConsole;
+use crate::scripting::runner::run_post_request_script;
+use crate::scripting::send::ScriptContext;
 use crate::transformers::request::transform_request;
 
+/// How often idle workers check whether the ramp needs them
+const IDLE_POLL: Duration = Duration::from_millis(10);
+/// How often a snapshot of the metrics is sent to the progress channel
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadProfile {
+    /// `concurrency` users sending requests back to back for `duration`
+    #[default]
+    ConstantUsers,
+    /// `rate` requests started every second for `duration`, however long responses take
+    ConstantRate,
+    /// Users ramped linearly between the stage targets
+    RampingUsers,
+    /// Requests per second ramped linearly between the stage targets
+    RampingRate,
+}
+
+impl LoadProfile {
+    pub const ALL: [LoadProfile; 4] = [
+        LoadProfile::ConstantUsers,
+        LoadProfile::ConstantRate,
+        LoadProfile::RampingUsers,
+        LoadProfile::RampingRate,
+    ];
+
+    pub fn is_ramping(&self) -> bool {
+        matches!(self, LoadProfile::RampingUsers | LoadProfile::RampingRate)
+    }
+
+    /// Requests are started on a schedule instead of by users waiting for responses
+    pub fn is_rate(&self) -> bool {
+        matches!(self, LoadProfile::ConstantRate | LoadProfile::RampingRate)
+    }
+}
+
+impl std::fmt::Display for LoadProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LoadProfile::ConstantUsers => "Constant Users",
+            LoadProfile::ConstantRate => "Constant Rate",
+            LoadProfile::RampingUsers => "Ramping Users",
+            LoadProfile::RampingRate => "Ramping Rate",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Step of a ramping profile, moving to `target` users or requests per second over `duration`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadStage {
+    pub duration: Duration,
+    pub target: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PerfConfig {
+    pub profile: LoadProfile,
     pub duration: Duration,
+    /// Users of the constant users profile, the ramping profiles use the stage targets.
+    /// Limits the requests in flight for the rate profiles.
     pub concurrency: usize,
+    /// Requests per second of the constant rate profile, scenario iterations per second
+    /// when there are several steps
+    pub rate: u32,
+    pub stages: Vec<LoadStage>,
+    /// Stop once this many requests were started
+    pub max_requests: Option<u64>,
     pub timeout: Duration,
+    /// Conditions the run has to meet to pass
+    pub thresholds: Vec<Threshold>,
 }
 
 impl Default for PerfConfig {
     fn default() -> Self {
         Self {
+            profile: LoadProfile::default(),
             duration: Duration::from_secs(60),
             concurrency: 10,
+            rate: 50,
+            stages: vec![
+                LoadStage {
+                    duration: Duration::from_secs(30),
+                    target: 10,
+                },
+                LoadStage {
+                    duration: Duration::from_secs(60),
+                    target: 10,
+                },
+                LoadStage {
+                    duration: Duration::from_secs(30),
+                    target: 0,
+                },
+            ],
+            max_requests: None,
             timeout: Duration::from_secs(30),
+            thresholds: Vec::new(),
+        }
+    }
+}
+
+impl PerfConfig {
+    /// Length of the run, the sum of the stages for the ramping profiles
+    pub fn total_duration(&self) -> Duration {
+        if self.profile.is_ramping() {
+            self.stages.iter().map(|stage| stage.duration).sum()
+        } else {
+            self.duration
+        }
+    }
+
+    /// Most users active at once in the user profiles
+    fn max_users(&self) -> usize {
+        match self.profile {
+            LoadProfile::RampingUsers => self
+                .stages
+                .iter()
+                .map(|stage| stage.target as usize)
+                .max()
+                .unwrap_or_default(),
+            _ => self.concurrency,
+        }
+    }
+
+    /// Users that should be active `elapsed` into the run
+    fn users_at(&self, elapsed: Duration) -> f64 {
+        match self.profile {
+            LoadProfile::RampingUsers => ramp(&self.stages, elapsed).0,
+            _ => self.concurrency as f64,
+        }
+    }
+
+    /// Requests that should have been started `elapsed` into the run of a rate profile
+    fn requests_due(&self, elapsed: Duration) -> f64 {
+        match self.profile {
+            LoadProfile::RampingRate => ramp(&self.stages, elapsed).1,
+            _ => self.rate as f64 * elapsed.as_secs_f64(),
+        }
+    }
+
+    /// Time into the run at which request `index` of a rate profile is due,
+    /// `None` if the run ends before it
+    fn scheduled_at(&self, index: u64, after: Duration) -> Option<Duration> {
+        let total = self.total_duration();
+        let index = index as f64;
+        if self.requests_due(total) <= index {
+            return None;
+        }
+
+        let (mut low, mut high) = (after.as_secs_f64(), total.as_secs_f64());
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            if self.requests_due(Duration::from_secs_f64(mid)) >= index {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Some(Duration::from_secs_f64(high))
+    }
+}
+
+/// Value of the linear ramp through the stage targets `elapsed` into the stages,
+/// starting from zero, along with the area under the ramp until then
+fn ramp(stages: &[LoadStage], elapsed: Duration) -> (f64, f64) {
+    let mut remaining = elapsed.as_secs_f64();
+    let (mut from, mut area) = (0.0, 0.0);
+
+    for stage in stages {
+        let length = stage.duration.as_secs_f64();
+        let to = stage.target as f64;
+        if remaining < length {
+            let value = from + (to - from) * remaining / length;
+            return (value, area + (from + value) / 2.0 * remaining);
+        }
+        area += (from + to) / 2.0 * length;
+        remaining -= length;
+        from = to;
+    }
+    (from, area)
+}
+
+/// Sends the scenario steps and records the outcomes
+#[derive(Clone)]
+struct LoadSender {
+    client: reqwest::Client,
+    timeout: Duration,
+    steps: Arc<Vec<ScenarioStep>>,
+    env: EnvironmentChain,
+    auth_state: Arc<AuthState>,
+    context: ScriptContext,
+    metrics: Arc<Mutex<PerfMetrics>>,
+    start_time: Instant,
+    started: Arc<AtomicU64>,
+    max_requests: Option<u64>,
+}
+
+impl LoadSender {
+    /// Claims the next request, false once `max_requests` were started
+    fn claim(&self) -> bool {
+        let started = self.started.fetch_add(1, Ordering::Relaxed);
+        self.max_requests.is_none_or(|max| started < max)
+    }
+
+    fn exhausted(&self) -> bool {
+        self.max_requests
+            .is_some_and(|max| self.started.load(Ordering::Relaxed) >= max)
+    }
+
+    /// Sends the steps in order with the variables captured along the way, stopping at the
+    /// first failed step. Returns false once `max_requests` were started.
+    ///
+    /// Latency of the first step is measured from `scheduled`, so time spent waiting to send
+    /// counts too.
+    async fn run_iteration(
+        &self,
+        prepared: &[Option<PreparedRequest>],
+        scheduled: Instant,
+    ) -> bool {
+        let mut variables = VarMap::new();
+        let mut scheduled = Some(scheduled);
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if !self.claim() {
+                return false;
+            }
+
+            let prepared = prepared.get(index).and_then(Option::as_ref);
+            let outcome = self
+                .send_step(step, prepared, &mut variables, scheduled.take())
+                .await;
+            let failed = outcome.is_err();
+            self.record(index, outcome).await;
+            if failed {
+                break;
+            }
+        }
+        true
+    }
+
+    /// Sends the request of the step, building it first when it uses captured variables,
+    /// and captures the variables it sets
+    async fn send_step(
+        &self,
+        step: &ScenarioStep,
+        prepared: Option<&PreparedRequest>,
+        variables: &mut VarMap,
+        scheduled: Option<Instant>,
+    ) -> Result<(u16, Duration), String> {
+        let context = self.context.clone().with_console(ScriptConsole::new());
+        let request = match prepared.and_then(PreparedRequest::try_clone) {
+            Some(request) => request,
+            None => {
+                let env = self.env.clone().with_layer(Arc::new(variables.clone()));
+                let request = step.request.clone();
+                transform_request(
+                    self.client.clone(),
+                    request,
+                    env,
+                    &self.auth_state,
+                    &context,
+                )
+                .await
+                .map_err(|e| format!("{:#}", e))?
+            }
+        };
+        variables.extend(request.variables.clone());
+
+        let started = scheduled.unwrap_or_else(Instant::now);
+        let response =
+            tokio::time::timeout(self.timeout, send_request(self.client.clone(), request))
+                .await
+                .map_err(|_| "Request timeout".to_string())?
+                .map_err(|e| e.to_string())?;
+        let latency = started.elapsed();
+
+        if let Some(script) = step.request.post_request.as_deref()
+            && !script.trim().is_empty()
+        {
+            let (captured, _) = run_post_request_script(script, &response, None, &context)
+                .map_err(|e| format!("{:#}", e))?;
+            variables.extend(captured.all());
+        }
+        variables.extend(
+            step.extract(&response.body.data)
+                .map_err(|e| format!("{:#}", e))?,
+        );
+
+        Ok((response.status.as_u16(), latency))
+    }
+
+    async fn record(&self, step: usize, outcome: Result<(u16, Duration), String>) {
+        let elapsed = self.start_time.elapsed();
+        let mut metrics = self.metrics.lock().await;
+        match outcome {
+            Ok((status, latency)) => metrics.record_response(step, latency, status, elapsed),
+            Err(error) => metrics.record_failure(step, error, elapsed),
+        }
+    }
+}
+
+/// Sends a snapshot of the metrics every `PROGRESS_INTERVAL` until the receiver is dropped
+async fn report_progress(
+    metrics: Arc<Mutex<PerfMetrics>>,
+    start_time: Instant,
+    progress: mpsc::Sender<PerfMetrics>,
+) {
+    let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        let mut snapshot = metrics.lock().await.clone();
+        snapshot.total_duration = start_time.elapsed();
+        if progress.send(snapshot).await.is_err() {
+            return;
         }
     }
 }
 
+/// Copies of the requests built before the run, one for each step
+fn clone_prepared(prepared: &[Option<PreparedRequest>]) -> Vec<Option<PreparedRequest>> {
+    prepared
+        .iter()
+        .map(|request| request.as_ref().and_then(PreparedRequest::try_clone))
+        .collect()
+}
+
 pub struct PerfRunner {
     client: reqwest::Client,
     config: PerfConfig,
+    auth_state: Arc<AuthState>,
+    context: ScriptContext,
 }
 
 impl PerfRunner {
     pub fn new(client: reqwest::Client, config: PerfConfig) -> Self {
-        Self { client, config }
+        Self {
+            client,
+            config,
+            auth_state: Default::default(),
+            context: ScriptContext::new(),
+        }
+    }
+
+    pub fn with_auth_state(mut self, auth_state: Arc<AuthState>) -> Self {
+        self.auth_state = auth_state;
+        self
     }
 
+    pub fn with_script_context(mut self, context: ScriptContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Runs the scenario, each virtual user or scheduled iteration sending all the steps in order
     pub async fn run(
         &self,
-        request: Request,
+        steps: Vec<ScenarioStep>,
         env: EnvironmentChain,
         progress: mpsc::Sender<PerfMetrics>,
     ) -> anyhow::Result<PerfMetrics> {
-        let built_request = transform_request(self.client.clone(), request, env).await?;
-
-        if built_request.try_clone().is_none() {
-            anyhow::bail!("Request with file body not supported for performance testing");
+        if steps.is_empty() {
+            anyhow::bail!("No requests to run");
         }
 
-        let metrics = Arc::new(Mutex::new(PerfMetrics::new()));
+        // Steps that don't depend on variables captured by earlier steps are built once
+        let mut prepared = Vec::with_capacity(steps.len());
+        let mut captured = false;
+        for step in &steps {
+            let request = if captured {
+                None
+            } else {
+                let request = transform_request(
+                    self.client.clone(),
+                    step.request.clone(),
+                    env.clone(),
+                    &self.auth_state,
+                    &self.context,
+                )
+                .await?;
+                if request.try_clone().is_none() {
+                    anyhow::bail!("Request with file body not supported for performance testing");
+                }
+                Some(request)
+            };
+            prepared.push(request);
+            captured |= step.captures();
+        }
 
-        let mut tasks = Vec::new();
+        let metrics = Arc::new(Mutex::new(PerfMetrics::with_steps(
+            steps.iter().map(|step| step.name.clone()),
+        )));
         let start_time = Instant::now();
+        let sender = LoadSender {
+            client: self.client.clone(),
+            timeout: self.config.timeout,
+            steps: Arc::new(steps),
+            env,
+            auth_state: Arc::clone(&self.auth_state),
+            context: self.context.clone(),
+            metrics: Arc::clone(&metrics),
+            start_time,
+            started: Arc::new(AtomicU64::new(0)),
+            max_requests: self.config.max_requests,
+        };
 
-        for _ in 0..self.config.concurrency {
-            let client = self.client.clone();
-            let request = built_request.try_clone().unwrap();
-            let metrics = Arc::clone(&metrics);
-            let timeout = self.config.timeout;
-            let progress = progress.clone();
-            let duration = self.config.duration;
+        // Snapshots are throttled, the requests only hold the lock to record their outcome
+        let reporter = tokio::spawn(report_progress(Arc::clone(&metrics), start_time, progress));
+        if self.config.profile.is_rate() {
+            self.run_rate(sender, prepared).await;
+        } else {
+            self.run_users(sender, prepared).await;
+        }
+        reporter.abort();
 
-            let task = tokio::spawn(async move {
-                loop {
-                    let request = request.try_clone().unwrap();
-                    let metrics = Arc::clone(&metrics);
-                    let client = client.clone();
+        let total_duration = start_time.elapsed();
+        let mut final_metrics = metrics.lock().await.clone();
+        final_metrics.total_duration = total_duration;
+        final_metrics.thresholds = self.config.thresholds.clone();
 
-                    if start_time.elapsed() >= duration {
+        Ok(final_metrics)
+    }
+
+    /// Closed model, each user starts the next iteration once the previous one completed
+    async fn run_users(&self, sender: LoadSender, prepared: Vec<Option<PreparedRequest>>) {
+        let mut tasks = JoinSet::new();
+        let total = self.config.total_duration();
+
+        for user in 0..self.config.max_users() {
+            let sender = sender.clone();
+            let config = self.config.clone();
+            let prepared = clone_prepared(&prepared);
+
+            tasks.spawn(async move {
+                loop {
+                    let elapsed = sender.start_time.elapsed();
+                    if elapsed >= total {
                         return;
                     }
-
-                    let result = tokio::time::timeout(timeout, send_request(client, request)).await;
-
-                    let mut metrics = metrics.lock().await;
-                    match result {
-                        Ok(Ok(response)) => {
-                            metrics.record_success(response.duration, response.status.as_u16());
-                        }
-                        Ok(Err(e)) => {
-                            metrics.record_failure(e.to_string());
-                        }
-                        Err(_) => {
-                            metrics.record_failure("Request timeout".to_string());
-                        }
+                    if user as f64 >= config.users_at(elapsed) {
+                        tokio::time::sleep(IDLE_POLL).await;
+                        continue;
+                    }
+                    if !sender.run_iteration(&prepared, Instant::now()).await {
+                        return;
                     }
-
-                    let mut snapshot = metrics.clone();
-                    snapshot.total_duration = start_time.elapsed();
-                    let _ = progress.send(snapshot).await;
                 }
             });
+        }
+
+        tasks.join_all().await;
+    }
+
+    /// Open model, iterations start on schedule even when earlier ones are still running
+    async fn run_rate(&self, sender: LoadSender, prepared: Vec<Option<PreparedRequest>>) {
+        let mut tasks = JoinSet::new();
+        let in_flight = Arc::new(Semaphore::new(self.config.concurrency.max(1)));
+        let mut offset = Duration::ZERO;
+
+        for index in 0.. {
+            let Some(next) = self.config.scheduled_at(index, offset) else {
+                break;
+            };
+            offset = next;
+
+            let scheduled = sender.start_time + offset;
+            tokio::time::sleep_until(scheduled.into()).await;
+            if sender.exhausted() {
+                break;
+            }
+            let permit = Arc::clone(&in_flight)
+                .acquire_owned()
+                .await
+                .expect("Semaphore is never closed");
 
-            tasks.push(task);
+            let sender = sender.clone();
+            let prepared = clone_prepared(&prepared);
+            tasks.spawn(async move {
+                sender.run_iteration(&prepared, scheduled).await;
+                drop(permit);
+            });
         }
 
-        for task in tasks {
-            let _ = task.await;
+        tasks.join_all().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramping(profile: LoadProfile) -> PerfConfig {
+        PerfConfig {
+            profile,
+            stages: vec![
+                LoadStage {
+                    duration: Duration::from_secs(10),
+                    target: 20,
+                },
+                LoadStage {
+                    duration: Duration::from_secs(10),
+                    target: 20,
+                },
+                LoadStage {
+                    duration: Duration::from_secs(5),
+                    target: 0,
+                },
+            ],
+            ..PerfConfig::default()
         }
+    }
 
-        let total_duration = start_time.elapsed();
-        let mut final_metrics = metrics.lock().await.clone();
-        final_metrics.total_duration = total_duration;
+    #[test]
+    fn test_ramping_users() {
+        let config = ramping(LoadProfile::RampingUsers);
 
-        Ok(final_metrics)
+        assert_eq!(config.total_duration(), Duration::from_secs(25));
+        assert_eq!(config.max_users(), 20);
+        assert_eq!(config.users_at(Duration::ZERO), 0.0);
+        assert_eq!(config.users_at(Duration::from_secs(5)), 10.0);
+        assert_eq!(config.users_at(Duration::from_secs(15)), 20.0);
+        assert_eq!(config.users_at(Duration::from_millis(22_500)), 10.0);
+    }
+
+    #[test]
+    fn test_ramping_rate_requests_due() {
+        let config = ramping(LoadProfile::RampingRate);
+
+        assert_eq!(config.requests_due(Duration::from_secs(10)), 100.0);
+        assert_eq!(config.requests_due(Duration::from_secs(20)), 300.0);
+        assert_eq!(config.requests_due(Duration::from_secs(25)), 350.0);
+        assert_eq!(config.requests_due(Duration::from_secs(60)), 350.0);
+    }
+
+    #[test]
+    fn test_constant_rate_schedule() {
+        let config = PerfConfig {
+            profile: LoadProfile::ConstantRate,
+            rate: 4,
+            duration: Duration::from_secs(1),
+            ..PerfConfig::default()
+        };
+
+        let mut offset = Duration::ZERO;
+        let schedule = (0..)
+            .map_while(|index| {
+                offset = config.scheduled_at(index, offset)?;
+                Some(offset.as_millis())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(schedule, vec![0, 250, 500, 750]);
+    }
+
+    #[test]
+    fn test_ramping_rate_schedule_ends_with_last_stage() {
+        let config = ramping(LoadProfile::RampingRate);
+
+        let last = config.scheduled_at(349, Duration::ZERO).unwrap();
+        assert!(last > Duration::from_secs(20) && last < Duration::from_secs(25));
+        assert_eq!(config.scheduled_at(350, last), None);
+    }
+
+    #[test]
+    fn test_decode_config_without_profile() {
+        let config: PerfConfig =
+            serde_json::from_str(r#"{"duration":{"secs":5,"nanos":0},"concurrency":3}"#).unwrap();
+
+        assert_eq!(config.profile, LoadProfile::ConstantUsers);
+        assert_eq!(config.duration, Duration::from_secs(5));
+        assert_eq!(config.concurrency, 3);
+        assert_eq!(config.max_requests, None);
     }
 }
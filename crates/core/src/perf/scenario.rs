@@ -0,0 +1,125 @@
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::http::request::Request;
+use crate::scripting::stdlib::json_query;
+
+/// Captures a value of the JSON response into a variable for the following steps
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extraction {
+    pub variable: String,
+    /// JSONPath into the response body, such as `$.data.token`
+    pub path: String,
+}
+
+impl Extraction {
+    /// First value at the path, strings without their quotes and other values as JSON
+    pub fn extract(&self, body: &Value) -> anyhow::Result<String> {
+        let values = json_query(body, &self.path)?;
+        match values.into_iter().next() {
+            Some(Value::String(value)) => Ok(value),
+            Some(value) => Ok(value.to_string()),
+            None => bail!("No value at {} for {}", self.path, self.variable),
+        }
+    }
+}
+
+/// One request of a scenario, sent by each virtual user in order
+#[derive(Debug, Clone)]
+pub struct ScenarioStep {
+    pub name: String,
+    /// Request with the contents of its pre and post-request scripts
+    pub request: Request,
+    pub extractions: Vec<Extraction>,
+}
+
+impl ScenarioStep {
+    pub fn new(name: String, request: Request) -> Self {
+        Self {
+            name,
+            request,
+            extractions: Vec::new(),
+        }
+    }
+
+    pub fn with_extractions(mut self, extractions: Vec<Extraction>) -> Self {
+        self.extractions = extractions
+            .into_iter()
+            .filter(|e| !e.variable.is_empty() && !e.path.is_empty())
+            .collect();
+        self
+    }
+
+    /// Whether the step sets variables, so the following steps are built for each iteration
+    pub(super) fn captures(&self) -> bool {
+        let script =
+            |script: &Option<String>| script.as_ref().is_some_and(|s| !s.trim().is_empty());
+        script(&self.request.pre_request)
+            || script(&self.request.post_request)
+            || !self.extractions.is_empty()
+    }
+
+    /// Values of the extractions in the JSON response body
+    pub(super) fn extract(&self, body: &[u8]) -> anyhow::Result<Vec<(String, String)>> {
+        if self.extractions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let body: Value = serde_json::from_slice(body).context("Response body is not JSON")?;
+        self.extractions
+            .iter()
+            .map(|e| Ok((e.variable.clone(), e.extract(&body)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn extraction(variable: &str, path: &str) -> Extraction {
+        Extraction {
+            variable: variable.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_extract_values() {
+        let body = json!({"data": {"token": "abc", "id": 42, "items": [{"id": 7}, {"id": 8}]}});
+
+        assert_eq!(
+            extraction("token", "$.data.token").extract(&body).unwrap(),
+            "abc"
+        );
+        assert_eq!(extraction("id", "$.data.id").extract(&body).unwrap(), "42");
+        assert_eq!(
+            extraction("first", "$.data.items[*].id")
+                .extract(&body)
+                .unwrap(),
+            "7"
+        );
+    }
+
+    #[test]
+    fn test_extract_missing_value() {
+        let error = extraction("token", "$.token")
+            .extract(&json!({}))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "No value at $.token for token");
+    }
+
+    #[test]
+    fn test_step_extract_requires_json() {
+        let step = ScenarioStep::new("Login".to_string(), Request::default())
+            .with_extractions(vec![extraction("token", "$.token"), extraction("", "$.id")]);
+        assert_eq!(step.extractions.len(), 1);
+        assert!(step.captures());
+
+        let values = step.extract(br#"{"token": "abc"}"#).unwrap();
+        assert_eq!(values, vec![("token".to_string(), "abc".to_string())]);
+        assert!(step.extract(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,358 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use serde::{Deserialize, Serialize};
+
+use super::metrics::PerfStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdMetric {
+    P50,
+    P95,
+    P99,
+    Avg,
+    Max,
+    /// Percent of the requests that failed
+    ErrorRate,
+    /// Requests per second
+    Rps,
+}
+
+impl ThresholdMetric {
+    pub const ALL: [ThresholdMetric; 7] = [
+        ThresholdMetric::P50,
+        ThresholdMetric::P95,
+        ThresholdMetric::P99,
+        ThresholdMetric::Avg,
+        ThresholdMetric::Max,
+        ThresholdMetric::ErrorRate,
+        ThresholdMetric::Rps,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThresholdMetric::P50 => "p50",
+            ThresholdMetric::P95 => "p95",
+            ThresholdMetric::P99 => "p99",
+            ThresholdMetric::Avg => "avg",
+            ThresholdMetric::Max => "max",
+            ThresholdMetric::ErrorRate => "error_rate",
+            ThresholdMetric::Rps => "rps",
+        }
+    }
+
+    /// Value of the metric, latencies in milliseconds and the error rate in percent.
+    /// `None` when no responses were received to measure latencies with.
+    pub fn value(&self, stats: &PerfStats) -> Option<f64> {
+        let millis = |duration: Option<Duration>| duration.map(|d| d.as_secs_f64() * 1000.0);
+        match self {
+            ThresholdMetric::P50 => millis(stats.p50),
+            ThresholdMetric::P95 => millis(stats.p95),
+            ThresholdMetric::P99 => millis(stats.p99),
+            ThresholdMetric::Avg => millis(stats.avg),
+            ThresholdMetric::Max => millis(stats.max),
+            ThresholdMetric::ErrorRate => Some(stats.error_rate() * 100.0),
+            ThresholdMetric::Rps => Some(stats.requests_per_second),
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            ThresholdMetric::ErrorRate => format!("{}%", round(value)),
+            ThresholdMetric::Rps => round(value),
+            _ => format!("{}ms", round(value)),
+        }
+    }
+}
+
+impl std::fmt::Display for ThresholdMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Up to two decimals, without trailing zeros
+fn round(value: f64) -> String {
+    let value = format!("{:.2}", value);
+    value
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl ThresholdOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThresholdOp::Lt => "<",
+            ThresholdOp::Le => "<=",
+            ThresholdOp::Gt => ">",
+            ThresholdOp::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, actual: f64, limit: f64) -> bool {
+        match self {
+            ThresholdOp::Lt => actual < limit,
+            ThresholdOp::Le => actual <= limit,
+            ThresholdOp::Gt => actual > limit,
+            ThresholdOp::Ge => actual >= limit,
+        }
+    }
+}
+
+/// Condition a run has to meet to pass, such as `p95 < 300ms`, `error_rate < 1%` or `rps > 200`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Threshold {
+    pub metric: ThresholdMetric,
+    pub op: ThresholdOp,
+    /// Limit in the unit of the metric, milliseconds for latencies and percent for the error rate
+    pub value: f64,
+}
+
+impl Threshold {
+    pub fn evaluate(&self, stats: &PerfStats) -> ThresholdResult {
+        let actual = self.metric.value(stats);
+        ThresholdResult {
+            threshold: *self,
+            actual,
+            passed: actual.is_some_and(|actual| self.op.holds(actual, self.value)),
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (op_at, op) = ["<=", ">=", "<", ">"]
+            .into_iter()
+            .find_map(|op| s.find(op).map(|at| (at, op)))
+            .with_context(|| format!("Invalid threshold {}, expected a <, <=, > or >=", s))?;
+
+        let name = s[..op_at].trim();
+        let metric = ThresholdMetric::ALL
+            .into_iter()
+            .find(|m| m.as_str().eq_ignore_ascii_case(name))
+            .with_context(|| {
+                format!(
+                    "Unknown threshold metric {}, expected p50, p95, p99, avg, max, error_rate or rps",
+                    name
+                )
+            })?;
+        let op = match op {
+            "<=" => ThresholdOp::Le,
+            ">=" => ThresholdOp::Ge,
+            "<" => ThresholdOp::Lt,
+            _ => ThresholdOp::Gt,
+        };
+
+        let limit = s[op_at + op.as_str().len()..].trim();
+        let (number, scale) = match metric {
+            ThresholdMetric::ErrorRate => (limit.strip_suffix('%').unwrap_or(limit), 1.0),
+            ThresholdMetric::Rps => (limit.strip_suffix("/s").unwrap_or(limit), 1.0),
+            _ => match limit.strip_suffix("ms") {
+                Some(number) => (number, 1.0),
+                None => match limit.strip_suffix('s') {
+                    Some(number) => (number, 1000.0),
+                    None => (limit, 1.0),
+                },
+            },
+        };
+        let value: f64 = number
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid threshold value {}", limit))?;
+        if !value.is_finite() || value < 0.0 {
+            bail!("Invalid threshold value {}", limit);
+        }
+
+        Ok(Threshold {
+            metric,
+            op,
+            value: value * scale,
+        })
+    }
+}
+
+impl std::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.metric,
+            self.op.as_str(),
+            self.metric.format(self.value)
+        )
+    }
+}
+
+impl TryFrom<String> for Threshold {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Threshold> for String {
+    fn from(value: Threshold) -> Self {
+        value.to_string()
+    }
+}
+
+/// Outcome of a threshold at the end of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdResult {
+    pub threshold: Threshold,
+    /// Measured value, `None` when the metric could not be measured, which fails the threshold
+    pub actual: Option<f64>,
+    pub passed: bool,
+}
+
+impl ThresholdResult {
+    pub fn actual_label(&self) -> String {
+        self.actual
+            .map(|actual| self.threshold.metric.format(actual))
+            .unwrap_or_else(|| "N/A".to_string())
+    }
+}
+
+pub fn evaluate_thresholds(thresholds: &[Threshold], stats: &PerfStats) -> Vec<ThresholdResult> {
+    thresholds.iter().map(|t| t.evaluate(stats)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perf::metrics::PerfMetrics;
+
+    fn threshold(s: &str) -> Threshold {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_thresholds() {
+        assert_eq!(
+            threshold("p95 < 300ms"),
+            Threshold {
+                metric: ThresholdMetric::P95,
+                op: ThresholdOp::Lt,
+                value: 300.0,
+            }
+        );
+        assert_eq!(threshold("p99<=1.5s").value, 1500.0);
+        assert_eq!(threshold("error_rate < 1%").op, ThresholdOp::Lt);
+        assert_eq!(threshold("RPS >= 200").metric, ThresholdMetric::Rps);
+
+        assert_eq!(threshold("p99<=1.5s").to_string(), "p99 <= 1500ms");
+        assert_eq!(threshold("error_rate<0.5").to_string(), "error_rate < 0.5%");
+    }
+
+    #[test]
+    fn test_parse_invalid_thresholds() {
+        let error = |s: &str| s.parse::<Threshold>().unwrap_err().to_string();
+
+        assert_eq!(
+            error("p95 = 300ms"),
+            "Invalid threshold p95 = 300ms, expected a <, <=, > or >="
+        );
+        assert!(error("p90 < 300ms").starts_with("Unknown threshold metric p90"));
+        assert_eq!(error("rps > fast"), "Invalid threshold value fast");
+        assert_eq!(error("p95 < -1ms"), "Invalid threshold value -1ms");
+    }
+
+    #[test]
+    fn test_evaluate_thresholds() {
+        let mut metrics = PerfMetrics::new();
+        for _ in 0..9 {
+            metrics.record_response(0, Duration::from_millis(20), 200, Duration::ZERO);
+        }
+        metrics.record_failure(0, "timeout".to_string(), Duration::ZERO);
+        metrics.total_duration = Duration::from_secs(1);
+        let stats = metrics.calculate_stats();
+
+        let thresholds = [
+            threshold("p95 < 300ms"),
+            threshold("error_rate < 1%"),
+            threshold("rps >= 10"),
+        ];
+        let results = evaluate_thresholds(&thresholds, &stats);
+
+        let passed: Vec<_> = results.iter().map(|r| r.passed).collect();
+        assert_eq!(passed, vec![true, false, true]);
+        assert_eq!(results[1].actual_label(), "10%");
+    }
+
+    #[test]
+    fn test_error_rate_counts_error_responses() {
+        let mut metrics = PerfMetrics::new();
+        for status in [200, 404, 500, 503] {
+            metrics.record_response(0, Duration::from_millis(20), status, Duration::ZERO);
+        }
+        let stats = metrics.calculate_stats();
+        let result = threshold("error_rate < 1%").evaluate(&stats);
+
+        assert_eq!((stats.successful_requests, stats.failed_requests), (1, 3));
+        assert!(!result.passed);
+        assert_eq!(result.actual_label(), "75%");
+        // Error responses were still received, so they have latencies
+        assert!(threshold("p95 < 300ms").evaluate(&stats).passed);
+    }
+
+    #[test]
+    fn test_latency_threshold_near_limit() {
+        let p95 = |latencies: &[(u64, usize)]| {
+            let mut metrics = PerfMetrics::new();
+            for &(millis, count) in latencies {
+                for _ in 0..count {
+                    let latency = Duration::from_millis(millis);
+                    metrics.record_response(0, latency, 200, Duration::ZERO);
+                }
+            }
+            threshold("p95 < 300ms").evaluate(&metrics.calculate_stats())
+        };
+
+        assert!(p95(&[(295, 95), (5000, 5)]).passed);
+        assert!(!p95(&[(10, 5), (305, 95)]).passed);
+        assert!(p95(&[(250, 100)]).passed);
+        assert_eq!(p95(&[(250, 100)]).actual_label(), "250ms");
+
+        // A single slow response is every percentile
+        let result = p95(&[(5000, 1)]);
+        assert!(!result.passed);
+        assert_eq!(result.actual_label(), "5000ms");
+    }
+
+    #[test]
+    fn test_latency_threshold_fails_without_responses() {
+        let stats = PerfMetrics::new().calculate_stats();
+        let result = threshold("p95 < 300ms").evaluate(&stats);
+
+        assert!(!result.passed);
+        assert_eq!(result.actual_label(), "N/A");
+    }
+
+    #[test]
+    fn test_decode_thresholds() {
+        #[derive(Deserialize)]
+        struct Config {
+            thresholds: Vec<Threshold>,
+        }
+
+        let config: Config =
+            toml::from_str("thresholds = [\"p95 < 300ms\", \"rps > 200\"]").unwrap();
+        assert_eq!(config.thresholds[1].to_string(), "rps > 200");
+        assert!(toml::from_str::<Config>("thresholds = [\"p95 ~ 3\"]").is_err());
+    }
+}
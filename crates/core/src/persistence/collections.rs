@@ -3,9 +3,11 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use crate::http::collection::{Collection, Entry, Folder, FolderId, RequestId, RequestRef, Script};
+use crate::http::request::Auth;
 use crate::http::{CollectionKey, KeyValList};
 use crate::import::postman;
 use crate::persistence::Version;
+use crate::proxy::ProxySettings;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
@@ -13,10 +15,12 @@ use serde_with::{DurationMilliSeconds, serde_as};
 use std::ops::Not;
 use tokio::fs;
 
+use super::cookies::load_cookie_jar;
 use super::environment::read_environments;
+use super::request::{EncodedAuthType, decode_auth, encode_auth};
 use super::{
-    COLLECTION_ROOT_FILE, EncodedKeyValue, JS_EXTENSION, REQUESTS, SCRIPTS, TOML_EXTENSION,
-    TS_EXTENSION, decode_key_values, encode_key_values,
+    COLLECTION_ROOT_FILE, EncodedKeyValue, FOLDER_ROOT_FILE, JS_EXTENSION, REQUESTS, SCRIPTS,
+    TOML_EXTENSION, TS_EXTENSION, decode_key_values, encode_key_values,
 };
 
 fn default_timeout() -> Duration {
@@ -33,10 +37,23 @@ pub struct EncodedCollection {
     #[serde(default = "default_timeout")]
     #[serde_as(as = "DurationMilliSeconds")]
     pub timeout: Duration,
+    #[serde(default, skip_serializing_if = "Not::not")]
+    pub abort_on_script_error: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default_environment: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub headers: Vec<EncodedKeyValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<EncodedAuthType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+}
+
+/// Optional settings stored inside a folder, a missing file means everything is inherited
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EncodedFolder {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<EncodedAuthType>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -74,8 +91,11 @@ async fn create_collections_state(collections_file: PathBuf) -> Result<Collectio
             version: Version::V1,
             disable_cert_verification: false,
             timeout: Duration::from_secs(300),
+            abort_on_script_error: false,
             default_environment: None,
             headers: vec![],
+            auth: None,
+            proxy: None,
         },
     )
     .await?;
@@ -171,6 +191,10 @@ pub async fn open_collection(
     let entries = find_all_requests(&path).await?;
     let scripts = find_all_scripts(&path).await?;
     let dotenv = read_dotenv(&path);
+    let cookies = load_cookie_jar(Some(&path)).await.unwrap_or_else(|e| {
+        log::error!("Error loading cookie jar: {:?}", e);
+        Default::default()
+    });
 
     let default_env = collection
         .default_environment
@@ -190,7 +214,13 @@ pub async fn open_collection(
         default_env,
         active_environment: default_env,
         timeout: collection.timeout,
+        abort_on_script_error: collection.abort_on_script_error,
+        proxy: collection.proxy,
         expanded: false,
+        auth: decode_auth(collection.auth),
+        auth_state: Default::default(),
+        runtime_vars: Default::default(),
+        cookies,
     })
 }
 
@@ -254,19 +284,27 @@ async fn walk_entries(dir_path: &Path) -> Result<Vec<Entry>> {
     while let Some(entry) = dir.next_entry().await? {
         if entry.file_type().await?.is_dir() {
             let entries = Box::pin(walk_entries(&entry.path())).await?;
+            let folder = read_folder(&entry.path())
+                .await
+                .inspect_err(|e| log::error!("Error reading folder settings: {:?}", e))
+                .unwrap_or_default();
             all_entries.push(Entry::Folder(Folder {
                 id: FolderId::new(),
                 name: entry.file_name().to_string_lossy().to_string(),
                 entries,
                 path: entry.path(),
                 expanded: false,
+                auth: decode_folder_auth(folder.auth),
             }));
         } else {
             let name = entry.file_name();
             let name = name.to_string_lossy();
             let without_ext = name.trim_end_matches(&TOML_EXTENSION);
 
-            if !name.ends_with(&TOML_EXTENSION) || without_ext.is_empty() {
+            if !name.ends_with(&TOML_EXTENSION)
+                || without_ext.is_empty()
+                || name == FOLDER_ROOT_FILE
+            {
                 continue;
             }
 
@@ -286,11 +324,78 @@ pub fn encode_collection(collection: &Collection) -> EncodedCollection {
         version: Version::V1,
         disable_cert_verification: collection.disable_ssl,
         timeout: collection.timeout,
+        abort_on_script_error: collection.abort_on_script_error,
         default_environment: collection
             .default_env
             .and_then(|env| collection.environments.get(env))
             .map(|env| env.name.clone()),
         headers: encode_key_values(KeyValList::clone(&collection.headers)),
+        auth: encode_auth(collection.auth.clone()),
+        proxy: collection
+            .proxy
+            .as_ref()
+            .map(ProxySettings::without_password),
+    }
+}
+
+async fn read_folder(path: &Path) -> Result<EncodedFolder> {
+    let file = path.join(FOLDER_ROOT_FILE);
+    if !fs::try_exists(&file).await? {
+        return Ok(EncodedFolder::default());
+    }
+
+    let data = fs::read_to_string(&file).await?;
+    toml::from_str(&data).with_context(|| format!("Failed to parse {:?}", file))
+}
+
+pub async fn save_folder(path: &Path, folder: EncodedFolder) -> Result<()> {
+    let data = toml::to_string_pretty(&folder)?;
+    fs::create_dir_all(path).await?;
+    fs::write(path.join(FOLDER_ROOT_FILE), data).await?;
+    Ok(())
+}
+
+// Unlike requests, a folder without auth inherits from its parent
+pub fn encode_folder_auth(auth: Auth) -> Option<EncodedAuthType> {
+    match auth {
+        Auth::Inherit => None,
+        Auth::None => Some(EncodedAuthType::None),
+        auth => encode_auth(auth),
+    }
+}
+
+fn decode_folder_auth(auth: Option<EncodedAuthType>) -> Auth {
+    match auth {
+        None => Auth::Inherit,
+        auth => decode_auth(auth),
+    }
+}
+
+/// Resolves `Auth::Inherit` for a request file by reading the folder and collection
+/// files on disk, for callers that don't have an open `Collection`
+pub async fn resolve_auth(root: &Path, request_path: &Path, auth: Auth) -> Result<Auth> {
+    if auth != Auth::Inherit {
+        return Ok(auth);
+    }
+
+    let requests = root.join(REQUESTS);
+    let mut dir = request_path.parent();
+    while let Some(path) = dir
+        && path.starts_with(&requests)
+        && path != requests
+    {
+        let auth = decode_folder_auth(read_folder(path).await?.auth);
+        if auth != Auth::Inherit {
+            return Ok(auth);
+        }
+        dir = path.parent();
+    }
+
+    let data = fs::read_to_string(root.join(COLLECTION_ROOT_FILE)).await?;
+    let collection: EncodedCollection = toml::from_str(&data)?;
+    match decode_auth(collection.auth) {
+        Auth::Inherit => Ok(Auth::None),
+        auth => Ok(auth),
     }
 }
 
@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use cookie_store::CookieStore;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::cookies::{CookieFormat, CookieJar, export_cookies, import_cookies};
+use crate::persistence::collections::project_dirs;
+
+use super::COOKIES;
+
+/// Jar of the requests that are not saved in a collection
+const UNSAVED_JAR: &str = "unsaved";
+
+/// Jars are named after the collection path, so they survive removing and reopening the collection.
+/// `None` is the jar of the requests not saved in a collection.
+fn cookie_jar_file(collection: Option<&Path>) -> Result<PathBuf> {
+    let dirs = project_dirs().context("Failed to find data directory")?;
+    let name = match collection {
+        Some(path) => hex::encode(Sha256::digest(path.to_string_lossy().as_bytes())),
+        None => UNSAVED_JAR.to_string(),
+    };
+    Ok(dirs.data_dir().join(COOKIES).join(format!("{name}.json")))
+}
+
+/// Loads the saved cookie jar of the collection, a missing jar is empty
+pub async fn load_cookie_jar(collection: Option<&Path>) -> Result<Arc<CookieJar>> {
+    read_jar(&cookie_jar_file(collection)?).await
+}
+
+pub async fn save_cookie_jar(collection: Option<PathBuf>, jar: Arc<CookieJar>) -> Result<()> {
+    write_jar(&cookie_jar_file(collection.as_deref())?, &jar).await
+}
+
+/// An unreadable jar is moved aside instead of being overwritten by the next save
+async fn read_jar(path: &Path) -> Result<Arc<CookieJar>> {
+    let mut store = CookieStore::default();
+    if !fs::try_exists(path).await? {
+        return Ok(Arc::new(CookieJar::new(store)));
+    }
+
+    let content = fs::read_to_string(path).await?;
+    if let Err(e) = import_cookies(&mut store, &content, CookieFormat::Json) {
+        let corrupt = path.with_extension("json.corrupt");
+        fs::rename(path, &corrupt).await?;
+        return Err(e.context(format!(
+            "Failed to load cookie jar {}, moved it to {}",
+            path.display(),
+            corrupt.display()
+        )));
+    }
+
+    Ok(Arc::new(CookieJar::new(store)))
+}
+
+async fn write_jar(path: &Path, jar: &CookieJar) -> Result<()> {
+    // Generation read under the same lock, changes made while writing keep the jar dirty
+    let (content, generation) = {
+        let store = jar.read().expect("Lock");
+        (
+            export_cookies(&store, CookieFormat::Json)?,
+            jar.generation(),
+        )
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+    fs::write(path, content).await?;
+    jar.mark_saved(generation);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_save_and_load_jar() {
+        let dir = std::env::temp_dir().join(format!("sanchaar-jar-{}", std::process::id()));
+        let path = dir.join("jar.json");
+
+        let jar = CookieJar::default();
+        let url = Url::parse("https://example.com/").unwrap();
+        let cookie = cookie_store::Cookie::parse("session=abc; Max-Age=3600", &url).unwrap();
+        jar.write().unwrap().insert(cookie, &url).unwrap();
+        assert!(jar.is_dirty());
+
+        write_jar(&path, &jar).await.unwrap();
+        assert!(!jar.is_dirty());
+
+        let loaded = read_jar(&path).await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(!loaded.is_dirty());
+        assert!(
+            loaded
+                .read()
+                .unwrap()
+                .contains("example.com", "/", "session")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_jar_is_kept() {
+        let dir = std::env::temp_dir().join(format!("sanchaar-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("jar.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let error = read_jar(&path).await.unwrap_err();
+        let kept = std::fs::read_to_string(dir.join("jar.json.corrupt"));
+        let exists = path.exists();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert!(error.to_string().starts_with("Failed to load cookie jar"));
+        assert_eq!(kept.unwrap(), "not json");
+        assert!(!exists);
+    }
+}
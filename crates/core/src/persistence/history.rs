@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqlitePool};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -13,6 +14,7 @@ use std::time::Duration;
 use crate::client::{ContentType, Response, ResponseBody};
 use crate::http::KeyValList;
 use crate::http::request::{Auth, Method, Request, RequestBody};
+use crate::perf::PerfRun;
 use crate::persistence::collections::project_dirs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,6 +136,22 @@ impl HistoryDatabase {
         .execute(&pool)
         .await;
 
+        // Completed performance test runs, with the config and stats as JSON
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS perf_runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                collection_name TEXT,
+                requests TEXT NOT NULL,
+                config TEXT NOT NULL,
+                stats TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
         Ok(Self { pool })
     }
 
@@ -433,6 +451,79 @@ impl HistoryDatabase {
 
         Ok(result.rows_affected())
     }
+
+    pub async fn save_perf_run(&self, run: &PerfRun) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO perf_runs (timestamp, collection_name, requests, config, stats)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(run.timestamp.to_rfc3339())
+        .bind(run.collection_name.as_deref())
+        .bind(serde_json::to_string(&run.requests)?)
+        .bind(serde_json::to_string(&run.config)?)
+        .bind(serde_json::to_string(&run.stats)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Saved perf runs, newest first
+    pub async fn get_perf_runs(&self, limit: Option<i64>) -> Result<Vec<PerfRun>> {
+        let limit = limit.unwrap_or(100);
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, timestamp, collection_name, requests, config, stats
+            FROM perf_runs
+            ORDER BY timestamp DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Runs saved by another version may not decode, these are skipped
+        let runs = rows
+            .iter()
+            .filter_map(|row| {
+                decode_perf_run(row)
+                    .inspect_err(|e| log::warn!("Skipping unreadable perf run: {:?}", e))
+                    .ok()
+            })
+            .collect();
+
+        Ok(runs)
+    }
+
+    pub async fn delete_perf_run(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM perf_runs WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn decode_perf_run(row: &SqliteRow) -> Result<PerfRun> {
+    let timestamp_str: String = row.try_get("timestamp")?;
+    let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)?.with_timezone(&Utc);
+    let requests: String = row.try_get("requests")?;
+    let config: String = row.try_get("config")?;
+    let stats: String = row.try_get("stats")?;
+
+    Ok(PerfRun {
+        id: row.try_get("id")?,
+        timestamp,
+        collection_name: row.try_get("collection_name")?,
+        requests: serde_json::from_str(&requests)?,
+        config: serde_json::from_str(&config)?,
+        stats: serde_json::from_str(&stats)?,
+    })
 }
 
 impl HistoryEntry {
@@ -459,6 +550,7 @@ impl HistoryEntry {
             assertions: Default::default(),
             pre_request: None,
             post_request: None,
+            abort_on_script_error: None,
         })
     }
 
@@ -501,3 +593,38 @@ pub fn get_history_db_path() -> Result<PathBuf> {
 
     Ok(data_dir.join(super::HISTORY_DB))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perf::{PerfConfig, PerfMetrics};
+
+    #[tokio::test]
+    async fn test_get_perf_runs_skips_unreadable_rows() {
+        let path = std::env::temp_dir().join(format!("sanchaar-history-{}.db", std::process::id()));
+        let db = HistoryDatabase::new(path.clone()).await.unwrap();
+
+        let stats = PerfMetrics::new().calculate_stats();
+        let run = PerfRun::new(
+            None,
+            vec!["Login".to_string()],
+            PerfConfig::default(),
+            stats,
+        );
+        db.save_perf_run(&run).await.unwrap();
+        sqlx::query(
+            "INSERT INTO perf_runs (timestamp, requests, config, stats) VALUES (?, '[]', '{}', 'not json')",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let runs = db.get_perf_runs(None).await.unwrap();
+        db.pool.close().await;
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].requests, ["Login"]);
+    }
+}
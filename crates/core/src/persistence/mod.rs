@@ -5,19 +5,25 @@ use strum::{Display, EnumString};
 use crate::http::{KeyValList, KeyValue};
 
 pub mod collections;
+pub mod cookies;
 pub mod environment;
 pub mod history;
+pub mod perf;
 pub mod request;
+pub mod settings;
 
 pub const TOML_SUFFIX: &str = "toml";
 pub const TOML_EXTENSION: &str = ".toml";
 pub const JS_EXTENSION: &str = "js";
 pub const TS_EXTENSION: &str = "ts";
 pub const COLLECTION_ROOT_FILE: &str = "collection.toml";
+pub const FOLDER_ROOT_FILE: &str = ".folder.toml";
 pub const ENVIRONMENTS: &str = "environments";
 pub const SCRIPTS: &str = "scripts";
 pub const REQUESTS: &str = "requests";
 pub const HISTORY_DB: &str = "history.db";
+pub const COOKIES: &str = "cookies";
+pub const SETTINGS_FILE: &str = "settings.toml";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EncodedKeyValue {
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use serde_with::{DurationSeconds, serde_as};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::perf::{LoadProfile, LoadStage, PerfConfig, Threshold};
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncodedLoadStage {
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub duration: Duration,
+    pub target: u32,
+}
+
+/// Perf test settings kept next to the collection, such as `perf.toml`:
+///
+/// ```toml
+/// requests = ["users/login.toml", "users/list.toml"]
+/// profile = "constant_users"
+/// duration = 30
+/// users = 20
+/// thresholds = ["p95 < 300ms", "error_rate < 1%", "rps > 200"]
+/// ```
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncodedPerfConfig {
+    /// Request files of the scenario steps, relative to the `requests` directory of the collection
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub requests: Vec<PathBuf>,
+    pub profile: LoadProfile,
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub duration: Duration,
+    pub users: usize,
+    pub rate: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<EncodedLoadStage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u64>,
+    #[serde_as(as = "DurationSeconds<u64>")]
+    pub timeout: Duration,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub thresholds: Vec<Threshold>,
+}
+
+impl Default for EncodedPerfConfig {
+    fn default() -> Self {
+        let config = PerfConfig::default();
+        Self {
+            requests: Vec::new(),
+            profile: config.profile,
+            duration: config.duration,
+            users: config.concurrency,
+            rate: config.rate,
+            stages: Vec::new(),
+            max_requests: config.max_requests,
+            timeout: config.timeout,
+            thresholds: Vec::new(),
+        }
+    }
+}
+
+impl From<EncodedPerfConfig> for PerfConfig {
+    fn from(value: EncodedPerfConfig) -> Self {
+        let stages = if value.stages.is_empty() {
+            PerfConfig::default().stages
+        } else {
+            value
+                .stages
+                .into_iter()
+                .map(|stage| LoadStage {
+                    duration: stage.duration,
+                    target: stage.target,
+                })
+                .collect()
+        };
+
+        PerfConfig {
+            profile: value.profile,
+            duration: value.duration,
+            concurrency: value.users,
+            rate: value.rate,
+            stages,
+            max_requests: value.max_requests,
+            timeout: value.timeout,
+            thresholds: value.thresholds,
+        }
+    }
+}
+
+pub async fn read_perf_config(path: &Path) -> anyhow::Result<EncodedPerfConfig> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read perf config {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Invalid perf config {}", path.display()))
+}
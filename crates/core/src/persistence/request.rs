@@ -1,3 +1,4 @@
+use std::ops::Not;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -5,7 +6,9 @@ use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
 
 use crate::assertions::Assertions;
-use crate::http::request::{Auth, AuthIn, JwtAlgorithm, Method, Request, RequestBody};
+use crate::http::request::{
+    Auth, AuthIn, JwtAlgorithm, Method, OAuth2, OAuth2Grant, Request, RequestBody,
+};
 use crate::http::{KeyFile, KeyFileList};
 use crate::persistence::Version;
 
@@ -62,6 +65,8 @@ pub struct EncodedRequest {
     pub pre_request: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_request: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abort_on_script_error: Option<bool>,
     #[serde(default, skip_serializing_if = "Assertions::is_empty")]
     pub assertions: Assertions,
 }
@@ -162,9 +167,43 @@ impl From<EncodedJwtAlgorithm> for JwtAlgorithm {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodedOAuth2Grant {
+    #[default]
+    ClientCredentials,
+    Password,
+    AuthorizationCode,
+    RefreshToken,
+}
+
+impl From<OAuth2Grant> for EncodedOAuth2Grant {
+    fn from(val: OAuth2Grant) -> Self {
+        match val {
+            OAuth2Grant::ClientCredentials => EncodedOAuth2Grant::ClientCredentials,
+            OAuth2Grant::Password => EncodedOAuth2Grant::Password,
+            OAuth2Grant::AuthorizationCode => EncodedOAuth2Grant::AuthorizationCode,
+            OAuth2Grant::RefreshToken => EncodedOAuth2Grant::RefreshToken,
+        }
+    }
+}
+
+impl From<EncodedOAuth2Grant> for OAuth2Grant {
+    fn from(val: EncodedOAuth2Grant) -> Self {
+        match val {
+            EncodedOAuth2Grant::ClientCredentials => OAuth2Grant::ClientCredentials,
+            EncodedOAuth2Grant::Password => OAuth2Grant::Password,
+            EncodedOAuth2Grant::AuthorizationCode => OAuth2Grant::AuthorizationCode,
+            EncodedOAuth2Grant::RefreshToken => OAuth2Grant::RefreshToken,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EncodedAuthType {
+    None,
+    Inherit,
     Basic {
         username: String,
         password: String,
@@ -172,6 +211,10 @@ pub enum EncodedAuthType {
     Bearer {
         token: String,
     },
+    Digest {
+        username: String,
+        password: String,
+    },
     APIKey {
         key: String,
         value: String,
@@ -184,6 +227,38 @@ pub enum EncodedAuthType {
         payload: String,
         add_to: EncodedAuthIn,
     },
+    #[serde(rename = "oauth2")]
+    OAuth2 {
+        #[serde(default)]
+        grant: EncodedOAuth2Grant,
+        token_url: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        auth_url: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        redirect_url: String,
+        client_id: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        client_secret: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        scope: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        username: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        password: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        refresh_token: String,
+        #[serde(default, skip_serializing_if = "Not::not")]
+        use_pkce: bool,
+    },
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4 {
+        access_key: String,
+        secret_key: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        session_token: String,
+        region: String,
+        service: String,
+    },
 }
 
 pub fn encode_request(req: Request) -> EncodedRequest {
@@ -199,6 +274,7 @@ pub fn encode_request(req: Request) -> EncodedRequest {
         assertions,
         pre_request,
         post_request,
+        abort_on_script_error,
     } = req;
 
     let encoded_method = match method {
@@ -225,6 +301,7 @@ pub fn encode_request(req: Request) -> EncodedRequest {
         body: encode_body(body),
         pre_request,
         post_request,
+        abort_on_script_error,
         assertions,
     }
 }
@@ -256,11 +333,13 @@ fn encode_key_files(files: KeyFileList) -> Vec<EncodedKeyFile> {
         .collect()
 }
 
-fn encode_auth(auth: Auth) -> Option<EncodedAuthType> {
+pub(crate) fn encode_auth(auth: Auth) -> Option<EncodedAuthType> {
     match auth {
         Auth::None => None,
+        Auth::Inherit => Some(EncodedAuthType::Inherit),
         Auth::Basic { username, password } => Some(EncodedAuthType::Basic { username, password }),
         Auth::Bearer { token } => Some(EncodedAuthType::Bearer { token }),
+        Auth::Digest { username, password } => Some(EncodedAuthType::Digest { username, password }),
         Auth::APIKey { key, value, add_to } => Some(EncodedAuthType::APIKey {
             key,
             value,
@@ -277,6 +356,44 @@ fn encode_auth(auth: Auth) -> Option<EncodedAuthType> {
             payload,
             add_to: add_to.into(),
         }),
+        Auth::OAuth2(OAuth2 {
+            grant,
+            token_url,
+            auth_url,
+            redirect_url,
+            client_id,
+            client_secret,
+            scope,
+            username,
+            password,
+            refresh_token,
+            use_pkce,
+        }) => Some(EncodedAuthType::OAuth2 {
+            grant: grant.into(),
+            token_url,
+            auth_url,
+            redirect_url,
+            client_id,
+            client_secret,
+            scope,
+            username,
+            password,
+            refresh_token,
+            use_pkce,
+        }),
+        Auth::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        } => Some(EncodedAuthType::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        }),
     }
 }
 
@@ -310,11 +427,13 @@ fn decode_body(body: Option<EncodedRequestBody>) -> Option<RequestBody> {
     Some(decode)
 }
 
-fn decode_auth(auth: Option<EncodedAuthType>) -> Auth {
+pub(crate) fn decode_auth(auth: Option<EncodedAuthType>) -> Auth {
     match auth {
-        None => Auth::None,
+        None | Some(EncodedAuthType::None) => Auth::None,
+        Some(EncodedAuthType::Inherit) => Auth::Inherit,
         Some(EncodedAuthType::Basic { username, password }) => Auth::Basic { username, password },
         Some(EncodedAuthType::Bearer { token }) => Auth::Bearer { token },
+        Some(EncodedAuthType::Digest { username, password }) => Auth::Digest { username, password },
         Some(EncodedAuthType::APIKey { key, value, add_to }) => Auth::APIKey {
             key,
             value,
@@ -331,6 +450,44 @@ fn decode_auth(auth: Option<EncodedAuthType>) -> Auth {
             payload,
             add_to: add_to.into(),
         },
+        Some(EncodedAuthType::OAuth2 {
+            grant,
+            token_url,
+            auth_url,
+            redirect_url,
+            client_id,
+            client_secret,
+            scope,
+            username,
+            password,
+            refresh_token,
+            use_pkce,
+        }) => Auth::OAuth2(OAuth2 {
+            grant: grant.into(),
+            token_url,
+            auth_url,
+            redirect_url,
+            client_id,
+            client_secret,
+            scope,
+            username,
+            password,
+            refresh_token,
+            use_pkce,
+        }),
+        Some(EncodedAuthType::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        }) => Auth::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        },
     }
 }
 
@@ -346,6 +503,7 @@ fn decode_request(req: EncodedRequest) -> Request {
         auth,
         pre_request,
         post_request,
+        abort_on_script_error,
         assertions,
         ..
     } = req;
@@ -362,6 +520,7 @@ fn decode_request(req: EncodedRequest) -> Request {
         assertions,
         pre_request,
         post_request,
+        abort_on_script_error,
     }
 }
 
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::persistence::collections::project_dirs;
+use crate::proxy::ProxySettings;
+
+use super::SETTINGS_FILE;
+
+/// Settings of the app shared by all collections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Store cookies from responses and send them with requests
+    pub cookies_enabled: bool,
+    /// Proxy of collections without their own proxy
+    pub proxy: ProxySettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            cookies_enabled: true,
+            proxy: ProxySettings::default(),
+        }
+    }
+}
+
+fn settings_file() -> Result<PathBuf> {
+    let dirs = project_dirs().context("Failed to find data directory")?;
+    Ok(dirs.data_dir().join(SETTINGS_FILE))
+}
+
+/// Loads the saved settings, defaults are used until settings are saved
+pub async fn load_settings() -> Result<AppSettings> {
+    let path = settings_file()?;
+    if !fs::try_exists(&path).await? {
+        return Ok(AppSettings::default());
+    }
+
+    let data = fs::read_to_string(&path).await?;
+    toml::from_str(&data).context("Failed to parse settings")
+}
+
+pub async fn save_settings(settings: AppSettings) -> Result<()> {
+    let path = settings_file()?;
+    let data = toml::to_string_pretty(&settings)?;
+    fs::write(path, data).await?;
+    Ok(())
+}
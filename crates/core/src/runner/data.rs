@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use anyhow::{Context, bail};
+use serde_json::Value;
+
+use crate::http::VarMap;
+
+/// Reads iteration rows from a CSV file with a header row, or a JSON array of objects
+pub async fn load_iterations(path: &Path) -> anyhow::Result<Vec<VarMap>> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read data file {}", path.display()))?;
+
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    let rows = match extension.map(str::to_ascii_lowercase).as_deref() {
+        Some("csv") => parse_csv(&content)?,
+        Some("json") => parse_json(&content)?,
+        _ => bail!("Unsupported data file, expected .csv or .json"),
+    };
+    // A run without rows would pass without sending a single request
+    if rows.is_empty() {
+        bail!("Data file {} has no rows", path.display());
+    }
+    Ok(rows)
+}
+
+fn parse_csv(content: &str) -> anyhow::Result<Vec<VarMap>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(index, record)| {
+            let record = record.with_context(|| format!("Invalid CSV row {}", index + 1))?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_json(content: &str) -> anyhow::Result<Vec<VarMap>> {
+    let Value::Array(rows) = serde_json::from_str(content)? else {
+        bail!("JSON data file must contain an array of objects");
+    };
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let Value::Object(row) = row else {
+                bail!("JSON data row {} is not an object", index + 1);
+            };
+            Ok(row
+                .into_iter()
+                .map(|(name, value)| (name, json_value(value)))
+                .collect())
+        })
+        .collect()
+}
+
+fn json_value(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("id, name\n1,alice\n2,\"bob, jr\"\n").unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["id"], "1");
+        assert_eq!(rows[0]["name"], "alice");
+        assert_eq!(rows[1]["name"], "bob, jr");
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let rows =
+            parse_json(r#"[{"id": 1, "name": "alice", "admin": true, "tag": null}]"#).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["id"], "1");
+        assert_eq!(rows[0]["name"], "alice");
+        assert_eq!(rows[0]["admin"], "true");
+        assert_eq!(rows[0]["tag"], "");
+    }
+
+    #[test]
+    fn test_parse_json_rejects_non_array() {
+        assert!(parse_json(r#"{"id": 1}"#).is_err());
+        assert!(parse_json(r#"[1, 2]"#).is_err());
+
+        let error = parse_json(r#"[{"id": 1}, [2]]"#).unwrap_err();
+        assert_eq!(error.to_string(), "JSON data row 2 is not an object");
+    }
+
+    #[tokio::test]
+    async fn test_load_iterations_without_rows() {
+        let dir = std::env::temp_dir().join(format!("sanchaar-data-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut errors = Vec::new();
+        for (name, content) in [
+            ("empty.csv", ""),
+            ("header.csv", "id,name\n"),
+            ("empty.JSON", "[]"),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            errors.push((load_iterations(&path).await, path));
+        }
+        let rows = {
+            let path = dir.join("rows.CSV");
+            std::fs::write(&path, "id\n1\n").unwrap();
+            load_iterations(&path).await
+        };
+        let _ = std::fs::remove_dir_all(&dir);
+
+        for (result, path) in errors {
+            let error = result.unwrap_err().to_string();
+            assert_eq!(error, format!("Data file {} has no rows", path.display()));
+        }
+        assert_eq!(rows.unwrap().len(), 1);
+    }
+}
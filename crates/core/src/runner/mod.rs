@@ -0,0 +1,444 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use tokio::sync::mpsc;
+
+mod data;
+
+pub use data::load_iterations;
+
+use crate::assertions::{
+    self,
+    runner::{AssertionOutcome, ConditionResult, MatcherResult, script_outcome},
+};
+use crate::auth::AuthState;
+use crate::client::{Response, send_request};
+use crate::http::VarMap;
+use crate::http::collection::{Collection, Entry, FolderId, RequestId};
+use crate::http::environment::{EnvironmentChain, RuntimeVariables};
+use crate::http::request::Auth;
+use crate::scripting::console::ScriptConsole;
+use crate::scripting::runner::run_post_request_script;
+use crate::scripting::send::ScriptContext;
+use crate::transformers::request::transform_request;
+
+/// What a collection run executes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunTarget {
+    Collection,
+    Folder(FolderId),
+    Request(RequestId),
+}
+
+/// A request queued for a collection run, with auth inherited from its folders
+#[derive(Debug, Clone)]
+pub struct RunItem {
+    pub id: RequestId,
+    pub name: String,
+    pub path: PathBuf,
+    inherited_auth: Auth,
+}
+
+#[derive(Debug, Clone)]
+pub enum RunOutcome {
+    Completed {
+        response: Response,
+        assertions: Vec<AssertionOutcome>,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub id: RequestId,
+    /// Index of the data row the request ran with
+    pub iteration: usize,
+    pub name: String,
+    pub path: PathBuf,
+    pub outcome: RunOutcome,
+    pub duration: Duration,
+}
+
+impl RunResult {
+    pub fn passed(&self) -> bool {
+        match &self.outcome {
+            RunOutcome::Completed { assertions, .. } => assertions
+                .iter()
+                .flat_map(|a| a.results.iter())
+                .all(|r| matches!(r.result, MatcherResult::Passed)),
+            RunOutcome::Failed(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub duration: Duration,
+}
+
+impl RunSummary {
+    pub fn total(&self) -> usize {
+        self.passed + self.failed
+    }
+
+    pub fn record(&mut self, result: &RunResult) {
+        if result.passed() {
+            self.passed += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+}
+
+/// Runs the requests of a collection or folder one after another, in tree order,
+/// repeating the whole sequence for each iteration data row
+pub struct CollectionRunner {
+    client: reqwest::Client,
+    items: Vec<RunItem>,
+    env: EnvironmentChain,
+    runtime_vars: Arc<RuntimeVariables>,
+    auth_state: Arc<AuthState>,
+    script_context: ScriptContext,
+    iterations: Vec<Arc<VarMap>>,
+}
+
+impl CollectionRunner {
+    pub fn new(
+        client: reqwest::Client,
+        collection: &Collection,
+        target: RunTarget,
+    ) -> anyhow::Result<Self> {
+        let mut items = Vec::new();
+        match target {
+            RunTarget::Collection => collect_items(collection, &collection.entries, &mut items),
+            RunTarget::Folder(id) => {
+                let folder = collection.folder(id).context("Folder not found")?;
+                collect_items(collection, &folder.entries, &mut items);
+            }
+            RunTarget::Request(id) => {
+                let item = collection.get_ref(id).context("Request not found")?;
+                items.push(RunItem {
+                    id,
+                    name: item.name.clone(),
+                    path: item.path.clone(),
+                    inherited_auth: collection.resolve_auth(id, Auth::Inherit),
+                });
+            }
+        }
+
+        let env = collection
+            .get_active_environment()
+            .map(|e| e.vars())
+            .unwrap_or_default();
+
+        Ok(Self {
+            client,
+            items,
+            env: EnvironmentChain::from_iter(Arc::clone(&collection.dotenv), [env]),
+            runtime_vars: Arc::clone(&collection.runtime_vars),
+            auth_state: Arc::clone(&collection.auth_state),
+            script_context: ScriptContext::for_collection(collection),
+            iterations: vec![Default::default()],
+        })
+    }
+
+    /// Runs every request once per row, with the row's columns as variables
+    pub fn with_iterations(mut self, rows: Vec<VarMap>) -> Self {
+        self.iterations = rows.into_iter().map(Arc::new).collect();
+        self
+    }
+
+    pub fn items(&self) -> &[RunItem] {
+        &self.items
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations.len()
+    }
+
+    pub async fn run(&self, progress: mpsc::Sender<RunResult>) -> RunSummary {
+        let start = Instant::now();
+        let mut summary = RunSummary::default();
+
+        'run: for (iteration, data) in self.iterations.iter().enumerate() {
+            for item in self.items.iter() {
+                let started = Instant::now();
+                let outcome = match self.run_item(item, data).await {
+                    Ok(outcome) => outcome,
+                    Err(e) => RunOutcome::Failed(format!("{:#}", e)),
+                };
+
+                let result = RunResult {
+                    id: item.id,
+                    iteration,
+                    name: item.name.clone(),
+                    path: item.path.clone(),
+                    outcome,
+                    duration: started.elapsed(),
+                };
+                summary.record(&result);
+
+                if progress.send(result).await.is_err() {
+                    break 'run;
+                }
+            }
+        }
+
+        summary.duration = start.elapsed();
+        summary
+    }
+
+    async fn run_item(&self, item: &RunItem, data: &Arc<VarMap>) -> anyhow::Result<RunOutcome> {
+        let mut request = self
+            .script_context
+            .load_request(&item.path, Some(item.inherited_auth.clone()))
+            .await?;
+        let post_request = request.post_request.take();
+
+        let assertions = request.assertions.clone();
+        let env = self
+            .env
+            .clone()
+            .with_layer(self.runtime_vars.snapshot())
+            .with_layer(Arc::clone(data));
+        let context = self
+            .script_context
+            .clone()
+            .with_console(ScriptConsole::new());
+
+        let prepared = transform_request(
+            self.client.clone(),
+            request,
+            env,
+            &self.auth_state,
+            &context,
+        )
+        .await?;
+        self.runtime_vars.extend(prepared.variables.clone());
+
+        let response = send_request(self.client.clone(), prepared).await?;
+
+        let tests = post_request
+            .filter(|s| !s.trim().is_empty())
+            .map(|script| self.run_post_script(&script, &response, &context));
+
+        let mut assertions = assertions::run(&response, &assertions);
+        assertions.extend(tests.and_then(script_outcome));
+
+        Ok(RunOutcome::Completed {
+            response,
+            assertions,
+        })
+    }
+
+    /// Runs the post-request script, keeping its variables for the following requests
+    fn run_post_script(
+        &self,
+        script: &str,
+        response: &Response,
+        context: &ScriptContext,
+    ) -> anyhow::Result<Vec<ConditionResult>> {
+        let (variables, tests) = run_post_request_script(script, response, None, context)?;
+        self.runtime_vars.extend(variables.all());
+        Ok(tests)
+    }
+}
+
+fn collect_items(collection: &Collection, entries: &[Entry], items: &mut Vec<RunItem>) {
+    for entry in entries {
+        match entry {
+            Entry::Item(item) => items.push(RunItem {
+                id: item.id,
+                name: item.name.clone(),
+                path: item.path.clone(),
+                inherited_auth: collection.resolve_auth(item.id, Auth::Inherit),
+            }),
+            Entry::Folder(folder) => collect_items(collection, &folder.entries, items),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::http::CollectionKey;
+    use crate::persistence::collections::open_collection;
+
+    /// Answers every request with a token, sending the request line and headers back to the test
+    async fn serve(listener: TcpListener, requests: mpsc::UnboundedSender<String>) {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let read = stream.read(&mut buffer).await.unwrap();
+            let _ = requests.send(String::from_utf8_lossy(&buffer[..read]).to_lowercase());
+
+            let body = r#"{"token":"abc"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    }
+
+    /// Collection with a login request whose token the nested folder authenticates with
+    async fn collection(name: &str, url: &str) -> (Collection, PathBuf) {
+        let root = std::env::temp_dir().join(format!("sanchaar-{}-{}", name, std::process::id()));
+        let files = [
+            (
+                "collection.toml",
+                "name = \"Runner\"\nversion = \"V1\"\n[[headers]]\nname = \"X-Collection\"\nvalue = \"1\"\n"
+                    .to_string(),
+            ),
+            (
+                "scripts/login.js",
+                "variables[\"token\"] = JSON.parse(response.body).token;".to_string(),
+            ),
+            (
+                "scripts/trace.js",
+                "variables[\"trace\"] = \"t-1\";".to_string(),
+            ),
+            (
+                "requests/a-login.toml",
+                format!("method = \"POST\"\nurl = \"{url}/login\"\npost_request = \"login.js\"\n"),
+            ),
+            (
+                "requests/b-users/.folder.toml",
+                "[auth.bearer]\ntoken = \"{{token}}\"\n".to_string(),
+            ),
+            (
+                "requests/b-users/a-list.toml",
+                format!("method = \"GET\"\nurl = \"{url}/list\"\nauth = \"inherit\"\npre_request = \"trace.js\"\n"),
+            ),
+            (
+                "requests/b-users/b-nested/get.toml",
+                format!("method = \"GET\"\nurl = \"{url}/get\"\nauth = \"inherit\"\n[[headers]]\nname = \"X-Trace\"\nvalue = \"{{{{trace}}}}\"\n"),
+            ),
+            (
+                "requests/c-logout.toml",
+                format!("method = \"POST\"\nurl = \"{url}/logout\"\n"),
+            ),
+        ];
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        let mut collection = open_collection(root.clone(), CollectionKey::new())
+            .await
+            .unwrap();
+        // Directories are listed in no particular order, the tree is sorted to know the run order
+        fn sort(entries: &mut [Entry]) {
+            entries.sort_by_key(|entry| match entry {
+                Entry::Item(item) => item.path.clone(),
+                Entry::Folder(folder) => folder.path.clone(),
+            });
+            for entry in entries {
+                if let Entry::Folder(folder) = entry {
+                    sort(&mut folder.entries);
+                }
+            }
+        }
+        sort(&mut collection.entries);
+
+        (collection, root)
+    }
+
+    #[tokio::test]
+    async fn test_run_collection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, mut requests) = mpsc::unbounded_channel();
+        let server = tokio::spawn(serve(listener, sender));
+
+        let (collection, root) = collection("run", &url).await;
+        let runner =
+            CollectionRunner::new(reqwest::Client::new(), &collection, RunTarget::Collection)
+                .unwrap();
+        let (progress, mut results) = mpsc::channel(10);
+        let summary = runner.run(progress).await;
+        server.abort();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let mut names = Vec::new();
+        while let Ok(result) = results.try_recv() {
+            names.push(result.name);
+        }
+        assert_eq!(names, vec!["a-login", "a-list", "get", "c-logout"]);
+        assert_eq!((summary.passed, summary.failed), (4, 0));
+
+        let mut sent = Vec::new();
+        while let Ok(request) = requests.try_recv() {
+            sent.push(request);
+        }
+        let lines: Vec<_> = sent.iter().map(|r| r.lines().next().unwrap()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "post /login http/1.1",
+                "get /list http/1.1",
+                "get /get http/1.1",
+                "post /logout http/1.1",
+            ]
+        );
+        assert!(sent.iter().all(|r| r.contains("x-collection: 1")));
+
+        // The token from the login post-request script authenticates the folder
+        assert!(!sent[0].contains("authorization"));
+        assert!(sent[1].contains("authorization: bearer abc"));
+        assert!(sent[2].contains("authorization: bearer abc"));
+        assert!(!sent[3].contains("authorization"));
+
+        // Set by the pre-request script of the previous request
+        assert!(sent[2].contains("x-trace: t-1"));
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_without_receiver() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, mut requests) = mpsc::unbounded_channel();
+        let server = tokio::spawn(serve(listener, sender));
+
+        let (collection, root) = collection("stop", &url).await;
+        let runner =
+            CollectionRunner::new(reqwest::Client::new(), &collection, RunTarget::Collection)
+                .unwrap();
+        let (progress, results) = mpsc::channel(10);
+        drop(results);
+        let summary = runner.run(progress).await;
+        server.abort();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(summary.total(), 1);
+        assert!(requests.try_recv().is_ok());
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_failed_requests_are_counted() {
+        let result = RunResult {
+            id: RequestId::new(),
+            iteration: 0,
+            name: "Login".to_string(),
+            path: PathBuf::from("login.toml"),
+            outcome: RunOutcome::Failed("error sending request".to_string()),
+            duration: Duration::ZERO,
+        };
+
+        let mut summary = RunSummary::default();
+        summary.record(&result);
+
+        assert!(!result.passed());
+        assert_eq!((summary.passed, summary.failed), (0, 1));
+    }
+}
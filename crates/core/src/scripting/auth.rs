@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+use crate::http::request::{Auth, AuthIn, JwtAlgorithm, OAuth2};
+
+/// Auth as seen by scripts, tagged with a `type` field
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum ScriptAuth {
+    None,
+    Inherit,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    Digest {
+        username: String,
+        password: String,
+    },
+    ApiKey {
+        key: String,
+        value: String,
+        #[serde(default)]
+        add_to: AuthIn,
+    },
+    JwtBearer {
+        #[serde(default)]
+        algorithm: JwtAlgorithm,
+        secret: String,
+        payload: String,
+        #[serde(default)]
+        add_to: AuthIn,
+    },
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2),
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4 {
+        access_key: String,
+        secret_key: String,
+        #[serde(default)]
+        session_token: String,
+        region: String,
+        service: String,
+    },
+}
+
+impl From<Auth> for ScriptAuth {
+    fn from(auth: Auth) -> Self {
+        match auth {
+            Auth::None => ScriptAuth::None,
+            Auth::Inherit => ScriptAuth::Inherit,
+            Auth::Basic { username, password } => ScriptAuth::Basic { username, password },
+            Auth::Bearer { token } => ScriptAuth::Bearer { token },
+            Auth::Digest { username, password } => ScriptAuth::Digest { username, password },
+            Auth::APIKey { key, value, add_to } => ScriptAuth::ApiKey { key, value, add_to },
+            Auth::JWTBearer {
+                algorithm,
+                secret,
+                payload,
+                add_to,
+            } => ScriptAuth::JwtBearer {
+                algorithm,
+                secret,
+                payload,
+                add_to,
+            },
+            Auth::OAuth2(oauth2) => ScriptAuth::OAuth2(oauth2),
+            Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => ScriptAuth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            },
+        }
+    }
+}
+
+impl From<ScriptAuth> for Auth {
+    fn from(auth: ScriptAuth) -> Self {
+        match auth {
+            ScriptAuth::None => Auth::None,
+            ScriptAuth::Inherit => Auth::Inherit,
+            ScriptAuth::Basic { username, password } => Auth::Basic { username, password },
+            ScriptAuth::Bearer { token } => Auth::Bearer { token },
+            ScriptAuth::Digest { username, password } => Auth::Digest { username, password },
+            ScriptAuth::ApiKey { key, value, add_to } => Auth::APIKey { key, value, add_to },
+            ScriptAuth::JwtBearer {
+                algorithm,
+                secret,
+                payload,
+                add_to,
+            } => Auth::JWTBearer {
+                algorithm,
+                secret,
+                payload,
+                add_to,
+            },
+            ScriptAuth::OAuth2(oauth2) => Auth::OAuth2(oauth2),
+            ScriptAuth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            },
+        }
+    }
+}
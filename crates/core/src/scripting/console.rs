@@ -0,0 +1,81 @@
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+/// Script that produced a console entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    PreRequest,
+    PostRequest,
+}
+
+impl Display for ScriptKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptKind::PreRequest => write!(f, "pre-request"),
+            ScriptKind::PostRequest => write!(f, "post-request"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    Log,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Display for ConsoleLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsoleLevel::Log => write!(f, "log"),
+            ConsoleLevel::Info => write!(f, "info"),
+            ConsoleLevel::Warn => write!(f, "warn"),
+            ConsoleLevel::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A `console` call or script error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleEntry {
+    pub script: ScriptKind,
+    pub level: ConsoleLevel,
+    pub message: String,
+}
+
+/// Output of the scripts run for a request, shared by the pre and post-request scripts
+/// and the requests they send
+#[derive(Debug, Clone, Default)]
+pub struct ScriptConsole {
+    entries: Arc<Mutex<Vec<ConsoleEntry>>>,
+}
+
+impl ScriptConsole {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, script: ScriptKind, level: ConsoleLevel, message: String) {
+        match level {
+            ConsoleLevel::Log | ConsoleLevel::Info => log::info!("[Script] {}", message),
+            ConsoleLevel::Warn => log::warn!("[Script] {}", message),
+            ConsoleLevel::Error => log::error!("[Script] {}", message),
+        }
+
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(ConsoleEntry {
+                script,
+                level,
+                message,
+            });
+        }
+    }
+
+    pub fn entries(&self) -> Vec<ConsoleEntry> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+}
@@ -1,11 +1,28 @@
+mod auth;
+pub mod console;
+pub mod modules;
 pub mod runner;
-
-use crate::http::request::Method;
+pub mod send;
+pub mod stdlib;
+
+use self::auth::ScriptAuth;
+use self::console::{ConsoleLevel, ScriptConsole, ScriptKind};
+use self::modules::ScriptsResolver;
+use self::send::RequestSender;
+use crate::assertions::runner::{ConditionResult, Description, MatcherResult};
+use crate::http::request::{Auth, Method, RequestBody};
 use crate::http::{KeyValList, KeyValue};
-use anyhow::{Context, Result};
-use rquickjs::{Ctx, Object, Runtime};
+use anyhow::{Context, Result, anyhow, bail};
+use rquickjs::function::Rest;
+use rquickjs::loader::{BuiltinResolver, ModuleLoader, ScriptLoader};
+use rquickjs::{Array, Ctx, Exception, Module, Object, Runtime, Type};
+use serde_json::Value;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Defines `test`, `expect` and the `sanchaar` object for scripts
+const PRELUDE: &str = include_str!("prelude.js");
 
 /// Request context exposed to scripts for modification
 #[derive(Debug, Clone)]
@@ -14,7 +31,9 @@ pub struct ScriptRequest {
     pub url: String,
     pub headers: KeyValList,
     pub query_params: KeyValList,
-    pub body: Option<String>,
+    pub path_params: KeyValList,
+    pub body: RequestBody,
+    pub auth: Auth,
 }
 
 impl ScriptRequest {
@@ -24,10 +43,27 @@ impl ScriptRequest {
             url,
             headers,
             query_params,
-            body: None,
+            path_params: KeyValList::new(),
+            body: RequestBody::None,
+            auth: Auth::None,
         }
     }
 
+    pub fn with_path_params(mut self, path_params: KeyValList) -> Self {
+        self.path_params = path_params;
+        self
+    }
+
+    pub fn with_body(mut self, body: RequestBody) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
     /// Set request method from script
     pub fn set_method(&mut self, method: String) -> Result<()> {
         self.method = method.parse().context("Invalid HTTP method")?;
@@ -87,6 +123,59 @@ impl ScriptRequest {
             .map(|p| p.value.clone())
     }
 
+    /// Add or update a path parameter from script
+    pub fn set_path_param(&mut self, key: String, value: String) {
+        self.path_params.retain(|p| p.name != key);
+        self.path_params.push(KeyValue {
+            name: key,
+            value,
+            disabled: false,
+        });
+    }
+
+    /// Replace the text of the body from script, a request without a text body becomes a text body
+    pub fn set_body_text(&mut self, text: String) {
+        match &mut self.body {
+            RequestBody::Json(body) | RequestBody::XML(body) | RequestBody::Text(body) => {
+                *body = text;
+            }
+            body => *body = RequestBody::Text(text),
+        }
+    }
+
+    /// Replace the enabled form or multipart fields from script, other bodies become a form
+    pub fn set_body_fields(&mut self, fields: Vec<(String, String)>) {
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| KeyValue {
+                name,
+                value,
+                disabled: false,
+            })
+            .collect();
+
+        match &mut self.body {
+            RequestBody::Form(params) | RequestBody::Multipart { params, .. } => {
+                params.retain(|p| p.disabled);
+                params.extend(KeyValList::from(fields));
+            }
+            body => *body = RequestBody::Form(KeyValList::from(fields)),
+        }
+    }
+
+    /// Name of the body type, for script access
+    pub fn body_type(&self) -> &'static str {
+        match self.body {
+            RequestBody::Multipart { .. } => "multipart",
+            RequestBody::Form(_) => "form",
+            RequestBody::Json(_) => "json",
+            RequestBody::XML(_) => "xml",
+            RequestBody::Text(_) => "text",
+            RequestBody::File(_) => "file",
+            RequestBody::None => "none",
+        }
+    }
+
     /// Get all headers as a map (for script access)
     pub fn headers_map(&self) -> HashMap<String, String> {
         self.headers
@@ -151,6 +240,9 @@ impl ScriptVariables {
 pub struct ScriptEngine {
     runtime: Runtime,
     timeout: Duration,
+    sender: Option<RequestSender>,
+    console: ScriptConsole,
+    modules: Option<PathBuf>,
 }
 
 impl ScriptEngine {
@@ -160,10 +252,14 @@ impl ScriptEngine {
         // Set memory and execution limits
         runtime.set_memory_limit(50 * 1024 * 1024); // 50MB memory limit
         runtime.set_max_stack_size(256 * 1024); // 256KB stack
+        runtime.set_loader(builtin_resolver(), builtin_loader());
 
         Ok(Self {
             runtime,
             timeout: Duration::from_secs(5), // 5 second timeout
+            sender: None,
+            console: ScriptConsole::new(),
+            modules: None,
         })
     }
 
@@ -172,6 +268,43 @@ impl ScriptEngine {
         self
     }
 
+    /// Enables `sanchaar.sendRequest` in pre-request scripts
+    pub fn with_sender(mut self, sender: RequestSender) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Collects `console` output and script errors
+    pub fn with_console(mut self, console: ScriptConsole) -> Self {
+        self.console = console;
+        self
+    }
+
+    /// Lets scripts import modules from the directory with relative paths
+    pub fn with_modules(mut self, dir: PathBuf) -> Self {
+        self.runtime.set_loader(
+            (builtin_resolver(), ScriptsResolver::new(&dir)),
+            (builtin_loader(), ScriptLoader::default()),
+        );
+        self.modules = Some(dir);
+        self
+    }
+
+    /// Time the script must finish by, nested scripts share the deadline of the script sending them
+    fn deadline(&self) -> Instant {
+        let deadline = Instant::now() + self.timeout;
+        self.sender
+            .as_ref()
+            .and_then(|s| s.context.deadline())
+            .map_or(deadline, |outer| outer.min(deadline))
+    }
+
+    /// Interrupts the running script once the deadline has passed
+    fn set_deadline(&self, deadline: Instant) {
+        self.runtime
+            .set_interrupt_handler(Some(Box::new(move || Instant::now() > deadline)));
+    }
+
     /// Execute a pre-request script
     pub fn execute_pre_request(
         &self,
@@ -179,78 +312,194 @@ impl ScriptEngine {
         request: &mut ScriptRequest,
         variables: &mut ScriptVariables,
     ) -> Result<()> {
+        let deadline = self.deadline();
+        self.set_deadline(deadline);
         let context = rquickjs::Context::full(&self.runtime)?;
 
-        context.with(|ctx| {
-            self.setup_console(&ctx)?;
+        let result = context.with(|ctx| {
+            self.setup_console(&ctx, ScriptKind::PreRequest)?;
             self.setup_request_object(&ctx, request)?;
             self.setup_variables_object(&ctx, variables)?;
+            self.setup_sender(&ctx, deadline)?;
+            self.setup_prelude(&ctx)?;
 
             // Execute the script
-            ctx.eval::<(), _>(script)
-                .context("Script execution failed")?;
+            self.eval_script(&ctx, script, ScriptKind::PreRequest, deadline)?;
+            self.run_pending_jobs(&ctx, deadline)?;
 
             // Extract modifications from the request object
             self.extract_request_modifications(&ctx, request)?;
             self.extract_variables(&ctx, variables)?;
 
             Ok::<_, anyhow::Error>(())
-        })?;
+        });
 
-        Ok(())
+        self.report_error(ScriptKind::PreRequest, result)
     }
 
-    /// Execute a post-request script
+    /// Execute a post-request script, returning the results of its `test` calls
     pub fn execute_post_request(
         &self,
         script: &str,
         response: &ScriptResponse,
         variables: &mut ScriptVariables,
-    ) -> Result<()> {
+    ) -> Result<Vec<ConditionResult>> {
+        let deadline = self.deadline();
+        self.set_deadline(deadline);
         let context = rquickjs::Context::full(&self.runtime)?;
 
-        context.with(|ctx| {
-            self.setup_console(&ctx)?;
+        let result = context.with(|ctx| {
+            self.setup_console(&ctx, ScriptKind::PostRequest)?;
             self.setup_response_object(&ctx, response)?;
             self.setup_variables_object(&ctx, variables)?;
+            self.setup_prelude(&ctx)?;
 
-            // Execute the script
-            ctx.eval::<(), _>(script)
-                .context("Script execution failed")?;
+            // Execute the script, then settle the async tests
+            self.eval_script(&ctx, script, ScriptKind::PostRequest, deadline)?;
+            self.run_pending_jobs(&ctx, deadline)?;
 
             // Extract variable modifications
             self.extract_variables(&ctx, variables)?;
 
-            Ok::<_, anyhow::Error>(())
-        })?;
+            self.extract_tests(&ctx)
+        });
 
-        Ok(())
+        self.report_error(ScriptKind::PostRequest, result)
     }
 
-    /// Setup console.log for debugging
-    fn setup_console<'js>(&self, ctx: &Ctx<'js>) -> Result<()> {
-        let console = Object::new(ctx.clone())?;
+    /// Evaluates scripts with imports as ES modules, pre-request scripts without them that use
+    /// `await` are wrapped in an async function so they can await `sanchaar.sendRequest`
+    fn eval_script<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        script: &str,
+        kind: ScriptKind,
+        deadline: Instant,
+    ) -> Result<()> {
+        let result = if modules::is_module(script) {
+            let dir = self.modules.as_deref().unwrap_or(Path::new(""));
+            let name = dir.join(format!("{}.js", kind));
+            Module::evaluate(ctx.clone(), name.to_string_lossy().to_string(), script)
+                .and_then(|promise| promise.finish::<()>())
+        } else if kind == ScriptKind::PreRequest && uses_await(script) {
+            let script = format!("(async () => {{\n{}\n}})().catch(__sanchaar_fail);", script);
+            ctx.eval::<(), _>(script)
+        } else {
+            ctx.eval::<(), _>(script)
+        };
 
-        console.set(
-            "log",
-            rquickjs::Function::new(ctx.clone(), |msg: String| {
-                log::info!("[Script] {}", msg);
-            })?,
-        )?;
+        result.map_err(|e| self.script_error(ctx, e, deadline))
+    }
 
-        console.set(
-            "error",
-            rquickjs::Function::new(ctx.clone(), |msg: String| {
-                log::error!("[Script] {}", msg);
-            })?,
-        )?;
+    /// Adds the error of a failed script to the console
+    fn report_error<T>(&self, script: ScriptKind, result: Result<T>) -> Result<T> {
+        if let Err(e) = &result {
+            self.console
+                .push(script, ConsoleLevel::Error, format!("{:#}", e));
+        }
+        result
+    }
+
+    /// Setup `test`, `expect` and the `sanchaar` object with the standard library helpers
+    fn setup_prelude<'js>(&self, ctx: &Ctx<'js>) -> Result<()> {
+        ctx.eval::<(), _>(PRELUDE)
+            .context("Failed to setup script helpers")?;
+
+        let sanchaar: Object = ctx.globals().get("sanchaar")?;
+        stdlib::install(ctx, &sanchaar)?;
+        Ok(())
+    }
 
-        console.set(
-            "warn",
-            rquickjs::Function::new(ctx.clone(), |msg: String| {
-                log::warn!("[Script] {}", msg);
-            })?,
+    /// Setup the native function behind `sanchaar.sendRequest`
+    fn setup_sender<'js>(&self, ctx: &Ctx<'js>, deadline: Instant) -> Result<()> {
+        let Some(sender) = self.sender.clone() else {
+            return Ok(());
+        };
+
+        ctx.globals().set(
+            "__sanchaar_send",
+            rquickjs::Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'_>, target: String| -> rquickjs::Result<String> {
+                    sender
+                        .send_blocking(&target, deadline)
+                        .map_err(|e| Exception::throw_message(&ctx, &format!("{:#}", e)))
+                },
+            )?,
         )?;
+        Ok(())
+    }
+
+    /// Runs promise callbacks queued by the script, failing with the error of the script if any
+    fn run_pending_jobs<'js>(&self, ctx: &Ctx<'js>, deadline: Instant) -> Result<()> {
+        while Instant::now() <= deadline && ctx.execute_pending_job() {}
+
+        if Instant::now() > deadline {
+            bail!("Script timed out");
+        }
+
+        if let Some(error) = ctx.globals().get::<_, Option<String>>("__sanchaar_error")? {
+            bail!("Script execution failed: {}", error);
+        }
+        Ok(())
+    }
+
+    /// Reports scripts stopped by the deadline as timed out, and thrown errors with their stack
+    fn script_error<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        error: rquickjs::Error,
+        deadline: Instant,
+    ) -> anyhow::Error {
+        if Instant::now() > deadline {
+            return anyhow!("Script timed out");
+        }
+        if !matches!(error, rquickjs::Error::Exception) {
+            return anyhow::Error::new(error).context("Script execution failed");
+        }
+
+        let thrown = ctx.catch();
+        let message = match thrown.as_exception() {
+            Some(exception) => {
+                let message = exception.message().unwrap_or_default();
+                match exception.stack() {
+                    Some(stack) if !stack.is_empty() => format!("{}\n{}", message, stack),
+                    _ => message,
+                }
+            }
+            None => js_to_string(ctx, thrown),
+        };
+        anyhow!("Script execution failed: {}", message)
+    }
+
+    /// Setup `console` to collect script output
+    fn setup_console<'js>(&self, ctx: &Ctx<'js>, script: ScriptKind) -> Result<()> {
+        let console = Object::new(ctx.clone())?;
+
+        let methods = [
+            ("log", ConsoleLevel::Log),
+            ("info", ConsoleLevel::Info),
+            ("warn", ConsoleLevel::Warn),
+            ("error", ConsoleLevel::Error),
+        ];
+        for (name, level) in methods {
+            let output = self.console.clone();
+            console.set(
+                name,
+                rquickjs::Function::new(
+                    ctx.clone(),
+                    move |ctx: Ctx<'js>, args: Rest<rquickjs::Value<'js>>| {
+                        let message = args
+                            .0
+                            .into_iter()
+                            .map(|arg| console_arg(&ctx, arg))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        output.push(script, level, message);
+                    },
+                )?,
+            )?;
+        }
 
         ctx.globals().set("console", console)?;
         Ok(())
@@ -278,6 +527,35 @@ impl ScriptEngine {
         }
         request_obj.set("params", params_obj)?;
 
+        // Set path params as object
+        let path_params_obj = Object::new(ctx.clone())?;
+        for param in request.path_params.iter() {
+            path_params_obj.set(param.name.clone(), param.value.clone())?;
+        }
+        request_obj.set("pathParams", path_params_obj)?;
+
+        // Set body as text, or as an object of fields for forms
+        request_obj.set("bodyType", request.body_type())?;
+        match &request.body {
+            RequestBody::Json(body) | RequestBody::XML(body) | RequestBody::Text(body) => {
+                request_obj.set("body", body.clone())?;
+            }
+            RequestBody::Form(params) | RequestBody::Multipart { params, .. } => {
+                let fields_obj = Object::new(ctx.clone())?;
+                for param in params.iter().filter(|p| !p.disabled) {
+                    fields_obj.set(param.name.clone(), param.value.clone())?;
+                }
+                request_obj.set("body", fields_obj)?;
+            }
+            RequestBody::File(_) | RequestBody::None => {
+                request_obj.set("body", rquickjs::Value::new_null(ctx.clone()))?;
+            }
+        }
+
+        // Set auth as a plain object tagged with its type
+        let auth = serde_json::to_string(&ScriptAuth::from(request.auth.clone()))?;
+        request_obj.set("auth", ctx.json_parse(auth)?)?;
+
         ctx.globals().set("request", request_obj)?;
         Ok(())
     }
@@ -360,6 +638,58 @@ impl ScriptEngine {
             }
         }
 
+        // Extract path params
+        if let Ok(path_params_obj) = request_obj.get::<_, Object>("pathParams") {
+            for key in path_params_obj.keys::<String>() {
+                if let Ok(key) = key
+                    && let Ok(value) = path_params_obj.get::<_, String>(&key)
+                {
+                    request.set_path_param(key, value);
+                }
+            }
+        }
+
+        // Extract body, objects replace form fields or are sent as JSON
+        let body: rquickjs::Value = request_obj.get("body")?;
+        match body.type_of() {
+            Type::String => {
+                if let Ok(text) = body.get::<String>() {
+                    request.set_body_text(text);
+                }
+            }
+            Type::Object | Type::Array => match request.body {
+                RequestBody::Form(_) | RequestBody::Multipart { .. } => {
+                    let fields = body.get::<Object>()?;
+                    let fields = fields
+                        .props::<String, rquickjs::Value>()
+                        .filter_map(|prop| prop.ok())
+                        .map(|(key, value)| (key, js_to_string(ctx, value)))
+                        .collect();
+                    request.set_body_fields(fields);
+                }
+                _ => {
+                    if let Some(json) = ctx.json_stringify(body)? {
+                        request.body = RequestBody::Json(json.to_string()?);
+                    }
+                }
+            },
+            Type::Null | Type::Undefined => {
+                if !matches!(request.body, RequestBody::File(_)) {
+                    request.body = RequestBody::None;
+                }
+            }
+            _ => log::warn!("Unsupported request body set from script"),
+        }
+
+        // Extract auth
+        let auth: rquickjs::Value = request_obj.get("auth")?;
+        if let Some(auth) = ctx.json_stringify(auth)? {
+            match serde_json::from_str::<ScriptAuth>(&auth.to_string()?) {
+                Ok(auth) => request.auth = auth.into(),
+                Err(e) => log::warn!("Invalid auth set from script: {}", e),
+            }
+        }
+
         Ok(())
     }
 
@@ -383,6 +713,67 @@ impl ScriptEngine {
 
         Ok(())
     }
+
+    /// Extract results of `test` calls from JavaScript context
+    fn extract_tests<'js>(&self, ctx: &Ctx<'js>) -> Result<Vec<ConditionResult>> {
+        let tests: Array = ctx.globals().get("__sanchaar_tests")?;
+
+        let mut results = Vec::with_capacity(tests.len());
+        for test in tests.iter::<Object>() {
+            let test = test?;
+            let name = test.get::<_, String>("name")?;
+            let result = if test.get::<_, bool>("passed")? {
+                MatcherResult::Passed
+            } else {
+                let json = |key: &str| {
+                    test.get::<_, Option<String>>(key)
+                        .ok()
+                        .flatten()
+                        .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+                };
+                MatcherResult::Failed(Description {
+                    summary: test.get::<_, String>("message").unwrap_or_default(),
+                    expected: json("expected").unwrap_or(Value::Null),
+                    actual: json("actual"),
+                })
+            };
+            results.push(ConditionResult { name, result });
+        }
+
+        Ok(results)
+    }
+}
+
+/// Converts a script value to a string the way JavaScript would, keeping strings as is
+fn js_to_string<'js>(ctx: &Ctx<'js>, value: rquickjs::Value<'js>) -> String {
+    if let Some(s) = value.as_string() {
+        return s.to_string().unwrap_or_default();
+    }
+    ctx.json_stringify(value)
+        .ok()
+        .flatten()
+        .and_then(|s| s.to_string().ok())
+        .unwrap_or_default()
+}
+
+/// Resolves the `sanchaar` standard library module
+fn builtin_resolver() -> BuiltinResolver {
+    BuiltinResolver::default().with_module(stdlib::MODULE_NAME)
+}
+
+fn builtin_loader() -> ModuleLoader {
+    ModuleLoader::default().with_module(stdlib::MODULE_NAME, stdlib::SanchaarModule)
+}
+
+/// Formats a `console` argument, errors show their message
+fn console_arg<'js>(ctx: &Ctx<'js>, value: rquickjs::Value<'js>) -> String {
+    if let Some(exception) = value.as_exception() {
+        return format!("Error: {}", exception.message().unwrap_or_default());
+    }
+    if value.is_undefined() {
+        return "undefined".to_string();
+    }
+    js_to_string(ctx, value)
 }
 
 impl Default for ScriptEngine {
@@ -391,6 +782,16 @@ impl Default for ScriptEngine {
     }
 }
 
+/// Whether the script uses `await`, matches in strings and comments only cost the wrapping
+fn uses_await(script: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    script.match_indices("await").any(|(at, word)| {
+        let before = script[..at].chars().next_back();
+        let after = script[at + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -525,4 +926,337 @@ mod tests {
 
         assert_eq!(request.method, Method::POST);
     }
+
+    #[test]
+    fn test_await_in_pre_request() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            const token = await Promise.resolve("abc123");
+            request.headers["Authorization"] = "Bearer " + token;
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        engine
+            .execute_pre_request(script, &mut request, &mut variables)
+            .unwrap();
+
+        assert_eq!(
+            request.get_header("Authorization"),
+            Some("Bearer abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pre_request_without_await_runs_globally() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        // Declarations stay global when the script is not wrapped in an async function
+        let script = r#"
+            var prefix = "Bearer ";
+            function token() { return globalThis.prefix + "abc123"; }
+            request.headers["Authorization"] = token();
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        engine
+            .execute_pre_request(script, &mut request, &mut variables)
+            .unwrap();
+
+        assert_eq!(
+            request.get_header("Authorization"),
+            Some("Bearer abc123".to_string())
+        );
+        assert!(uses_await("const t = await token();"));
+        assert!(!uses_await("const awaited = awaitAll();"));
+    }
+
+    #[test]
+    fn test_stdlib_helpers() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            const { crypto, json } = await import("sanchaar");
+            request.headers["X-Signature"] = crypto.hmac("key", "data");
+            request.headers["X-Digest"] = sanchaar.crypto.sha256("abc");
+            request.headers["Authorization"] = "Basic " + sanchaar.base64.encode("user:pass");
+            request.headers["X-Id"] = String(json.get('{"user": {"id": 7}}', "$.user.id"));
+            variables["request_id"] = sanchaar.uuid.v4();
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        engine
+            .execute_pre_request(script, &mut request, &mut variables)
+            .unwrap();
+
+        assert_eq!(
+            request.get_header("X-Signature"),
+            Some(stdlib::hmac("key", "data", "sha256").unwrap())
+        );
+        assert_eq!(request.get_header("X-Digest"), Some(stdlib::sha256("abc")));
+        assert_eq!(
+            request.get_header("Authorization"),
+            Some("Basic dXNlcjpwYXNz".to_string())
+        );
+        assert_eq!(request.get_header("X-Id"), Some("7".to_string()));
+        assert_eq!(variables.get("request_id").map(|id| id.len()), Some(36));
+    }
+
+    #[test]
+    fn test_send_request_without_sender() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            await sanchaar.sendRequest("auth/login");
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        let error = engine
+            .execute_pre_request(script, &mut request, &mut variables)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("sendRequest is not available"));
+    }
+
+    #[test]
+    fn test_script_timeout() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let engine = ScriptEngine::new()
+            .unwrap()
+            .with_timeout(Duration::from_millis(50));
+        let error = engine
+            .execute_pre_request("while (true) {}", &mut request, &mut variables)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Script timed out");
+    }
+
+    #[test]
+    fn test_console_output() {
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            console.log("url", request.url, { id: 1 });
+            console.warn("slow");
+            throw new Error("boom");
+        "#;
+
+        let console = ScriptConsole::new();
+        let engine = ScriptEngine::new().unwrap().with_console(console.clone());
+        assert!(
+            engine
+                .execute_pre_request(script, &mut request, &mut variables)
+                .is_err()
+        );
+
+        let entries = console.entries();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].level, ConsoleLevel::Log);
+        assert_eq!(
+            entries[0].message,
+            r#"url https://api.example.com {"id":1}"#
+        );
+        assert_eq!(entries[1].level, ConsoleLevel::Warn);
+        assert_eq!(entries[2].script, ScriptKind::PreRequest);
+        assert_eq!(entries[2].level, ConsoleLevel::Error);
+        assert!(entries[2].message.contains("Error: boom"));
+    }
+
+    #[test]
+    fn test_import_modules() {
+        let dir = std::env::temp_dir().join(format!("sanchaar-import-{}", std::process::id()));
+        let scripts = dir.join("scripts");
+        std::fs::create_dir_all(scripts.join("lib")).unwrap();
+        std::fs::write(
+            scripts.join("lib").join("auth.js"),
+            r#"
+                import { crypto } from "sanchaar";
+                export function sign(data) {
+                    return crypto.hmac("key", data);
+                }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("secret.js"), "export const secret = 1;").unwrap();
+
+        let mut request = ScriptRequest::new(
+            Method::GET,
+            "https://api.example.com".to_string(),
+            KeyValList::new(),
+            KeyValList::new(),
+        );
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            import { sign } from "./lib/auth.js";
+            request.headers["X-Signature"] = sign("data");
+        "#;
+        let engine = ScriptEngine::new().unwrap().with_modules(scripts.clone());
+        engine
+            .execute_pre_request(script, &mut request, &mut variables)
+            .unwrap();
+        assert_eq!(
+            request.get_header("X-Signature"),
+            Some(stdlib::hmac("key", "data", "sha256").unwrap())
+        );
+
+        let script = r#"
+            import { secret } from "../secret.js";
+        "#;
+        let engine = ScriptEngine::new().unwrap().with_modules(scripts);
+        assert!(
+            engine
+                .execute_pre_request(script, &mut request, &mut variables)
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn create_test_response() -> ScriptResponse {
+        ScriptResponse {
+            status: 200,
+            headers: HashMap::from([("content-type".to_string(), "application/json".to_string())]),
+            body: r#"{"id": 42, "tags": ["a", "b"]}"#.to_string(),
+            duration_ms: 120,
+        }
+    }
+
+    #[test]
+    fn test_script_tests() {
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            const body = JSON.parse(response.body);
+            test("status is ok", () => {
+                expect(response).to.have.status(200);
+                expect(response).to.have.header("Content-Type", "application/json");
+            });
+            test("body has id", () => {
+                expect(body).to.have.property("id", 42);
+                expect(body.tags).to.include("b").and.to.have.lengthOf(2);
+                expect(response.duration).to.be.below(500);
+            });
+            test("id is a string", () => {
+                expect(body.id).to.be.a("string");
+            });
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        let tests = engine
+            .execute_post_request(script, &create_test_response(), &mut variables)
+            .unwrap();
+
+        assert_eq!(tests.len(), 3);
+        assert_eq!(tests[0].name, "status is ok");
+        assert!(matches!(tests[0].result, MatcherResult::Passed));
+        assert!(matches!(tests[1].result, MatcherResult::Passed));
+
+        let MatcherResult::Failed(description) = &tests[2].result else {
+            panic!("Expected test to fail");
+        };
+        assert_eq!(description.summary, "expected 42 to be a string");
+        assert_eq!(description.expected, Value::from("string"));
+        assert_eq!(description.actual, Some(Value::from(42)));
+    }
+
+    #[test]
+    fn test_script_expect_not() {
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            test("negated", () => {
+                expect(response.status).to.not.equal(404);
+                expect({ a: [1, 2] }).to.eql({ a: [1, 2] });
+                expect(response.status).to.not.be.oneOf([200, 201]);
+            });
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        let tests = engine
+            .execute_post_request(script, &create_test_response(), &mut variables)
+            .unwrap();
+
+        let MatcherResult::Failed(description) = &tests[0].result else {
+            panic!("Expected test to fail");
+        };
+        assert_eq!(
+            description.summary,
+            "expected 200 not to be one of [200,201]"
+        );
+    }
+
+    #[test]
+    fn test_async_script_tests() {
+        let mut variables = ScriptVariables::new();
+
+        let script = r#"
+            test("async passes", async () => {
+                const status = await Promise.resolve(response.status);
+                expect(status).to.equal(200);
+            });
+            test("async fails", async () => {
+                const status = await Promise.resolve(response.status);
+                expect(status).to.equal(404);
+            });
+            test("then fails", () => Promise.resolve(1).then((v) => expect(v).to.equal(2)));
+            test("never settles", () => new Promise(() => {}));
+        "#;
+
+        let engine = ScriptEngine::new().unwrap();
+        let tests = engine
+            .execute_post_request(script, &create_test_response(), &mut variables)
+            .unwrap();
+
+        assert_eq!(tests.len(), 4);
+        assert!(matches!(tests[0].result, MatcherResult::Passed));
+
+        let MatcherResult::Failed(description) = &tests[1].result else {
+            panic!("Expected async test to fail");
+        };
+        assert_eq!(description.summary, "expected 200 to equal 404");
+        assert!(matches!(tests[2].result, MatcherResult::Failed(_)));
+
+        let MatcherResult::Failed(description) = &tests[3].result else {
+            panic!("Expected unsettled test to fail");
+        };
+        assert_eq!(description.summary, "test did not complete");
+    }
 }
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use rquickjs::loader::Resolver;
+use rquickjs::{Ctx, Error};
+
+use crate::persistence::JS_EXTENSION;
+
+/// Resolves relative imports to files in the collection scripts directory,
+/// imports that resolve outside of it are rejected
+pub struct ScriptsResolver {
+    root: PathBuf,
+}
+
+impl ScriptsResolver {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+        }
+    }
+
+    fn resolve_path(&self, base: &str, name: &str) -> Option<PathBuf> {
+        if !name.starts_with("./") && !name.starts_with("../") {
+            return None;
+        }
+
+        let dir = Path::new(base).parent().unwrap_or(&self.root);
+        let mut path = dir.join(name);
+        if path.extension().is_none() {
+            path.set_extension(JS_EXTENSION);
+        }
+
+        // Resolves symlinks and `..` so the check can't be bypassed
+        let path = path.canonicalize().ok()?;
+        path.starts_with(&self.root).then_some(path)
+    }
+}
+
+impl Resolver for ScriptsResolver {
+    fn resolve<'js>(
+        &mut self,
+        _ctx: &Ctx<'js>,
+        base: &str,
+        name: &str,
+    ) -> rquickjs::Result<String> {
+        self.resolve_path(base, name)
+            .map(|path| path.to_string_lossy().to_string())
+            .ok_or_else(|| Error::new_resolving(base, name))
+    }
+}
+
+/// Scripts with static `import` or `export` statements are run as ES modules
+pub fn is_module(script: &str) -> bool {
+    script.lines().map(str::trim_start).any(|line| {
+        line.strip_prefix("import")
+            .is_some_and(|rest| rest.starts_with([' ', '{', '*', '"', '\'']))
+            || line.starts_with("export ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_module() {
+        assert!(is_module(
+            "import { sign } from \"./lib/auth.js\";\nsign();"
+        ));
+        assert!(is_module("  import * as auth from './auth.js'"));
+        assert!(is_module("export const a = 1;"));
+        assert!(!is_module("const { crypto } = await import(\"sanchaar\");"));
+        assert!(!is_module("request.headers[\"important\"] = \"1\";"));
+    }
+
+    #[test]
+    fn test_resolve_sandboxed() {
+        let dir = std::env::temp_dir().join(format!("sanchaar-modules-{}", std::process::id()));
+        let scripts = dir.join("scripts");
+        std::fs::create_dir_all(scripts.join("lib")).unwrap();
+        std::fs::write(scripts.join("lib").join("auth.js"), "export const a = 1;").unwrap();
+        std::fs::write(dir.join("secret.js"), "export const b = 2;").unwrap();
+
+        let resolver = ScriptsResolver::new(&scripts);
+        let base = resolver.root.join("pre-request.js");
+        let base = base.to_string_lossy();
+
+        let resolved = resolver.resolve_path(&base, "./lib/auth").unwrap();
+        assert_eq!(resolved, resolver.root.join("lib").join("auth.js"));
+        assert!(resolver.resolve_path(&base, "../secret.js").is_none());
+        assert!(resolver.resolve_path(&base, "./missing.js").is_none());
+        assert!(resolver.resolve_path(&base, "lib/auth.js").is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,326 @@
+// Helpers available to scripts.
+// Test results are collected in `__sanchaar_tests` and read back after the script runs,
+// errors of pre-request scripts are kept in `__sanchaar_error`.
+var __sanchaar_tests = [];
+var __sanchaar_error = null;
+
+class AssertionError extends Error {
+  constructor(message, expected, actual) {
+    super(message);
+    this.name = "AssertionError";
+    this.expected = expected;
+    this.actual = actual;
+  }
+}
+
+// Keeps the error of a pre-request script, with its stack when available
+function __sanchaar_fail(e) {
+  __sanchaar_error = e instanceof Error && e.stack ? `${e}\n${e.stack}` : String(e);
+}
+
+function __sanchaar_json(value) {
+  if (value === undefined) {
+    return undefined;
+  }
+  try {
+    return JSON.stringify(value);
+  } catch (e) {
+    return JSON.stringify(String(value));
+  }
+}
+
+function __sanchaar_inspect(value) {
+  if (typeof value === "string") {
+    return JSON.stringify(value);
+  }
+  return __sanchaar_json(value) ?? String(value);
+}
+
+function __sanchaar_deep_equal(a, b) {
+  if (a === b) {
+    return true;
+  }
+  if (typeof a !== "object" || typeof b !== "object" || a === null || b === null) {
+    return false;
+  }
+  if (Array.isArray(a) !== Array.isArray(b)) {
+    return false;
+  }
+  const keys = Object.keys(a);
+  if (keys.length !== Object.keys(b).length) {
+    return false;
+  }
+  return keys.every((key) => __sanchaar_deep_equal(a[key], b[key]));
+}
+
+function __sanchaar_type(value) {
+  if (value === null) {
+    return "null";
+  }
+  if (Array.isArray(value)) {
+    return "array";
+  }
+  return typeof value;
+}
+
+class Expectation {
+  constructor(actual) {
+    this.actual = actual;
+    this.negate = false;
+  }
+
+  assert(passed, message, expected) {
+    if (passed === this.negate) {
+      const prefix = this.negate ? "not " : "";
+      throw new AssertionError(
+        `expected ${__sanchaar_inspect(this.actual)} ${prefix}${message}`,
+        expected,
+        this.actual
+      );
+    }
+    return this;
+  }
+
+  get to() { return this; }
+  get be() { return this; }
+  get been() { return this; }
+  get is() { return this; }
+  get that() { return this; }
+  get which() { return this; }
+  get and() { return this; }
+  get has() { return this; }
+  get have() { return this; }
+  get with() { return this; }
+  get deep() { return this; }
+
+  get not() {
+    this.negate = !this.negate;
+    return this;
+  }
+
+  get ok() {
+    return this.assert(Boolean(this.actual), "to be truthy", true);
+  }
+
+  get true() {
+    return this.assert(this.actual === true, "to be true", true);
+  }
+
+  get false() {
+    return this.assert(this.actual === false, "to be false", false);
+  }
+
+  get null() {
+    return this.assert(this.actual === null, "to be null", null);
+  }
+
+  get undefined() {
+    return this.assert(this.actual === undefined, "to be undefined");
+  }
+
+  get empty() {
+    const value = this.actual;
+    const size = value === null || value === undefined
+      ? 0
+      : typeof value === "object" && !Array.isArray(value)
+        ? Object.keys(value).length
+        : value.length;
+    return this.assert(size === 0, "to be empty");
+  }
+
+  equal(expected) {
+    return this.assert(this.actual === expected, `to equal ${__sanchaar_inspect(expected)}`, expected);
+  }
+
+  equals(expected) {
+    return this.equal(expected);
+  }
+
+  eq(expected) {
+    return this.equal(expected);
+  }
+
+  eql(expected) {
+    return this.assert(
+      __sanchaar_deep_equal(this.actual, expected),
+      `to deeply equal ${__sanchaar_inspect(expected)}`,
+      expected
+    );
+  }
+
+  above(expected) {
+    return this.assert(this.actual > expected, `to be above ${expected}`, expected);
+  }
+
+  gt(expected) {
+    return this.above(expected);
+  }
+
+  least(expected) {
+    return this.assert(this.actual >= expected, `to be at least ${expected}`, expected);
+  }
+
+  gte(expected) {
+    return this.least(expected);
+  }
+
+  below(expected) {
+    return this.assert(this.actual < expected, `to be below ${expected}`, expected);
+  }
+
+  lt(expected) {
+    return this.below(expected);
+  }
+
+  most(expected) {
+    return this.assert(this.actual <= expected, `to be at most ${expected}`, expected);
+  }
+
+  lte(expected) {
+    return this.most(expected);
+  }
+
+  within(start, end) {
+    return this.assert(
+      this.actual >= start && this.actual <= end,
+      `to be within ${start}..${end}`,
+      [start, end]
+    );
+  }
+
+  a(type) {
+    const actual = __sanchaar_type(this.actual);
+    return this.assert(actual === type.toLowerCase(), `to be a ${type}`, type);
+  }
+
+  an(type) {
+    return this.a(type);
+  }
+
+  oneOf(list) {
+    return this.assert(list.includes(this.actual), `to be one of ${__sanchaar_inspect(list)}`, list);
+  }
+
+  include(expected) {
+    const value = this.actual;
+    const included = typeof value === "string" || Array.isArray(value)
+      ? value.includes(expected)
+      : value !== null && typeof value === "object" && Object.keys(expected).every(
+        (key) => __sanchaar_deep_equal(value[key], expected[key])
+      );
+    return this.assert(included, `to include ${__sanchaar_inspect(expected)}`, expected);
+  }
+
+  includes(expected) {
+    return this.include(expected);
+  }
+
+  contain(expected) {
+    return this.include(expected);
+  }
+
+  contains(expected) {
+    return this.include(expected);
+  }
+
+  match(pattern) {
+    const regex = pattern instanceof RegExp ? pattern : new RegExp(pattern);
+    return this.assert(regex.test(String(this.actual)), `to match ${regex}`, String(regex));
+  }
+
+  property(name, ...value) {
+    const target = this.actual;
+    const has = target !== null && target !== undefined && Object.prototype.hasOwnProperty.call(Object(target), name);
+    if (value.length === 0) {
+      return this.assert(has, `to have property ${__sanchaar_inspect(name)}`, name);
+    }
+    return this.assert(
+      has && __sanchaar_deep_equal(target[name], value[0]),
+      `to have property ${__sanchaar_inspect(name)} of ${__sanchaar_inspect(value[0])}`,
+      value[0]
+    );
+  }
+
+  length(expected) {
+    const length = this.actual?.length;
+    return this.assert(length === expected, `to have length ${expected}`, expected);
+  }
+
+  lengthOf(expected) {
+    return this.length(expected);
+  }
+
+  status(expected) {
+    const status = this.actual?.status;
+    return this.assert(status === expected, `to have status ${expected}`, expected);
+  }
+
+  header(name, ...value) {
+    const headers = this.actual?.headers ?? {};
+    const key = Object.keys(headers).find((h) => h.toLowerCase() === name.toLowerCase());
+    if (value.length === 0) {
+      return this.assert(key !== undefined, `to have header ${__sanchaar_inspect(name)}`, name);
+    }
+    return this.assert(
+      key !== undefined && headers[key] === value[0],
+      `to have header ${__sanchaar_inspect(name)} of ${__sanchaar_inspect(value[0])}`,
+      value[0]
+    );
+  }
+}
+
+function expect(actual) {
+  return new Expectation(actual);
+}
+
+function __sanchaar_test_failure(name, e) {
+  const error = e instanceof Error ? e : new Error(String(e));
+  return {
+    name,
+    passed: false,
+    message: error.message,
+    expected: __sanchaar_json(error.expected),
+    actual: __sanchaar_json(error.actual),
+  };
+}
+
+function test(name, fn) {
+  name = String(name);
+  let result;
+  try {
+    result = fn();
+  } catch (e) {
+    __sanchaar_tests.push(__sanchaar_test_failure(name, e));
+    return;
+  }
+
+  if (result !== null && typeof result === "object" && typeof result.then === "function") {
+    // Async tests are settled when the pending jobs run after the script, the entry keeps their
+    // order and fails the ones that never settle
+    const index = __sanchaar_tests.length;
+    __sanchaar_tests.push({ name, passed: false, message: "test did not complete" });
+    result.then(
+      () => { __sanchaar_tests[index] = { name, passed: true }; },
+      (e) => { __sanchaar_tests[index] = __sanchaar_test_failure(name, e); }
+    );
+  } else {
+    __sanchaar_tests.push({ name, passed: true });
+  }
+}
+
+// The standard library helpers (`crypto`, `base64`, `hex`, `uuid`, `time`, `json`) are added from Rust
+var sanchaar = {
+  // Sends a request by its path in the collection, or a `{ method, url, headers, params, body, auth }` object.
+  // The script blocks until the response arrives, so requests started together with `Promise.all`
+  // are still sent one after the other. Only available on a multi-threaded tokio runtime.
+  sendRequest(target) {
+    if (typeof __sanchaar_send !== "function") {
+      return Promise.reject(new Error("sendRequest is not available in this script"));
+    }
+    try {
+      return Promise.resolve(JSON.parse(__sanchaar_send(JSON.stringify(target))));
+    } catch (e) {
+      return Promise.reject(e);
+    }
+  },
+};
@@ -1,4 +1,6 @@
+use super::send::{RequestSender, ScriptContext};
 use super::{ScriptEngine, ScriptRequest, ScriptResponse, ScriptVariables};
+use crate::assertions::runner::ConditionResult;
 use crate::client::Response;
 use crate::http::request::Request;
 use anyhow::{Context, Result};
@@ -28,6 +30,19 @@ impl PreRequestRunner {
         self
     }
 
+    /// Collects the script output in the context console and lets the script import
+    /// modules from the collection scripts
+    pub fn with_context(mut self, context: &ScriptContext) -> Self {
+        self.engine = with_context(self.engine, context);
+        self
+    }
+
+    /// Lets the script send requests with `sanchaar.sendRequest`
+    pub fn with_sender(mut self, sender: RequestSender) -> Self {
+        self.engine = self.engine.with_sender(sender);
+        self
+    }
+
     /// Execute pre-request script and return modified request
     pub fn execute(&mut self, script: &str, request: &Request) -> Result<Request> {
         // Convert Request to ScriptRequest
@@ -36,7 +51,10 @@ impl PreRequestRunner {
             request.url.clone(),
             request.headers.clone(),
             request.query_params.clone(),
-        );
+        )
+        .with_path_params(request.path_params.clone())
+        .with_body(request.body.clone())
+        .with_auth(request.auth.clone());
 
         // Execute script
         self.engine
@@ -50,12 +68,13 @@ impl PreRequestRunner {
             url: script_req.url,
             headers: script_req.headers,
             query_params: script_req.query_params,
-            path_params: request.path_params.clone(),
-            body: request.body.clone(),
-            auth: request.auth.clone(),
+            path_params: script_req.path_params,
+            body: script_req.body,
+            auth: script_req.auth,
             assertions: request.assertions.clone(),
             pre_request: request.pre_request.clone(),
             post_request: request.post_request.clone(),
+            abort_on_script_error: request.abort_on_script_error,
         })
     }
 
@@ -94,8 +113,15 @@ impl PostRequestRunner {
         self
     }
 
-    /// Execute post-request script
-    pub fn execute(&mut self, script: &str, response: &Response) -> Result<()> {
+    /// Collects the script output in the context console and lets the script import
+    /// modules from the collection scripts
+    pub fn with_context(mut self, context: &ScriptContext) -> Self {
+        self.engine = with_context(self.engine, context);
+        self
+    }
+
+    /// Execute post-request script and return the results of its tests
+    pub fn execute(&mut self, script: &str, response: &Response) -> Result<Vec<ConditionResult>> {
         // Convert Response to ScriptResponse
         let script_resp = ScriptResponse {
             status: response.status.as_u16(),
@@ -111,9 +137,7 @@ impl PostRequestRunner {
         // Execute script
         self.engine
             .execute_post_request(script, &script_resp, &mut self.variables)
-            .context("Post-request script execution failed")?;
-
-        Ok(())
+            .context("Post-request script execution failed")
     }
 
     /// Get variables after script execution
@@ -127,6 +151,14 @@ impl PostRequestRunner {
     }
 }
 
+fn with_context(engine: ScriptEngine, context: &ScriptContext) -> ScriptEngine {
+    let engine = engine.with_console(context.console().clone());
+    match context.scripts_dir() {
+        Some(dir) => engine.with_modules(dir),
+        None => engine,
+    }
+}
+
 /// Run pre-request script on a request
 pub fn run_pre_request_script(
     script: &str,
@@ -144,27 +176,36 @@ pub fn run_pre_request_script(
     Ok((modified_request, variables))
 }
 
-/// Run post-request script on a response
+/// Run post-request script on a response, returning its variables and test results
 pub fn run_post_request_script(
     script: &str,
     response: &Response,
     variables: Option<ScriptVariables>,
-) -> Result<ScriptVariables> {
-    let mut runner = PostRequestRunner::new()?;
+    context: &ScriptContext,
+) -> Result<(ScriptVariables, Vec<ConditionResult>)> {
+    let mut runner = PostRequestRunner::new()?.with_context(context);
     if let Some(vars) = variables {
         runner = runner.with_variables(vars);
     }
 
-    runner.execute(script, response)?;
-    Ok(runner.variables().clone())
+    let tests = runner.execute(script, response)?;
+    Ok((runner.variables().clone(), tests))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::assertions::Assertions;
-    use crate::http::KeyValList;
     use crate::http::request::{Auth, Method, RequestBody};
+    use crate::http::{KeyValList, KeyValue};
+
+    fn kv(name: &str, value: &str) -> KeyValue {
+        KeyValue {
+            name: name.to_string(),
+            value: value.to_string(),
+            disabled: false,
+        }
+    }
 
     fn create_test_request() -> Request {
         Request {
@@ -179,6 +220,7 @@ mod tests {
             assertions: Assertions::default(),
             pre_request: None,
             post_request: None,
+            abort_on_script_error: None,
         }
     }
 
@@ -240,4 +282,67 @@ mod tests {
         let (modified_request, _) = run_pre_request_script(script, &request, None).unwrap();
         assert_eq!(modified_request.method, Method::PUT);
     }
+
+    #[test]
+    fn test_pre_request_script_modify_json_body() {
+        let mut request = create_test_request();
+        request.body = RequestBody::Json(r#"{"amount": 10}"#.to_string());
+        let script = r#"
+            const body = JSON.parse(request.body);
+            body.nonce = "n-1";
+            request.body = body;
+        "#;
+
+        let (modified_request, _) = run_pre_request_script(script, &request, None).unwrap();
+        assert_eq!(
+            modified_request.body,
+            RequestBody::Json(r#"{"amount":10,"nonce":"n-1"}"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_pre_request_script_modify_form_fields() {
+        let mut request = create_test_request();
+        request.body = RequestBody::Form(KeyValList::from(vec![
+            kv("user", "john"),
+            kv("remove", "me"),
+        ]));
+        let script = r#"
+            delete request.body.remove;
+            request.body.signature = "abc";
+        "#;
+
+        let (modified_request, _) = run_pre_request_script(script, &request, None).unwrap();
+        assert_eq!(
+            modified_request.body,
+            RequestBody::Form(KeyValList::from(vec![
+                kv("user", "john"),
+                kv("signature", "abc"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_pre_request_script_modify_path_params_and_auth() {
+        let mut request = create_test_request();
+        request.path_params = KeyValList::from(vec![kv("id", "1")]);
+        let script = r#"
+            request.pathParams.id = "42";
+            if (request.auth.type === "none") {
+                request.auth = { type: "bearer", token: "secret" };
+            }
+        "#;
+
+        let (modified_request, _) = run_pre_request_script(script, &request, None).unwrap();
+        assert_eq!(
+            modified_request.path_params,
+            KeyValList::from(vec![kv("id", "42")])
+        );
+        assert_eq!(
+            modified_request.auth,
+            Auth::Bearer {
+                token: "secret".to_string()
+            }
+        );
+    }
 }
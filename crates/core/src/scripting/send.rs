@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use super::auth::ScriptAuth;
+use super::console::{ConsoleLevel, ScriptConsole, ScriptKind};
+use crate::assertions::Assertions;
+use crate::auth::AuthState;
+use crate::client::{Response, send_request};
+use crate::http::collection::Collection;
+use crate::http::environment::EnvironmentChain;
+use crate::http::request::{Auth, Request, RequestBody};
+use crate::http::{KeyValList, KeyValue};
+use crate::persistence::collections::resolve_auth;
+use crate::persistence::request::read_request;
+use crate::persistence::{REQUESTS, SCRIPTS, TOML_EXTENSION};
+use crate::transformers::request::{ScriptAborted, transform_request};
+
+/// How deep scripts can chain `sendRequest` calls
+const MAX_DEPTH: usize = 3;
+
+/// Collection a script runs in, used to send requests by their path under `requests`
+#[derive(Debug, Clone, Default)]
+pub struct ScriptContext {
+    root: Option<PathBuf>,
+    headers: Arc<KeyValList>,
+    scripts: HashMap<String, PathBuf>,
+    console: ScriptConsole,
+    depth: usize,
+    deadline: Option<Instant>,
+    abort_on_error: bool,
+}
+
+impl ScriptContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn for_collection(collection: &Collection) -> Self {
+        Self {
+            root: Some(collection.path.clone()),
+            headers: Arc::clone(&collection.headers),
+            scripts: collection
+                .scripts
+                .iter()
+                .map(|s| (s.name.clone(), s.path.clone()))
+                .collect(),
+            console: ScriptConsole::new(),
+            depth: 0,
+            deadline: None,
+            abort_on_error: collection.abort_on_script_error,
+        }
+    }
+
+    /// Collects the output of the scripts in this console, including the scripts of sent requests
+    pub fn with_console(mut self, console: ScriptConsole) -> Self {
+        self.console = console;
+        self
+    }
+
+    pub fn console(&self) -> &ScriptConsole {
+        &self.console
+    }
+
+    /// Directory scripts can import modules from
+    pub fn scripts_dir(&self) -> Option<PathBuf> {
+        self.root.as_ref().map(|root| root.join(SCRIPTS))
+    }
+
+    /// Whether a failing pre-request script aborts the send, unless the request overrides it
+    pub fn abort_on_error(&self) -> bool {
+        self.abort_on_error
+    }
+
+    /// Deadline of the script that sent the request, nested scripts must finish before it
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    fn nested(&self, deadline: Instant) -> Result<Self> {
+        if self.depth >= MAX_DEPTH {
+            bail!("sendRequest can only be nested {} levels deep", MAX_DEPTH);
+        }
+
+        Ok(Self {
+            depth: self.depth + 1,
+            deadline: Some(deadline),
+            ..self.clone()
+        })
+    }
+
+    async fn read_request(&self, path: &str) -> Result<Request> {
+        let root = self
+            .root
+            .as_ref()
+            .context("Requests can only be sent by path from a collection")?;
+
+        let path = if path.ends_with(TOML_EXTENSION) {
+            root.join(REQUESTS).join(path)
+        } else {
+            root.join(REQUESTS).join(format!("{path}{TOML_EXTENSION}"))
+        };
+
+        let mut req = self.load_request(&path, None).await?;
+        // Only the response is returned to the script, assertions and post scripts are skipped
+        req.post_request = None;
+
+        Ok(req)
+    }
+
+    /// Reads a request file of the collection the way every runner sends it, see
+    /// [`ScriptContext::prepare_request`]. `inherited` is the auth resolved from an open
+    /// collection, without it the folder and collection files are read.
+    pub async fn load_request(&self, path: &Path, inherited: Option<Auth>) -> Result<Request> {
+        let req = read_request(&path.to_path_buf())
+            .await
+            .with_context(|| format!("Failed to read request {}", path.display()))?;
+
+        let inherited = match (&req.auth, inherited, &self.root) {
+            (Auth::Inherit, None, Some(root)) => {
+                Some(resolve_auth(root, path, Auth::Inherit).await?)
+            }
+            (_, inherited, _) => inherited,
+        };
+        self.prepare_request(req, inherited).await
+    }
+
+    /// Applies the collection headers, inherited auth and script contents to a request.
+    /// An unreadable pre-request script is skipped like a failing one, unless the send
+    /// aborts on script errors.
+    pub async fn prepare_request(
+        &self,
+        mut req: Request,
+        inherited: Option<Auth>,
+    ) -> Result<Request> {
+        let mut headers = KeyValList::clone(&self.headers);
+        headers.extend(req.headers);
+        req.headers = headers;
+
+        if req.auth == Auth::Inherit
+            && let Some(auth) = inherited
+        {
+            req.auth = auth;
+        }
+
+        req.pre_request = match req.pre_request.take() {
+            Some(name) => match self.read_script(&name).await {
+                Ok(script) => Some(script),
+                Err(e) if req.abort_on_script_error.unwrap_or(self.abort_on_error) => {
+                    return Err(e.context(ScriptAborted));
+                }
+                Err(e) => {
+                    self.console.push(
+                        ScriptKind::PreRequest,
+                        ConsoleLevel::Error,
+                        format!("{:#}", e),
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        req.post_request = match req.post_request.take() {
+            Some(name) => Some(self.read_script(&name).await?),
+            None => None,
+        };
+
+        Ok(req)
+    }
+
+    async fn read_script(&self, name: &str) -> Result<String> {
+        let path = self
+            .scripts
+            .get(name)
+            .with_context(|| format!("Script {} not found", name))?;
+
+        tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to load script {}", name))
+    }
+}
+
+/// Request passed to `sendRequest`, either a path in the collection or a request object
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SendTarget {
+    Path(String),
+    Request(ScriptSendRequest),
+}
+
+#[derive(Debug, Deserialize)]
+struct ScriptSendRequest {
+    #[serde(default)]
+    method: Option<String>,
+    url: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    params: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+    #[serde(default)]
+    auth: Option<ScriptAuth>,
+}
+
+fn to_kv_list(map: HashMap<String, String>) -> KeyValList {
+    KeyValList::from(
+        map.into_iter()
+            .map(|(name, value)| KeyValue {
+                name,
+                value,
+                disabled: false,
+            })
+            .collect(),
+    )
+}
+
+impl TryFrom<ScriptSendRequest> for Request {
+    type Error = anyhow::Error;
+
+    fn try_from(req: ScriptSendRequest) -> Result<Self> {
+        let method = match req.method {
+            Some(method) => method
+                .to_uppercase()
+                .parse()
+                .context("Invalid HTTP method")?,
+            None => Default::default(),
+        };
+        let body = match req.body {
+            None | Some(Value::Null) => RequestBody::None,
+            Some(Value::String(text)) => RequestBody::Text(text),
+            Some(json) => RequestBody::Json(json.to_string()),
+        };
+
+        Ok(Request {
+            description: String::new(),
+            method,
+            url: req.url,
+            headers: to_kv_list(req.headers),
+            query_params: to_kv_list(req.params),
+            path_params: KeyValList::new(),
+            body,
+            auth: req.auth.map(Auth::from).unwrap_or(Auth::None),
+            assertions: Assertions::default(),
+            pre_request: None,
+            post_request: None,
+            abort_on_script_error: None,
+        })
+    }
+}
+
+/// Sends requests for scripts with the client, environment and collection of the request running them
+#[derive(Clone)]
+pub struct RequestSender {
+    pub client: reqwest::Client,
+    pub env: EnvironmentChain,
+    pub auth_state: Arc<AuthState>,
+    pub context: ScriptContext,
+}
+
+impl RequestSender {
+    /// Sends the JSON encoded target and waits for the response, giving up at the deadline
+    pub(super) fn send_blocking(&self, target: &str, deadline: Instant) -> Result<String> {
+        let handle = Handle::try_current()
+            .map_err(|_| anyhow!("sendRequest is only available while sending a request"))?;
+        if handle.runtime_flavor() == RuntimeFlavor::CurrentThread {
+            bail!("sendRequest needs a multi-threaded runtime");
+        }
+
+        let target: SendTarget = serde_json::from_str(target).context("Invalid request")?;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let send = tokio::time::timeout(remaining, self.send(target, deadline));
+
+        // The script runs synchronously inside the task sending the outer request
+        let response = tokio::task::block_in_place(|| handle.block_on(send))
+            .map_err(|_| anyhow!("Script timed out waiting for the response"))??;
+
+        let headers = response
+            .headers
+            .iter()
+            .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect::<HashMap<_, _>>();
+
+        Ok(json!({
+            "status": response.status.as_u16(),
+            "headers": headers,
+            "body": String::from_utf8_lossy(&response.body.data),
+            "duration": response.duration.as_millis() as u64,
+        })
+        .to_string())
+    }
+
+    async fn send(&self, target: SendTarget, deadline: Instant) -> Result<Response> {
+        let context = self.context.nested(deadline)?;
+        let request = match target {
+            SendTarget::Path(path) => context.read_request(&path).await?,
+            SendTarget::Request(req) => req.try_into()?,
+        };
+
+        let prepared = transform_request(
+            self.client.clone(),
+            request,
+            self.env.clone(),
+            &self.auth_state,
+            &context,
+        )
+        .await?;
+
+        send_request(self.client.clone(), prepared).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::request::Method;
+
+    #[test]
+    fn test_nested_depth() {
+        let deadline = Instant::now();
+        let mut context = ScriptContext::new();
+        for _ in 0..MAX_DEPTH {
+            context = context.nested(deadline).unwrap();
+        }
+
+        assert_eq!(context.deadline(), Some(deadline));
+        assert!(context.nested(deadline).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_request() {
+        let root = std::env::temp_dir().join(format!("sanchaar-load-{}", std::process::id()));
+        let path = root.join(REQUESTS).join("login.toml");
+        let script = root.join(SCRIPTS).join("token.js");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(script.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "method = \"POST\"\nurl = \"https://example.com\"\nauth = \"inherit\"\n\
+             post_request = \"token.js\"\n[[headers]]\nname = \"X-Request\"\nvalue = \"1\"\n",
+        )
+        .unwrap();
+        std::fs::write(&script, "pm.test('ok', () => {})").unwrap();
+
+        let context = ScriptContext {
+            root: Some(root.clone()),
+            headers: Arc::new(KeyValList::from(vec![KeyValue {
+                disabled: false,
+                name: "X-Collection".to_string(),
+                value: "1".to_string(),
+            }])),
+            scripts: HashMap::from([("token.js".to_string(), script)]),
+            ..Default::default()
+        };
+        let inherited = Auth::Bearer {
+            token: "abc".to_string(),
+        };
+        let req = context.load_request(&path, Some(inherited.clone())).await;
+        let missing = ScriptContext::new().load_request(&path, None).await;
+        let _ = std::fs::remove_dir_all(&root);
+
+        let req = req.unwrap();
+        let headers: Vec<_> = req.headers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(headers, vec!["X-Collection", "X-Request"]);
+        assert_eq!(req.auth, inherited);
+        assert_eq!(req.post_request.as_deref(), Some("pm.test('ok', () => {})"));
+        assert!(
+            missing
+                .unwrap_err()
+                .to_string()
+                .starts_with("Script token.js not found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_pre_request_script() {
+        let request = |abort_on_script_error| Request {
+            pre_request: Some("sign.js".to_string()),
+            abort_on_script_error,
+            ..Default::default()
+        };
+
+        let context = ScriptContext::new();
+        let req = context.prepare_request(request(None), None).await.unwrap();
+        assert_eq!(req.pre_request, None);
+        let entries = context.console().entries();
+        assert_eq!(entries[0].level, ConsoleLevel::Error);
+        assert!(entries[0].message.starts_with("Script sign.js not found"));
+
+        let error = context
+            .prepare_request(request(Some(true)), None)
+            .await
+            .unwrap_err();
+        assert!(error.downcast_ref::<ScriptAborted>().is_some());
+    }
+
+    #[test]
+    fn test_send_target_request() {
+        let target: SendTarget = serde_json::from_str(
+            r#"{
+                "method": "post",
+                "url": "https://api.example.com/login",
+                "headers": { "X-Trace": "1" },
+                "body": { "user": "admin" },
+                "auth": { "type": "bearer", "token": "abc" }
+            }"#,
+        )
+        .unwrap();
+        let SendTarget::Request(req) = target else {
+            panic!("Expected a request object");
+        };
+        let req = Request::try_from(req).unwrap();
+
+        assert_eq!(req.method, Method::POST);
+        assert_eq!(req.headers.iter().next().unwrap().name, "X-Trace");
+        assert_eq!(
+            req.body,
+            RequestBody::Json(r#"{"user":"admin"}"#.to_string())
+        );
+        assert_eq!(
+            req.auth,
+            Auth::Bearer {
+                token: "abc".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_send_target_path() {
+        let target: SendTarget = serde_json::from_str(r#""auth/login""#).unwrap();
+        assert!(matches!(target, SendTarget::Path(path) if path == "auth/login"));
+    }
+}
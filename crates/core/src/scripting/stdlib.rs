@@ -0,0 +1,318 @@
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use jsonpath_rust::JsonPath;
+use md5::Md5;
+use rquickjs::function::Opt;
+use rquickjs::module::{Declarations, Exports, ModuleDef};
+use rquickjs::{Ctx, Exception, Function, Object};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+
+/// Name scripts import the helpers from
+pub const MODULE_NAME: &str = "sanchaar";
+
+/// Helper groups exported by the module and set on the global `sanchaar` object
+const HELPERS: [&str; 6] = ["crypto", "base64", "hex", "uuid", "time", "json"];
+
+pub fn sha256(data: &str) -> String {
+    hex::encode(Sha256::digest(data.as_bytes()))
+}
+
+pub fn sha512(data: &str) -> String {
+    hex::encode(Sha512::digest(data.as_bytes()))
+}
+
+pub fn md5(data: &str) -> String {
+    hex::encode(Md5::digest(data.as_bytes()))
+}
+
+/// Hex encoded HMAC of the data, with `sha256`, `sha512` or `md5`
+pub fn hmac(key: &str, data: &str, algorithm: &str) -> Result<String> {
+    let key = key.as_bytes();
+    let data = data.as_bytes();
+    let signature = match algorithm.to_lowercase().as_str() {
+        "sha256" => {
+            let mut mac =
+                Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        "sha512" => {
+            let mut mac =
+                Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any size");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        "md5" => {
+            let mut mac = Hmac::<Md5>::new_from_slice(key).expect("HMAC accepts keys of any size");
+            mac.update(data);
+            mac.finalize().into_bytes().to_vec()
+        }
+        _ => bail!("Unsupported HMAC algorithm {}", algorithm),
+    };
+
+    Ok(hex::encode(signature))
+}
+
+pub fn base64_encode(data: &str) -> String {
+    STANDARD.encode(data)
+}
+
+pub fn base64_decode(data: &str) -> Result<String> {
+    let bytes = STANDARD.decode(data.trim()).context("Invalid base64")?;
+    String::from_utf8(bytes).context("Decoded base64 is not valid UTF-8")
+}
+
+pub fn hex_encode(data: &str) -> String {
+    hex::encode(data)
+}
+
+pub fn hex_decode(data: &str) -> Result<String> {
+    let bytes = hex::decode(data.trim()).context("Invalid hex")?;
+    String::from_utf8(bytes).context("Decoded hex is not valid UTF-8")
+}
+
+pub fn uuid_v4() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Milliseconds since the Unix epoch
+pub fn time_now() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// Seconds since the Unix epoch
+pub fn time_unix() -> i64 {
+    Utc::now().timestamp()
+}
+
+/// Current time in RFC 3339 format, in UTC
+pub fn time_iso() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Values matched by the JSON path, in document order
+pub fn json_query(json: &Value, path: &str) -> Result<Vec<Value>> {
+    let values = json
+        .query(path)
+        .map_err(|e| anyhow::anyhow!("Invalid JSON path {}: {}", path, e))?;
+    Ok(values.into_iter().cloned().collect())
+}
+
+/// Converts an error to an exception thrown in the script
+fn throw(ctx: &Ctx<'_>, error: anyhow::Error) -> rquickjs::Error {
+    Exception::throw_message(ctx, &format!("{:#}", error))
+}
+
+/// Parses a script value as JSON, strings are parsed so response bodies can be passed as is
+fn to_json<'js>(ctx: &Ctx<'js>, value: rquickjs::Value<'js>) -> rquickjs::Result<Value> {
+    let json = match value.as_string() {
+        Some(text) => text.to_string()?,
+        None => match ctx.json_stringify(value)? {
+            Some(json) => json.to_string()?,
+            None => return Ok(Value::Null),
+        },
+    };
+    serde_json::from_str(&json).map_err(|e| throw(ctx, e.into()))
+}
+
+fn from_json<'js>(ctx: &Ctx<'js>, value: &Value) -> rquickjs::Result<rquickjs::Value<'js>> {
+    ctx.json_parse(value.to_string())
+}
+
+fn crypto_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let crypto = Object::new(ctx.clone())?;
+    crypto.set(
+        "sha256",
+        Function::new(ctx.clone(), |data: String| sha256(&data))?,
+    )?;
+    crypto.set(
+        "sha512",
+        Function::new(ctx.clone(), |data: String| sha512(&data))?,
+    )?;
+    crypto.set(
+        "md5",
+        Function::new(ctx.clone(), |data: String| md5(&data))?,
+    )?;
+    crypto.set(
+        "hmac",
+        Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'_>, key: String, data: String, algorithm: Opt<String>| {
+                let algorithm = algorithm.0.unwrap_or_else(|| "sha256".to_string());
+                hmac(&key, &data, &algorithm).map_err(|e| throw(&ctx, e))
+            },
+        )?,
+    )?;
+    Ok(crypto)
+}
+
+fn base64_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let base64 = Object::new(ctx.clone())?;
+    base64.set(
+        "encode",
+        Function::new(ctx.clone(), |data: String| base64_encode(&data))?,
+    )?;
+    base64.set(
+        "decode",
+        Function::new(ctx.clone(), |ctx: Ctx<'_>, data: String| {
+            base64_decode(&data).map_err(|e| throw(&ctx, e))
+        })?,
+    )?;
+    Ok(base64)
+}
+
+fn hex_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let hex = Object::new(ctx.clone())?;
+    hex.set(
+        "encode",
+        Function::new(ctx.clone(), |data: String| hex_encode(&data))?,
+    )?;
+    hex.set(
+        "decode",
+        Function::new(ctx.clone(), |ctx: Ctx<'_>, data: String| {
+            hex_decode(&data).map_err(|e| throw(&ctx, e))
+        })?,
+    )?;
+    Ok(hex)
+}
+
+fn uuid_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let uuid = Object::new(ctx.clone())?;
+    uuid.set("v4", Function::new(ctx.clone(), uuid_v4)?)?;
+    Ok(uuid)
+}
+
+fn time_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let time = Object::new(ctx.clone())?;
+    time.set("now", Function::new(ctx.clone(), time_now)?)?;
+    time.set("unix", Function::new(ctx.clone(), time_unix)?)?;
+    time.set("iso", Function::new(ctx.clone(), time_iso)?)?;
+    Ok(time)
+}
+
+fn json_helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Object<'js>> {
+    let json = Object::new(ctx.clone())?;
+    json.set(
+        "query",
+        Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, value: rquickjs::Value<'js>, path: String| {
+                let values =
+                    json_query(&to_json(&ctx, value)?, &path).map_err(|e| throw(&ctx, e))?;
+                from_json(&ctx, &Value::Array(values))
+            },
+        )?,
+    )?;
+    json.set(
+        "get",
+        Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, value: rquickjs::Value<'js>, path: String| {
+                let values =
+                    json_query(&to_json(&ctx, value)?, &path).map_err(|e| throw(&ctx, e))?;
+                match values.first() {
+                    Some(value) => from_json(&ctx, value),
+                    None => Ok(rquickjs::Value::new_undefined(ctx.clone())),
+                }
+            },
+        )?,
+    )?;
+    Ok(json)
+}
+
+/// Helper groups by name, shared by the module and the global object
+fn helpers<'js>(ctx: &Ctx<'js>) -> rquickjs::Result<Vec<(&'static str, Object<'js>)>> {
+    Ok(vec![
+        ("crypto", crypto_helpers(ctx)?),
+        ("base64", base64_helpers(ctx)?),
+        ("hex", hex_helpers(ctx)?),
+        ("uuid", uuid_helpers(ctx)?),
+        ("time", time_helpers(ctx)?),
+        ("json", json_helpers(ctx)?),
+    ])
+}
+
+/// Adds the helpers to an object, used for the global `sanchaar` object
+pub fn install<'js>(ctx: &Ctx<'js>, target: &Object<'js>) -> rquickjs::Result<()> {
+    for (name, helper) in helpers(ctx)? {
+        target.set(name, helper)?;
+    }
+    Ok(())
+}
+
+/// The `sanchaar` module, imported with `import { crypto } from "sanchaar"`
+pub struct SanchaarModule;
+
+impl ModuleDef for SanchaarModule {
+    fn declare<'js>(decl: &Declarations<'js>) -> rquickjs::Result<()> {
+        for name in HELPERS {
+            decl.declare(name)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, exports: &Exports<'js>) -> rquickjs::Result<()> {
+        for (name, helper) in helpers(ctx)? {
+            exports.export(name, helper)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(
+            sha256("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(md5("abc"), "900150983cd24fb0d6963f7d28e17f72");
+        assert!(sha512("abc").starts_with("ddaf35a193617aba"));
+    }
+
+    #[test]
+    fn test_hmac() {
+        assert_eq!(
+            hmac(
+                "key",
+                "The quick brown fox jumps over the lazy dog",
+                "SHA256"
+            )
+            .unwrap(),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+        assert_eq!(
+            hmac("key", "The quick brown fox jumps over the lazy dog", "md5").unwrap(),
+            "80070713463e7749b90c2dc24911e275"
+        );
+        assert!(hmac("key", "data", "sha3").is_err());
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(base64_encode("user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(base64_decode("dXNlcjpwYXNz").unwrap(), "user:pass");
+        assert!(base64_decode("not base64!").is_err());
+        assert_eq!(hex_encode("hi"), "6869");
+        assert_eq!(hex_decode("6869").unwrap(), "hi");
+        assert!(hex_decode("zz").is_err());
+    }
+
+    #[test]
+    fn test_json_query() {
+        let value = json!({ "items": [{ "id": 1 }, { "id": 2 }] });
+        assert_eq!(
+            json_query(&value, "$.items[*].id").unwrap(),
+            vec![json!(1), json!(2)]
+        );
+        assert!(json_query(&value, "$.missing").unwrap().is_empty());
+    }
+}
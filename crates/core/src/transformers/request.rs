@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Context;
 use jsonwebtoken::{EncodingKey, Header, encode};
@@ -6,17 +7,37 @@ use mime::{APPLICATION_JSON, TEXT_PLAIN, TEXT_XML};
 use mime_guess::{Mime, mime};
 use reqwest::multipart::Part;
 use reqwest::{RequestBuilder, Url};
-use reqwest::{header::CONTENT_TYPE, multipart::Form};
+use reqwest::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    multipart::Form,
+};
 use serde_json::Value;
 use tokio::fs::File;
 
+use crate::auth::AuthState;
+use crate::auth::digest::{DigestAuth, DigestCredentials};
+use crate::auth::oauth2::{self, TokenStore};
+use crate::auth::sigv4::{self, AwsCredentials};
+use crate::client::PreparedRequest;
 use crate::http::environment::EnvironmentChain;
 use crate::http::{
-    KeyFileList, KeyValList, KeyValue,
+    KeyFileList, KeyValList, KeyValue, VarMap,
     request::{Auth, AuthIn, Method, Request, RequestBody},
 };
+use crate::scripting::runner::PreRequestRunner;
+use crate::scripting::send::{RequestSender, ScriptContext};
 use crate::{APP_NAME, APP_VERSION};
 
+/// Marks the error of a pre-request script that stopped the request from being sent
+#[derive(Debug)]
+pub struct ScriptAborted;
+
+impl std::fmt::Display for ScriptAborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Pre-request script failed, request was not sent")
+    }
+}
+
 fn param_enabled(param: &KeyValue) -> bool {
     !param.disabled && !param.name.is_empty()
 }
@@ -86,17 +107,38 @@ pub async fn transform_request(
     client: reqwest::Client,
     mut req: Request,
     env: EnvironmentChain,
-) -> anyhow::Result<reqwest::Request> {
+    auth_state: &Arc<AuthState>,
+    context: &ScriptContext,
+) -> anyhow::Result<PreparedRequest> {
+    let mut variables = VarMap::new();
+
     // Execute pre-request script if present
     if let Some(pre_request_script) = &req.pre_request
         && !pre_request_script.trim().is_empty()
     {
-        use crate::scripting::runner::run_pre_request_script;
-        match run_pre_request_script(pre_request_script, &req, None) {
-            Ok((modified_request, _variables)) => {
+        let abort_on_error = req
+            .abort_on_script_error
+            .unwrap_or(context.abort_on_error());
+        let sender = RequestSender {
+            client: client.clone(),
+            env: env.clone(),
+            auth_state: Arc::clone(auth_state),
+            context: context.clone(),
+        };
+        let result = PreRequestRunner::new().and_then(|runner| {
+            let mut runner = runner.with_context(context).with_sender(sender);
+            let modified_request = runner.execute(pre_request_script, &req)?;
+            Ok((modified_request, runner.variables().all()))
+        });
+        match result {
+            Ok((modified_request, script_variables)) => {
                 req = modified_request;
+                variables = script_variables;
                 log::info!("Pre-request script executed successfully");
             }
+            Err(e) if abort_on_error => {
+                return Err(e.context(ScriptAborted));
+            }
             Err(e) => {
                 log::warn!("Pre-request script execution failed: {}", e);
                 // Continue with original request even if script fails
@@ -115,17 +157,37 @@ pub async fn transform_request(
         ..
     } = req;
 
-    let env = &env;
+    // Variables set by the script apply to this request too
+    let env = &if variables.is_empty() {
+        env
+    } else {
+        env.with_layer(Arc::new(variables.clone()))
+    };
 
     let url = process_url(url, env, path_params)?;
     let mut builder = client.request(req_method(method), url);
 
     builder = req_headers(builder, headers, env);
     builder = req_params(builder, query_params, env);
-    builder = req_auth(builder, auth, env);
+    let digest = digest_credentials(&auth, env).map(|credentials| DigestAuth {
+        credentials,
+        challenges: Arc::clone(&auth_state.digest),
+    });
+    let aws = aws_credentials(&auth, env);
+    builder = req_auth(builder, &client, auth, env, &auth_state.tokens).await?;
     builder = req_body(builder, body, env).await;
 
-    builder.build().context("Failed to build request")
+    let mut request = builder.build().context("Failed to build request")?;
+    if let Some(aws) = aws {
+        sigv4::sign_request(&mut request, &aws, chrono::Utc::now())
+            .context("Failed to sign request with AWS SigV4")?;
+    }
+
+    Ok(PreparedRequest {
+        request,
+        digest,
+        variables,
+    })
 }
 
 fn process_url(
@@ -247,9 +309,50 @@ async fn open_file(file: &PathBuf) -> (String, File) {
     (content_type, file)
 }
 
-fn req_auth(builder: RequestBuilder, auth: Auth, env: &EnvironmentChain) -> RequestBuilder {
+// Digest auth needs the server challenge, so credentials are applied while sending
+fn digest_credentials(auth: &Auth, env: &EnvironmentChain) -> Option<DigestCredentials> {
+    match auth {
+        Auth::Digest { username, password } => Some(DigestCredentials {
+            username: env.replace(username),
+            password: env.replace(password),
+        }),
+        _ => None,
+    }
+}
+
+// SigV4 covers the body and final headers, so signing happens once the request is built
+fn aws_credentials(auth: &Auth, env: &EnvironmentChain) -> Option<AwsCredentials> {
     match auth {
-        Auth::None => builder,
+        Auth::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        } => {
+            let session_token = env.replace(session_token);
+            Some(AwsCredentials {
+                access_key: env.replace(access_key),
+                secret_key: env.replace(secret_key),
+                session_token: (!session_token.is_empty()).then_some(session_token),
+                region: env.replace(region),
+                service: env.replace(service),
+            })
+        }
+        _ => None,
+    }
+}
+
+async fn req_auth(
+    builder: RequestBuilder,
+    client: &reqwest::Client,
+    auth: Auth,
+    env: &EnvironmentChain,
+    tokens: &TokenStore,
+) -> anyhow::Result<RequestBuilder> {
+    let builder = match auth {
+        // Inherited auth is resolved against the collection before transforming
+        Auth::None | Auth::Inherit => builder,
         Auth::Basic { username, password } => {
             let username = env.replace(&username);
             let password = env.replace(&password);
@@ -259,6 +362,7 @@ fn req_auth(builder: RequestBuilder, auth: Auth, env: &EnvironmentChain) -> Requ
             let token = env.replace(&token);
             builder.bearer_auth(token)
         }
+        Auth::Digest { .. } | Auth::AwsSigV4 { .. } => builder,
         Auth::APIKey { key, value, add_to } => {
             let key = env.replace(&key);
             let value = env.replace(&value);
@@ -292,5 +396,57 @@ fn req_auth(builder: RequestBuilder, auth: Auth, env: &EnvironmentChain) -> Requ
                 AuthIn::Query => builder.query(&[("token", token)]),
             }
         }
+        Auth::OAuth2(config) => {
+            let config = oauth2::replace_env(&config, env);
+            let token = oauth2::access_token(client, &config, tokens)
+                .await
+                .context("Failed to get OAuth2 access token")?;
+
+            builder.header(AUTHORIZATION, token.authorization())
+        }
+    };
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing_request(abort_on_script_error: Option<bool>) -> Request {
+        Request {
+            url: "https://api.example.com/users".to_string(),
+            pre_request: Some("throw new Error('signing failed');".to_string()),
+            abort_on_script_error,
+            ..Default::default()
+        }
+    }
+
+    async fn transform(req: Request) -> anyhow::Result<PreparedRequest> {
+        transform_request(
+            reqwest::Client::new(),
+            req,
+            EnvironmentChain::default(),
+            &Arc::default(),
+            &ScriptContext::new(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_script_error_sends_original_request() {
+        let prepared = transform(failing_request(None)).await.unwrap();
+        assert_eq!(
+            prepared.request.url().as_str(),
+            "https://api.example.com/users"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_script_error_aborts_request() {
+        let error = transform(failing_request(Some(true))).await.unwrap_err();
+
+        assert!(error.downcast_ref::<ScriptAborted>().is_some());
+        assert!(format!("{:#}", error).contains("signing failed"));
     }
 }
@@ -12,11 +12,13 @@ use crate::components::{
 use crate::ids::PERF_REQUEST_DROP_ZONE;
 use lib::http::collection::{Collection, Entry, FolderId, RequestId, RequestRef};
 use lib::http::{CollectionKey, CollectionRequest, request::Request};
+use lib::runner::RunTarget;
 
 use crate::commands::builders::{self, open_collection_cmd, open_request_cmd};
 use crate::state::popups::{Popup, PopupNameAction};
 use crate::state::tabs::collection_tab::CollectionTab;
 use crate::state::tabs::history_tab::HistoryTab;
+use crate::state::tabs::runner_tab::RunnerTab;
 use crate::state::{AppState, HttpTab, Tab};
 
 #[derive(Debug, Clone)]
@@ -92,7 +94,7 @@ impl CollectionTreeMsg {
                     .find(|(zone_id, _)| *zone_id == PERF_REQUEST_DROP_ZONE)
                     .inspect(|(_, _)| {
                         if let Some(Tab::Perf(tab)) = state.active_tab_mut() {
-                            tab.set_request(request);
+                            tab.add_step(request);
                         }
                     });
             }
@@ -170,6 +172,18 @@ fn handle_context_menu(
             }
             Task::none()
         }
+        MenuAction::Run(target) => {
+            let name = common.collections.get(key).and_then(|col| match target {
+                RunTarget::Collection => Some(col.name.clone()),
+                RunTarget::Folder(id) => col.folder(id).map(|folder| folder.name.clone()),
+                RunTarget::Request(id) => col.get_ref(id).map(|req| req.name.clone()),
+            });
+            if let Some(name) = name {
+                let tab = RunnerTab::new(format!("Run {}", name), key, target);
+                state.open_tab(Tab::Runner(Box::new(tab)));
+            }
+            Task::none()
+        }
     }
 }
 
@@ -372,6 +386,7 @@ pub enum MenuAction {
     RenameCollection(String),
     RemoveCollection,
     OpenCollection,
+    Run(RunTarget),
 }
 
 fn context_button_folder<'a>(
@@ -395,6 +410,10 @@ fn context_button_folder<'a>(
                 "New Folder",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewFolder(Some(folder_id))),
             ),
+            menu_item(
+                "Run",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::Run(RunTarget::Folder(folder_id))),
+            ),
             menu_item(
                 "Delete",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::DeleteFolder(folder_id)),
@@ -459,6 +478,13 @@ fn context_button_request(
                 "Copy Path",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::CopyPath(request_id)),
             ),
+            menu_item(
+                "Run",
+                CollectionTreeMsg::ContextMenu(
+                    col,
+                    MenuAction::Run(RunTarget::Request(request_id)),
+                ),
+            ),
             menu_item(
                 "Delete",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::DeleteRequest(request_id)),
@@ -492,6 +518,10 @@ fn context_button_collection<'a>(
                 "New Folder",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::NewFolder(None)),
             ),
+            menu_item(
+                "Run",
+                CollectionTreeMsg::ContextMenu(col, MenuAction::Run(RunTarget::Collection)),
+            ),
             menu_item(
                 "Close",
                 CollectionTreeMsg::ContextMenu(col, MenuAction::RemoveCollection),
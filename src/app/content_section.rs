@@ -14,6 +14,7 @@ use crate::components::{CardTab, TabBarAction, card_tab, card_tabs, colors, icon
 use crate::state::tabs::collection_tab::CollectionTab;
 use crate::state::tabs::history_tab::HistoryTab;
 use crate::state::tabs::perf_tab::PerfTab;
+use crate::state::tabs::runner_tab::RunnerTab;
 use crate::state::{AppState, HttpTab, Tab, TabKey};
 use lib::http::request::Method;
 
@@ -149,6 +150,7 @@ fn tabs_view<'a>(
             Tab::CookieStore(_) => cookie_tab(*key),
             Tab::History(tab) => history_tab(*key, tab),
             Tab::Perf(tab) => perf_tab(*key, tab),
+            Tab::Runner(tab) => runner_tab(*key, tab),
         })
         .collect();
 
@@ -179,6 +181,10 @@ fn perf_tab<'a>(key: TabKey, _tab: &'a PerfTab) -> CardTab<'a, TabKey> {
     card_tab(key, icon(icons::Speedometer), text("Performance"))
 }
 
+fn runner_tab<'a>(key: TabKey, tab: &'a RunnerTab) -> CardTab<'a, TabKey> {
+    card_tab(key, icon(icons::Play), text(&tab.name))
+}
+
 fn tab_card<'a>(key: TabKey, tab: &'a HttpTab) -> CardTab<'a, TabKey> {
     let dirty_flag = if tab.is_request_dirty() { "" } else { "" };
     card_tab(
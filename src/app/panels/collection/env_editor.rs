@@ -1,9 +1,9 @@
-use iced::widget::{Column, Row, button};
+use iced::widget::{Column, Row, button, container, text};
 use iced::{Element, Length, Task};
 
 use crate::app::panels::collection::env_table;
 use crate::components::Direction;
-use crate::components::{LineEditorMsg, scrollable_with};
+use crate::components::{LineEditorMsg, bold, scrollable_with};
 use lib::http::collection::Collection;
 use lib::http::environment::EnvironmentKey;
 
@@ -19,6 +19,8 @@ pub enum Message {
     AddVariable,
     UpdateVarValue(usize, EnvironmentKey, LineEditorMsg),
     UpdateVarName(usize, LineEditorMsg),
+    SaveRuntimeVars,
+    ClearRuntimeVars,
 }
 
 impl Message {
@@ -27,6 +29,7 @@ impl Message {
         let Some(Tab::Collection(tab)) = state.tabs.get_mut(&key) else {
             return Task::none();
         };
+        let collection_key = tab.collection_key;
         let data = &mut tab.env_editor;
 
         match self {
@@ -69,6 +72,22 @@ impl Message {
                     data.edited = true;
                 }
             }
+            Message::SaveRuntimeVars => {
+                if let Some(col) = state.common.collections.get(collection_key)
+                    && let Some(env_key) = col.active_environment
+                {
+                    let mut vars = col.runtime_vars.snapshot().iter().collect::<Vec<_>>();
+                    vars.sort();
+                    for (name, value) in vars {
+                        data.set_value(env_key, name, value);
+                    }
+                }
+            }
+            Message::ClearRuntimeVars => {
+                if let Some(col) = state.common.collections.get(collection_key) {
+                    col.runtime_vars.clear();
+                }
+            }
         }
         Task::none()
     }
@@ -95,9 +114,60 @@ pub fn view<'a>(tab: &'a CollectionTab, col: &'a Collection) -> Element<'a, Mess
     Column::new()
         .push(actions)
         .push(editor)
+        .push(runtime_vars_view(col))
         .spacing(8)
         .width(Length::Fill)
         .height(Length::Fill)
         .padding([8, 0])
         .into()
 }
+
+// Variables set by scripts while sending requests, kept until cleared or the app exits
+fn runtime_vars_view<'a>(col: &'a Collection) -> Element<'a, Message> {
+    let mut vars = col
+        .runtime_vars
+        .snapshot()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<Vec<_>>();
+    vars.sort();
+
+    let actions = Row::new()
+        .push(bold("Runtime Variables"))
+        .push(
+            button("Save to Active Environment")
+                .padding([2, 4])
+                .on_press_maybe(
+                    (!vars.is_empty() && col.active_environment.is_some())
+                        .then_some(Message::SaveRuntimeVars),
+                )
+                .style(button::secondary),
+        )
+        .push(
+            button("Clear")
+                .padding([2, 4])
+                .on_press_maybe((!vars.is_empty()).then_some(Message::ClearRuntimeVars))
+                .style(button::secondary),
+        )
+        .spacing(8);
+
+    let list: Element<'a, Message> = if vars.is_empty() {
+        text("No variables set by scripts").into()
+    } else {
+        Column::from_iter(vars.into_iter().map(|(name, value)| {
+            Row::new()
+                .push(text(name).width(Length::Fixed(150.)))
+                .push(text(value))
+                .spacing(8)
+                .into()
+        }))
+        .spacing(4)
+        .into()
+    };
+
+    Column::new()
+        .push(actions)
+        .push(container(list).style(container::bordered_box).padding(8))
+        .spacing(8)
+        .into()
+}
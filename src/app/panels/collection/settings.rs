@@ -1,11 +1,14 @@
 use lib::http::collection::Collection;
 use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use crate::app::panels::http::panes::request::auth_editor::{AuthEditorMsg, auth_view};
 use crate::components::scrollable;
-use crate::components::{KeyValList, KeyValUpdateMsg, key_value_editor, text_input};
+use crate::components::{
+    KeyValList, KeyValUpdateMsg, ProxyEditorMsg, key_value_editor, proxy_editor, text_input,
+};
 use iced::{
     Alignment, Element, Length, Task, padding,
-    widget::{Column, Row, pick_list, rule, space, toggler},
+    widget::{Column, Row, container, pick_list, rule, space, toggler},
 };
 
 use crate::{
@@ -18,11 +21,15 @@ use crate::{
 pub enum Message {
     UpdateDefaultEnv(String),
     UpdateHeaders(KeyValUpdateMsg),
+    UpdateAuth(AuthEditorMsg),
     UpdateVariables(KeyValUpdateMsg),
     SaveChanges,
     Saved,
     DisableSSL(bool),
     UpdateTimeout(String),
+    AbortOnScriptError(bool),
+    OverrideProxy(bool),
+    UpdateProxy(ProxyEditorMsg),
 }
 
 impl Message {
@@ -45,6 +52,10 @@ impl Message {
                 tab.edited = true;
                 tab.headers.update(msg);
             }
+            Message::UpdateAuth(msg) => {
+                tab.edited = true;
+                msg.update(&mut tab.auth);
+            }
             Message::UpdateVariables(_msg) => {
                 tab.edited = true;
                 // tab.variables.update(msg);
@@ -63,6 +74,18 @@ impl Message {
                     tab.timeout = Duration::from_millis(millis);
                 }
             }
+            Message::AbortOnScriptError(abort) => {
+                tab.edited = true;
+                tab.abort_on_script_error = abort;
+            }
+            Message::OverrideProxy(enabled) => {
+                tab.edited = true;
+                tab.proxy_override = enabled;
+            }
+            Message::UpdateProxy(msg) => {
+                tab.edited = true;
+                msg.update(&mut tab.proxy);
+            }
             Message::Saved => (),
         };
 
@@ -80,6 +103,23 @@ pub fn headers_view<'a>(vals: &'a KeyValList, vars: Arc<HashSet<String>>) -> Ele
         .into()
 }
 
+pub fn auth_section<'a>(
+    tab: &'a CollectionTab,
+    vars: Arc<HashSet<String>>,
+) -> Element<'a, Message> {
+    Column::new()
+        .push(rule::horizontal(2.))
+        .push("Collection Auth")
+        // The auth form fills its parent, so it needs a bounded height inside the scrollable
+        .push(
+            container(auth_view(&tab.auth, vars, false).map(Message::UpdateAuth))
+                .height(Length::Fixed(360.)),
+        )
+        .spacing(8)
+        .width(Length::Fill)
+        .into()
+}
+
 pub fn view<'a>(tab: &'a CollectionTab, col: &'a Collection) -> Element<'a, Message> {
     let environments = &tab.env_editor.environments;
     let envs: Vec<_> = environments.values().map(|env| env.name.clone()).collect();
@@ -124,13 +164,45 @@ pub fn view<'a>(tab: &'a CollectionTab, col: &'a Collection) -> Element<'a, Mess
         .width(Length::Fill)
         .align_y(Alignment::Center);
 
+    let abort_on_script_error = Row::new()
+        .push("Abort Request on Pre-request Script Error")
+        .push(space::horizontal().width(Length::FillPortion(4)))
+        .push(
+            toggler(tab.abort_on_script_error)
+                .on_toggle(Message::AbortOnScriptError)
+                .size(20),
+        )
+        .spacing(4)
+        .width(Length::Fill)
+        .align_y(Alignment::Center);
+
+    let proxy_override = Row::new()
+        .push("Override App Proxy")
+        .push(space::horizontal().width(Length::FillPortion(4)))
+        .push(
+            toggler(tab.proxy_override)
+                .on_toggle(Message::OverrideProxy)
+                .size(20),
+        )
+        .spacing(4)
+        .width(Length::Fill)
+        .align_y(Alignment::Center);
+
+    let proxy = tab
+        .proxy_override
+        .then(|| proxy_editor(&tab.proxy, true).map(Message::UpdateProxy));
+
     scrollable(
         Column::new()
             .push(default_env)
             .push(disable_ssl)
             .push(timeout)
+            .push(abort_on_script_error)
+            .push(proxy_override)
+            .push(proxy)
             .push(space::horizontal().width(Length::Fixed(8.)))
-            .push(headers_view(&tab.headers, header_vars))
+            .push(headers_view(&tab.headers, Arc::clone(&header_vars)))
+            .push(auth_section(tab, header_vars))
             .spacing(16)
             .width(Length::Fill)
             .height(Length::Shrink)
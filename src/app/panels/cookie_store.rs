@@ -1,12 +1,19 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::commands::dialog::{create_file_dialog, open_file_dialog_with_filter};
 use crate::components::{
-    LineEditorMsg, bold, icon, icon_button, icons, line_editor, scrollable, tooltip,
+    LineEditorMsg, bold, icon, icon_button, icons, line_editor, scrollable, text_input, tooltip,
 };
 use cookie_store::Cookie;
 use iced::widget::text::Wrapping;
-use iced::widget::{button, column, container, row, table, text};
+use iced::widget::{button, column, container, pick_list, row, space, table, text, toggler};
 use iced::{Alignment, Element, Length, Task};
+use lib::cookies::{CookieFields, SameSite};
+use lib::http::CollectionKey;
+use rfd::FileHandle;
 
-use crate::state::tabs::cookies_tab::CookiesTab;
+use crate::state::tabs::cookies_tab::{CookieEditor, CookiesTab};
 use crate::state::{AppState, Tab};
 
 #[derive(Debug, Clone)]
@@ -15,14 +22,111 @@ pub enum CookieTabMsg {
     ClearAllCookies,
     SearchChanged(LineEditorMsg),
     ClearSearch,
+    SelectJar(Option<CollectionKey>),
+    Import,
+    ImportFile(Option<Arc<FileHandle>>),
+    ImportLoaded(PathBuf, Result<String, String>),
+    Export,
+    ExportFile(Option<Arc<FileHandle>>),
+    Exported(PathBuf, Result<(), String>),
+    AddCookie,
+    EditCookie(CookieFields),
+    EditorChanged(CookieEditorMsg),
+    SaveCookie,
+    CancelEdit,
+}
+
+#[derive(Debug, Clone)]
+pub enum CookieEditorMsg {
+    Name(String),
+    Value(String),
+    Domain(String),
+    Path(String),
+    Expires(String),
+    IncludeSubdomains(bool),
+    Secure(bool),
+    HttpOnly(bool),
+    SameSite(Option<SameSite>),
+}
+
+impl CookieEditorMsg {
+    fn update(self, editor: &mut CookieEditor) {
+        let fields = &mut editor.fields;
+        match self {
+            CookieEditorMsg::Name(name) => fields.name = name,
+            CookieEditorMsg::Value(value) => fields.value = value,
+            CookieEditorMsg::Domain(domain) => fields.domain = domain,
+            CookieEditorMsg::Path(path) => fields.path = path,
+            CookieEditorMsg::Expires(expires) => editor.expires = expires,
+            CookieEditorMsg::IncludeSubdomains(include) => fields.include_subdomains = include,
+            CookieEditorMsg::Secure(secure) => fields.secure = secure,
+            CookieEditorMsg::HttpOnly(http_only) => fields.http_only = http_only,
+            CookieEditorMsg::SameSite(same_site) => fields.same_site = same_site,
+        }
+        editor.error = None;
+    }
 }
 
 impl CookieTabMsg {
     pub fn update(self, state: &mut AppState) -> Task<Self> {
-        let Some(Tab::CookieStore(tab)) = state.active_tab_mut() else {
+        let Some(Tab::CookieStore(tab)) = state.tabs.get_mut(&state.active_tab) else {
             return Task::none();
         };
         match self {
+            CookieTabMsg::SelectJar(jar) => {
+                tab.select_jar(&state.common, jar);
+                Task::none()
+            }
+            CookieTabMsg::Import => {
+                open_file_dialog_with_filter("Import Cookies", &["txt", "json"])
+                    .map(CookieTabMsg::ImportFile)
+            }
+            CookieTabMsg::ImportFile(handle) => {
+                let Some(handle) = handle else {
+                    return Task::none();
+                };
+                let path = handle.path().to_path_buf();
+                Task::perform(tokio::fs::read_to_string(path.clone()), move |result| {
+                    CookieTabMsg::ImportLoaded(path, result.map_err(|e| e.to_string()))
+                })
+            }
+            CookieTabMsg::ImportLoaded(path, content) => {
+                let result = content.and_then(|content| {
+                    tab.import(&path, &content).map_err(|e| format!("{:#}", e))
+                });
+                tab.status = Some(match result {
+                    Ok(count) => format!("Imported {} cookies", count),
+                    Err(e) => format!("Import failed: {}", e),
+                });
+                Task::none()
+            }
+            CookieTabMsg::Export => {
+                create_file_dialog("Export Cookies").map(CookieTabMsg::ExportFile)
+            }
+            CookieTabMsg::ExportFile(handle) => {
+                let Some(handle) = handle else {
+                    return Task::none();
+                };
+                let path = handle.path().to_path_buf();
+                match tab.export(&path) {
+                    Ok(content) => {
+                        Task::perform(tokio::fs::write(path.clone(), content), move |result| {
+                            CookieTabMsg::Exported(path, result.map_err(|e| e.to_string()))
+                        })
+                    }
+                    Err(e) => {
+                        tab.status = Some(format!("Export failed: {:#}", e));
+                        Task::none()
+                    }
+                }
+            }
+            CookieTabMsg::Exported(path, result) => {
+                tab.status = Some(match result {
+                    Ok(()) => format!("Exported cookies to {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+                Task::none()
+            }
             CookieTabMsg::DeleteCookie(name, domain, path) => {
                 tab.delete_cookie(&name, &domain, &path);
                 Task::none()
@@ -41,11 +145,210 @@ impl CookieTabMsg {
                 tab.clear_search_query();
                 Task::none()
             }
+            CookieTabMsg::AddCookie => {
+                tab.editor = Some(CookieEditor::new(None));
+                Task::none()
+            }
+            CookieTabMsg::EditCookie(fields) => {
+                tab.editor = Some(CookieEditor::new(Some(fields)));
+                Task::none()
+            }
+            CookieTabMsg::EditorChanged(msg) => {
+                if let Some(editor) = tab.editor.as_mut() {
+                    msg.update(editor);
+                }
+                Task::none()
+            }
+            CookieTabMsg::SaveCookie => {
+                tab.save_editor();
+                Task::none()
+            }
+            CookieTabMsg::CancelEdit => {
+                tab.editor = None;
+                Task::none()
+            }
         }
     }
 }
 
-pub fn view<'a>(tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
+/// Cookie jar of a collection, or of requests not saved in one
+#[derive(Debug, Clone, PartialEq)]
+struct Jar {
+    key: Option<CollectionKey>,
+    name: String,
+}
+
+impl std::fmt::Display for Jar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn jar_row<'a>(state: &'a AppState, tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
+    let jars = state
+        .common
+        .collections
+        .iter()
+        .map(|(key, col)| Jar {
+            key: Some(key),
+            name: col.name.clone(),
+        })
+        .chain([Jar {
+            key: None,
+            name: "Unsaved requests".to_string(),
+        }])
+        .collect::<Vec<_>>();
+    let selected = jars.iter().find(|jar| jar.key == tab.jar).cloned();
+
+    let jar_picker = pick_list(selected, jars, |jar| jar.to_string())
+        .on_select(|jar| CookieTabMsg::SelectJar(jar.key))
+        .padding([2, 6]);
+
+    let status = tab.status.as_deref().map(text);
+
+    row![
+        text("Cookie Jar"),
+        jar_picker,
+        space::horizontal(),
+        status,
+        tooltip(
+            "Import cookies.txt or JSON",
+            icon_button(icons::Import, Some(20), Some(4))
+                .style(button::text)
+                .on_press(CookieTabMsg::Import),
+        ),
+        tooltip(
+            "Export as cookies.txt, or JSON for .json files",
+            icon_button(icons::Download, Some(20), Some(4))
+                .style(button::text)
+                .on_press(CookieTabMsg::Export),
+        ),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(8)
+    .into()
+}
+
+const SAME_SITE_OPTIONS: [&str; 4] = ["Not set", "Strict", "Lax", "None"];
+
+fn editor_field<'a>(
+    label: &'a str,
+    input: impl Into<Element<'a, CookieTabMsg>>,
+) -> Element<'a, CookieTabMsg> {
+    column![text(label).size(14), input.into()]
+        .spacing(2)
+        .width(Length::Fill)
+        .into()
+}
+
+fn editor_toggle<'a>(
+    label: &'a str,
+    value: bool,
+    on_toggle: impl Fn(bool) -> CookieEditorMsg + 'a,
+) -> Element<'a, CookieTabMsg> {
+    row![
+        text(label),
+        toggler(value)
+            .on_toggle(move |value| CookieTabMsg::EditorChanged(on_toggle(value)))
+            .size(20),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(4)
+    .into()
+}
+
+fn editor_view(editor: &CookieEditor) -> Element<'_, CookieTabMsg> {
+    let fields = &editor.fields;
+    let edit = |msg: fn(String) -> CookieEditorMsg| {
+        move |value: String| CookieTabMsg::EditorChanged(msg(value))
+    };
+
+    let name_row = row![
+        editor_field(
+            "Name",
+            text_input("Name", &fields.name, edit(CookieEditorMsg::Name)).padding([2, 4]),
+        ),
+        editor_field(
+            "Value",
+            text_input("Value", &fields.value, edit(CookieEditorMsg::Value)).padding([2, 4]),
+        ),
+    ]
+    .spacing(8);
+
+    let location_row = row![
+        editor_field(
+            "Domain",
+            text_input("example.com", &fields.domain, edit(CookieEditorMsg::Domain))
+                .padding([2, 4]),
+        ),
+        editor_field(
+            "Path",
+            text_input("/", &fields.path, edit(CookieEditorMsg::Path)).padding([2, 4]),
+        ),
+        editor_field(
+            "Expires (UTC)",
+            text_input(
+                "YYYY-MM-DD HH:MM:SS, empty for session",
+                &editor.expires,
+                edit(CookieEditorMsg::Expires),
+            )
+            .padding([2, 4]),
+        ),
+    ]
+    .spacing(8);
+
+    let same_site = fields
+        .same_site
+        .map_or(SAME_SITE_OPTIONS[0], |s| s.as_str());
+    let same_site = pick_list(Some(same_site), SAME_SITE_OPTIONS, |option| {
+        option.to_string()
+    })
+    .on_select(|option| {
+        let same_site = SameSite::ALL.into_iter().find(|s| s.as_str() == option);
+        CookieTabMsg::EditorChanged(CookieEditorMsg::SameSite(same_site))
+    })
+    .padding([2, 6]);
+
+    let title = if editor.original.is_some() {
+        "Edit Cookie"
+    } else {
+        "Add Cookie"
+    };
+    let error = editor.error.as_deref().map(|e| text(e).style(text::danger));
+
+    let actions_row = row![
+        editor_toggle(
+            "Include Subdomains",
+            fields.include_subdomains,
+            CookieEditorMsg::IncludeSubdomains,
+        ),
+        editor_toggle("Secure", fields.secure, CookieEditorMsg::Secure),
+        editor_toggle("HttpOnly", fields.http_only, CookieEditorMsg::HttpOnly),
+        row![text("SameSite"), same_site]
+            .align_y(Alignment::Center)
+            .spacing(4),
+        space::horizontal(),
+        button("Cancel")
+            .style(button::subtle)
+            .on_press(CookieTabMsg::CancelEdit),
+        button("Save")
+            .style(button::primary)
+            .on_press(CookieTabMsg::SaveCookie),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(12);
+
+    container(
+        column![bold(title), name_row, location_row, actions_row]
+            .push(error)
+            .spacing(8),
+    )
+    .padding(8)
+    .style(container::bordered_box)
+    .into()
+}
+
+pub fn view<'a>(state: &'a AppState, tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
     let is_empty = tab.search_query_text.is_empty();
     let cookies = tab.cookies();
 
@@ -65,7 +368,14 @@ pub fn view<'a>(tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
 
     let clear_all_button = tooltip("Remove all cookies", clear_all_button);
 
-    let search_row = row![search_input, clear_all_button]
+    let add_button = tooltip(
+        "Add cookie",
+        icon_button(icons::Plus, Some(24), Some(8))
+            .style(button::secondary)
+            .on_press(CookieTabMsg::AddCookie),
+    );
+
+    let search_row = row![search_input, add_button, clear_all_button]
         .align_y(Alignment::Center)
         .spacing(8);
 
@@ -100,17 +410,27 @@ pub fn view<'a>(tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
         })
         .align_y(Alignment::Center),
         table::column(text(""), |cookie: Cookie<'static>| {
+            let fields = CookieFields::from_cookie(&cookie);
             let name = cookie.name().to_string();
             let domain = cookie.domain().unwrap_or_default().to_string();
             let path = cookie.path().unwrap_or_default().to_string();
 
-            tooltip(
-                "Delete cookie",
-                button(icon(icons::Delete).size(20))
-                    .padding([0, 4])
-                    .style(button::text)
-                    .on_press(CookieTabMsg::DeleteCookie(name, domain, path)),
-            )
+            row![
+                tooltip(
+                    "Edit cookie",
+                    button(icon(icons::Pencil).size(20))
+                        .padding([0, 4])
+                        .style(button::text)
+                        .on_press(CookieTabMsg::EditCookie(fields)),
+                ),
+                tooltip(
+                    "Delete cookie",
+                    button(icon(icons::Delete).size(20))
+                        .padding([0, 4])
+                        .style(button::text)
+                        .on_press(CookieTabMsg::DeleteCookie(name, domain, path)),
+                ),
+            ]
         })
         .align_x(Alignment::Center)
         .align_y(Alignment::Center),
@@ -131,7 +451,11 @@ pub fn view<'a>(tab: &'a CookiesTab) -> Element<'a, CookieTabMsg> {
         .into()
     };
 
-    column![search_row, content]
+    let editor = tab.editor.as_ref().map(editor_view);
+
+    column![jar_row(state, tab), search_row]
+        .push(editor)
+        .push(content)
         .spacing(8)
         .width(Length::Fill)
         .height(Length::Fill)
@@ -9,11 +9,33 @@ use crate::{
 use iced::{
     Element,
     Length::{self},
-    widget::{Column, Row, center, container, pick_list, text},
+    widget::{Column, Row, center, container, pick_list, text, toggler},
 };
 use strum::VariantNames;
 
-use crate::state::request::{RawAuthType, RequestPane};
+use crate::state::request::{OAuth2GrantType, RawAuthType};
+
+#[derive(Debug, Clone, Copy)]
+pub enum OAuth2Field {
+    TokenUrl,
+    AuthUrl,
+    RedirectUrl,
+    ClientId,
+    ClientSecret,
+    Scope,
+    Username,
+    Password,
+    RefreshToken,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AwsSigV4Field {
+    AccessKey,
+    SecretKey,
+    SessionToken,
+    Region,
+    Service,
+}
 
 #[derive(Debug, Clone)]
 pub enum AuthEditorMsg {
@@ -21,6 +43,8 @@ pub enum AuthEditorMsg {
     BearerToken(LineEditorMsg),
     BasicUsername(LineEditorMsg),
     BasicPassword(LineEditorMsg),
+    DigestUsername(LineEditorMsg),
+    DigestPassword(LineEditorMsg),
     APIKeyName(LineEditorMsg),
     APIKeyValue(LineEditorMsg),
     APIKeyAddTo(&'static str),
@@ -28,67 +52,159 @@ pub enum AuthEditorMsg {
     JWTBearerSecret(LineEditorMsg),
     JWTBearerPayload(CodeEditorMsg),
     JWTBearerAddTo(&'static str),
+    OAuth2Grant(&'static str),
+    OAuth2Pkce(bool),
+    OAuth2Field(OAuth2Field, LineEditorMsg),
+    AwsSigV4Field(AwsSigV4Field, LineEditorMsg),
 }
 
 impl AuthEditorMsg {
-    pub(crate) fn update(self, request: &mut RequestPane) {
+    pub(crate) fn update(self, auth: &mut RawAuthType) {
         match self {
-            AuthEditorMsg::ChangeAuthType(auth) => request.change_auth_type(auth),
+            AuthEditorMsg::ChangeAuthType(update) => {
+                *auth = RawAuthType::from_str(update).unwrap_or(RawAuthType::None);
+            }
             AuthEditorMsg::BearerToken(action) => {
-                if let RawAuthType::Bearer { token } = &mut request.auth {
+                if let RawAuthType::Bearer { token } = auth {
                     action.update(token);
                 }
             }
             AuthEditorMsg::BasicUsername(action) => {
-                if let RawAuthType::Basic { username, .. } = &mut request.auth {
+                if let RawAuthType::Basic { username, .. } = auth {
                     action.update(username);
                 }
             }
             AuthEditorMsg::BasicPassword(action) => {
-                if let RawAuthType::Basic { password, .. } = &mut request.auth {
+                if let RawAuthType::Basic { password, .. } = auth {
+                    action.update(password);
+                }
+            }
+            AuthEditorMsg::DigestUsername(action) => {
+                if let RawAuthType::Digest { username, .. } = auth {
+                    action.update(username);
+                }
+            }
+            AuthEditorMsg::DigestPassword(action) => {
+                if let RawAuthType::Digest { password, .. } = auth {
                     action.update(password);
                 }
             }
             AuthEditorMsg::APIKeyName(line_editor_msg) => {
-                if let RawAuthType::APIKey { key, .. } = &mut request.auth {
+                if let RawAuthType::APIKey { key, .. } = auth {
                     line_editor_msg.update(key);
                 }
             }
             AuthEditorMsg::APIKeyValue(line_editor_msg) => {
-                if let RawAuthType::APIKey { value, .. } = &mut request.auth {
+                if let RawAuthType::APIKey { value, .. } = auth {
                     line_editor_msg.update(value);
                 }
             }
             AuthEditorMsg::APIKeyAddTo(update) => {
-                if let RawAuthType::APIKey { add_to, .. } = &mut request.auth {
+                if let RawAuthType::APIKey { add_to, .. } = auth {
                     *add_to = AuthIn::from_str(update).unwrap_or(AuthIn::Header);
                 }
             }
             AuthEditorMsg::JWTBearerAlgorithm(algo) => {
-                if let RawAuthType::JWTBearer { algorithm, .. } = &mut request.auth {
+                if let RawAuthType::JWTBearer { algorithm, .. } = auth {
                     use crate::state::request::JwtAlgo;
                     *algorithm = JwtAlgo::from_str(algo).unwrap_or(JwtAlgo::HS256);
                 }
             }
             AuthEditorMsg::JWTBearerSecret(line_editor_msg) => {
-                if let RawAuthType::JWTBearer { secret, .. } = &mut request.auth {
+                if let RawAuthType::JWTBearer { secret, .. } = auth {
                     line_editor_msg.update(secret);
                 }
             }
             AuthEditorMsg::JWTBearerPayload(line_editor_msg) => {
-                if let RawAuthType::JWTBearer { payload, .. } = &mut request.auth {
+                if let RawAuthType::JWTBearer { payload, .. } = auth {
                     line_editor_msg.update(payload);
                 }
             }
             AuthEditorMsg::JWTBearerAddTo(update) => {
-                if let RawAuthType::JWTBearer { add_to, .. } = &mut request.auth {
+                if let RawAuthType::JWTBearer { add_to, .. } = auth {
                     *add_to = AuthIn::from_str(update).unwrap_or(AuthIn::Header);
                 }
             }
+            AuthEditorMsg::OAuth2Grant(update) => {
+                if let RawAuthType::OAuth2 { grant, .. } = auth {
+                    *grant = OAuth2GrantType::from_str(update).unwrap_or_default();
+                }
+            }
+            AuthEditorMsg::OAuth2Pkce(enabled) => {
+                if let RawAuthType::OAuth2 { use_pkce, .. } = auth {
+                    *use_pkce = enabled;
+                }
+            }
+            AuthEditorMsg::OAuth2Field(field, line_editor_msg) => {
+                if let Some(content) = oauth2_field_mut(auth, field) {
+                    line_editor_msg.update(content);
+                }
+            }
+            AuthEditorMsg::AwsSigV4Field(field, line_editor_msg) => {
+                if let Some(content) = aws_sigv4_field_mut(auth, field) {
+                    line_editor_msg.update(content);
+                }
+            }
         }
     }
 }
 
+fn oauth2_field_mut(auth: &mut RawAuthType, field: OAuth2Field) -> Option<&mut editor::Content> {
+    let RawAuthType::OAuth2 {
+        token_url,
+        auth_url,
+        redirect_url,
+        client_id,
+        client_secret,
+        scope,
+        username,
+        password,
+        refresh_token,
+        ..
+    } = auth
+    else {
+        return None;
+    };
+
+    let content = match field {
+        OAuth2Field::TokenUrl => token_url,
+        OAuth2Field::AuthUrl => auth_url,
+        OAuth2Field::RedirectUrl => redirect_url,
+        OAuth2Field::ClientId => client_id,
+        OAuth2Field::ClientSecret => client_secret,
+        OAuth2Field::Scope => scope,
+        OAuth2Field::Username => username,
+        OAuth2Field::Password => password,
+        OAuth2Field::RefreshToken => refresh_token,
+    };
+    Some(content)
+}
+
+fn aws_sigv4_field_mut(
+    auth: &mut RawAuthType,
+    field: AwsSigV4Field,
+) -> Option<&mut editor::Content> {
+    let RawAuthType::AwsSigV4 {
+        access_key,
+        secret_key,
+        session_token,
+        region,
+        service,
+    } = auth
+    else {
+        return None;
+    };
+
+    let content = match field {
+        AwsSigV4Field::AccessKey => access_key,
+        AwsSigV4Field::SecretKey => secret_key,
+        AwsSigV4Field::SessionToken => session_token,
+        AwsSigV4Field::Region => region,
+        AwsSigV4Field::Service => service,
+    };
+    Some(content)
+}
+
 fn field_row<'a>(
     label: &'static str,
     field: impl Into<Element<'a, AuthEditorMsg>>,
@@ -99,16 +215,24 @@ fn field_row<'a>(
         .into()
 }
 
-pub fn auth_view(request: &RequestPane, vars: Arc<HashSet<String>>) -> Element<AuthEditorMsg> {
-    let auth = &request.auth;
+/// Auth editor shared by requests and collections, `inherit` offers the Inherit option
+pub fn auth_view(
+    auth: &RawAuthType,
+    vars: Arc<HashSet<String>>,
+    inherit: bool,
+) -> Element<AuthEditorMsg> {
+    let variants = RawAuthType::VARIANTS
+        .iter()
+        .copied()
+        .filter(|variant| inherit || *variant != RawAuthType::Inherit.as_str())
+        .collect::<Vec<_>>();
+
     let header = Row::new()
         .push(text("Auth Type"))
         .push(
-            pick_list(Some(auth.as_str()), RawAuthType::VARIANTS, |auth| {
-                auth.to_string()
-            })
-            .on_select(AuthEditorMsg::ChangeAuthType)
-            .padding([2, 6]),
+            pick_list(Some(auth.as_str()), variants, |auth| auth.to_string())
+                .on_select(AuthEditorMsg::ChangeAuthType)
+                .padding([2, 6]),
         )
         .spacing(16)
         .height(Length::Shrink)
@@ -141,6 +265,22 @@ fn auth_body(auth: &RawAuthType, vars: Arc<HashSet<String>>) -> Element<AuthEdit
             .height(Length::Fill)
             .spacing(4)
             .into(),
+        RawAuthType::Digest { username, password } => Column::new()
+            .push(field_row(
+                "Username",
+                line_editor(username)
+                    .vars(Arc::clone(&vars))
+                    .map(AuthEditorMsg::DigestUsername),
+            ))
+            .push(field_row(
+                "Password",
+                line_editor(password)
+                    .vars(Arc::clone(&vars))
+                    .map(AuthEditorMsg::DigestPassword),
+            ))
+            .height(Length::Fill)
+            .spacing(4)
+            .into(),
         RawAuthType::Bearer { token } => Column::new()
             .push(field_row(
                 "Token",
@@ -151,6 +291,14 @@ fn auth_body(auth: &RawAuthType, vars: Arc<HashSet<String>>) -> Element<AuthEdit
             .height(Length::Fill)
             .spacing(4)
             .into(),
+        RawAuthType::Inherit => Column::new()
+            .push(text(
+                "Auth is inherited from the parent folder or collection",
+            ))
+            .align_x(iced::Alignment::Center)
+            .height(Length::Shrink)
+            .width(Length::Shrink)
+            .into(),
         RawAuthType::None => {
             let empty_icon = container(icon(icons::FileCancel).size(80.0)).padding(10);
             Column::new()
@@ -168,6 +316,106 @@ fn auth_body(auth: &RawAuthType, vars: Arc<HashSet<String>>) -> Element<AuthEdit
             payload,
             add_to,
         } => jwt_bearer_view(*algorithm, secret, payload, *add_to),
+        RawAuthType::OAuth2 {
+            grant,
+            token_url,
+            auth_url,
+            redirect_url,
+            client_id,
+            client_secret,
+            scope,
+            username,
+            password,
+            refresh_token,
+            use_pkce,
+        } => {
+            let field = |label, content, field| {
+                field_row(
+                    label,
+                    line_editor(content)
+                        .vars(Arc::clone(&vars))
+                        .map(move |msg| AuthEditorMsg::OAuth2Field(field, msg)),
+                )
+            };
+
+            let mut column = Column::new().push(field_row(
+                "Grant Type",
+                pick_list(Some(grant.as_str()), OAuth2GrantType::VARIANTS, |grant| {
+                    grant.to_string()
+                })
+                .on_select(AuthEditorMsg::OAuth2Grant),
+            ));
+
+            if *grant == OAuth2GrantType::AuthorizationCode {
+                column = column
+                    .push(field("Auth URL", auth_url, OAuth2Field::AuthUrl))
+                    .push(field(
+                        "Redirect URL",
+                        redirect_url,
+                        OAuth2Field::RedirectUrl,
+                    ));
+            }
+
+            column = column
+                .push(field("Token URL", token_url, OAuth2Field::TokenUrl))
+                .push(field("Client ID", client_id, OAuth2Field::ClientId))
+                .push(field(
+                    "Client Secret",
+                    client_secret,
+                    OAuth2Field::ClientSecret,
+                ))
+                .push(field("Scope", scope, OAuth2Field::Scope));
+
+            column = match grant {
+                OAuth2GrantType::Password => column
+                    .push(field("Username", username, OAuth2Field::Username))
+                    .push(field("Password", password, OAuth2Field::Password)),
+                OAuth2GrantType::RefreshToken => column.push(field(
+                    "Refresh Token",
+                    refresh_token,
+                    OAuth2Field::RefreshToken,
+                )),
+                OAuth2GrantType::AuthorizationCode => column.push(field_row(
+                    "Use PKCE",
+                    toggler(*use_pkce)
+                        .on_toggle(AuthEditorMsg::OAuth2Pkce)
+                        .size(20),
+                )),
+                OAuth2GrantType::ClientCredentials => column,
+            };
+
+            column.height(Length::Fill).spacing(4).into()
+        }
+        RawAuthType::AwsSigV4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        } => {
+            let field = |label, content, field| {
+                field_row(
+                    label,
+                    line_editor(content)
+                        .vars(Arc::clone(&vars))
+                        .map(move |msg| AuthEditorMsg::AwsSigV4Field(field, msg)),
+                )
+            };
+
+            Column::new()
+                .push(field("Access Key", access_key, AwsSigV4Field::AccessKey))
+                .push(field("Secret Key", secret_key, AwsSigV4Field::SecretKey))
+                .push(field(
+                    "Session Token",
+                    session_token,
+                    AwsSigV4Field::SessionToken,
+                ))
+                .push(field("Region", region, AwsSigV4Field::Region))
+                .push(field("Service", service, AwsSigV4Field::Service))
+                .height(Length::Fill)
+                .spacing(4)
+                .into()
+        }
     }
 }
 
@@ -19,7 +19,7 @@ use iced::{Length, Task, widget::text};
 use self::auth_editor::{AuthEditorMsg, auth_view};
 use self::body_view::body_tab;
 
-mod auth_editor;
+pub(crate) mod auth_editor;
 mod body_editor;
 mod body_view;
 mod bulk_edit;
@@ -40,6 +40,7 @@ pub enum RequestPaneMsg {
     ChangePreRequestScript(Option<String>),
     ChangePostRequestScript(Option<String>),
     ChangeScriptType(ReqScriptType),
+    ChangeAbortOnScriptError(&'static str),
     LoadScriptContent(String),
     ScriptEditorAction(CodeEditorMsg),
     SaveScript,
@@ -175,6 +176,9 @@ impl RequestPaneMsg {
                     request.script_edited = false;
                 }
             }
+            Self::ChangeAbortOnScriptError(option) => {
+                request.abort_on_script_error = abort_on_script_error(option);
+            }
             Self::LoadScriptContent(content) => {
                 use crate::components::editor;
                 request.script_content = Some(editor::Content::with_text(&content));
@@ -253,7 +257,7 @@ impl RequestPaneMsg {
             }
             Self::ChangeBodyType(ct) => request.change_body_type(ct),
             Self::FormatBody => request.format_body(),
-            Self::AuthEditorAction(action) => action.update(request),
+            Self::AuthEditorAction(action) => action.update(&mut request.auth),
             Self::OpenFilePicker => {
                 return open_file_dialog("Select File").map(|path| {
                     RequestPaneMsg::ChangeBodyFile(path.map(|p| p.path().to_path_buf()))
@@ -321,6 +325,24 @@ fn headers_view(
         .into()
 }
 
+const SCRIPT_ERROR_OPTIONS: [&str; 3] = ["Collection default", "Abort request", "Send anyway"];
+
+fn abort_on_script_error(option: &str) -> Option<bool> {
+    match option {
+        "Abort request" => Some(true),
+        "Send anyway" => Some(false),
+        _ => None,
+    }
+}
+
+fn script_error_option(abort: Option<bool>) -> &'static str {
+    match abort {
+        Some(true) => SCRIPT_ERROR_OPTIONS[1],
+        Some(false) => SCRIPT_ERROR_OPTIONS[2],
+        None => SCRIPT_ERROR_OPTIONS[0],
+    }
+}
+
 fn script_view<'a>(
     col: Option<&'a Collection>,
     tab: &'a HttpTab,
@@ -328,7 +350,7 @@ fn script_view<'a>(
     use crate::components::{
         button_tab, script_editor, script_placeholder, script_selector, vertical_button_tabs,
     };
-    use iced::widget::container;
+    use iced::widget::{container, pick_list};
 
     let Some(col) = col else {
         return Column::new().into();
@@ -357,6 +379,23 @@ fn script_view<'a>(
         RequestPaneMsg::ChangeScriptType,
     );
 
+    // Whether a failing pre-request script stops the request from being sent
+    let on_error = (script_type == ReqScriptType::PreRequest).then(|| {
+        Column::new()
+            .push(text("On script error").size(12))
+            .push(
+                pick_list(
+                    Some(script_error_option(request.abort_on_script_error)),
+                    SCRIPT_ERROR_OPTIONS,
+                    |option| option.to_string(),
+                )
+                .on_select(RequestPaneMsg::ChangeAbortOnScriptError)
+                .text_size(12)
+                .padding([2, 6]),
+            )
+            .spacing(4)
+    });
+
     // Script selector with remove/save buttons (no create)
     let selector = script_selector(
         scripts,
@@ -398,10 +437,15 @@ fn script_view<'a>(
 
     Row::new()
         .push(
-            container(script_type_tabs)
-                .padding(8)
-                .width(Length::Fixed(140.0))
-                .height(Length::Fill),
+            container(
+                Column::new()
+                    .push(script_type_tabs)
+                    .push(on_error)
+                    .spacing(16),
+            )
+            .padding(8)
+            .width(Length::Fixed(140.0))
+            .height(Length::Fill),
         )
         .push(content_view)
         .width(Length::Fill)
@@ -421,7 +465,7 @@ pub fn view<'a>(
         ReqTabId::Params => params_view(request, Arc::clone(&vars)),
         ReqTabId::Headers => headers_view(request, Arc::clone(&vars)),
         ReqTabId::Auth => {
-            auth_view(request, Arc::clone(&vars)).map(RequestPaneMsg::AuthEditorAction)
+            auth_view(&request.auth, Arc::clone(&vars), true).map(RequestPaneMsg::AuthEditorAction)
         }
         ReqTabId::Body => body_tab(&request.body, vars),
         ReqTabId::PreRequest => script_view(col, tab),
@@ -1,15 +1,17 @@
+use lib::assertions::runner::MatcherResult;
 use lib::client;
+use lib::scripting::console::{ConsoleEntry, ConsoleLevel};
 use lib::utils::fmt_duration;
 use std::sync::Arc;
 
 use humansize::{BINARY, format_size};
 use iced::widget::{Column, Row, button, container, space, text};
-use iced::{Alignment, Color, Element, Length, Task, Theme, clipboard};
+use iced::{Alignment, Color, Element, Font, Length, Task, Theme, clipboard, padding};
 
 use crate::components::editor::Content;
 use crate::components::{
     CodeEditorMsg, ContentType, LineEditorMsg, button_tab, button_tabs, code_editor, colors, icon,
-    icons, key_value_viewer, line_editor, tooltip,
+    icons, key_value_viewer, line_editor, scrollable, tooltip,
 };
 
 use crate::commands::builders::write_file_cmd;
@@ -159,6 +161,87 @@ fn headers_actions<'a>(status_size: u32) -> Row<'a, CompletedMsg> {
     )
 }
 
+fn tests_view(cr: &CompletedResponse) -> Element<'_, CompletedMsg> {
+    if cr.assertions.is_empty() {
+        return container(text("No assertions or script tests for this request"))
+            .padding(8)
+            .into();
+    }
+
+    let results = cr.assertions.iter().flat_map(|outcome| {
+        outcome
+            .results
+            .iter()
+            .map(move |cond| -> Element<'_, CompletedMsg> {
+                let (ico, color) = match cond.result {
+                    MatcherResult::Passed => (icons::CheckBold, colors::SUCCESS),
+                    MatcherResult::Failed(_) => (icons::Error, colors::DANGER),
+                };
+                let header = Row::new()
+                    .push(icon(ico).color(color))
+                    .push(text(format!("{} {}", outcome.name, cond.name)))
+                    .spacing(8)
+                    .align_y(Alignment::Center);
+
+                let details = match &cond.result {
+                    MatcherResult::Passed => None,
+                    MatcherResult::Failed(des) => {
+                        let actual = des.actual.as_ref().map(|a| a.to_string());
+                        Some(
+                            Column::new()
+                                .push(text(&des.summary).color(colors::DANGER))
+                                .push(text(format!("Expected: {}", des.expected)))
+                                .push(text(format!(
+                                    "Actual: {}",
+                                    actual.as_deref().unwrap_or("undefined")
+                                )))
+                                .spacing(2)
+                                .padding(padding::left(28)),
+                        )
+                    }
+                };
+
+                Column::new().push(header).push(details).spacing(4).into()
+            })
+    });
+
+    scrollable(Column::from_iter(results).spacing(8).padding(8)).into()
+}
+
+fn console_view(cr: &CompletedResponse) -> Element<'_, CompletedMsg> {
+    if cr.console.is_empty() {
+        return container(text("No script output for this request"))
+            .padding(8)
+            .into();
+    }
+
+    console_entries(&cr.console)
+}
+
+/// Script output, also shown when the request failed
+pub fn console_entries<'a, M: 'a>(console: &'a [ConsoleEntry]) -> Element<'a, M> {
+    let entries = console.iter().map(|entry| -> Element<'a, M> {
+        let color = match entry.level {
+            ConsoleLevel::Log => colors::GREY,
+            ConsoleLevel::Info => colors::INFO,
+            ConsoleLevel::Warn => colors::WARNING,
+            ConsoleLevel::Error => colors::DANGER,
+        };
+
+        Row::new()
+            .push(
+                text(format!("[{}] {}", entry.script, entry.level))
+                    .color(color)
+                    .width(Length::Fixed(140.)),
+            )
+            .push(text(&entry.message).font(Font::MONOSPACE))
+            .spacing(8)
+            .into()
+    });
+
+    scrollable(Column::from_iter(entries).spacing(4).padding(8)).into()
+}
+
 pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, CompletedMsg> {
     let res = &cr.result;
     let status_size = 12;
@@ -187,6 +270,7 @@ pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, Comp
             body_actions(status_size, BodyMode::Raw, cr.result.body.is_json())
         }
         ResponseTabId::Headers => headers_actions(status_size),
+        ResponseTabId::Console | ResponseTabId::Tests => Row::new(),
     };
     let actions = actions.spacing(8).padding(0).align_y(Alignment::Center);
 
@@ -226,6 +310,18 @@ pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, Comp
                 .collect::<Vec<_>>();
             key_value_viewer(headers)
         }
+        ResponseTabId::Console => console_view(cr),
+        ResponseTabId::Tests => tests_view(cr),
+    };
+
+    let tests_label = match cr.assertion_counts() {
+        (_, 0) => "Tests".to_string(),
+        (passed, total) => format!("Tests ({}/{})", passed, total),
+    };
+
+    let console_label = match cr.console.len() {
+        0 => "Console".to_string(),
+        count => format!("Console ({})", count),
     };
 
     let tabs = button_tabs(
@@ -234,6 +330,8 @@ pub fn view<'a>(tab: &'a HttpTab, cr: &'a CompletedResponse) -> Element<'a, Comp
             button_tab(ResponseTabId::BodyPreview, || text("Preview")),
             button_tab(ResponseTabId::BodyRaw, || text("Body")),
             button_tab(ResponseTabId::Headers, || text("Headers")),
+            button_tab(ResponseTabId::Console, move || text(console_label.clone())),
+            button_tab(ResponseTabId::Tests, move || text(tests_label.clone())),
         ]
         .into_iter(),
         CompletedMsg::TabChanged,
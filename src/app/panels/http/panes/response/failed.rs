@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
+use super::completed::console_entries;
 use crate::app::panels::http::panes::response::ResponsePaneMsg;
 use crate::components::{icon, icons};
 use iced::widget::{Column, Row, container, text};
+use lib::scripting::console::ConsoleEntry;
+use lib::transformers::request::ScriptAborted;
 
-pub fn view<'a>(e: Arc<anyhow::Error>) -> iced::Element<'a, ResponsePaneMsg> {
+pub fn view<'a>(
+    e: Arc<anyhow::Error>,
+    console: &'a [ConsoleEntry],
+) -> iced::Element<'a, ResponsePaneMsg> {
     let error_icon = icon(icons::Error).size(60.0);
 
     let error_msg = Row::new()
@@ -12,9 +18,25 @@ pub fn view<'a>(e: Arc<anyhow::Error>) -> iced::Element<'a, ResponsePaneMsg> {
         .push(text(e.root_cause().to_string()))
         .align_y(iced::Alignment::Center);
 
+    // The script error alone does not say that nothing was sent
+    let aborted = e
+        .downcast_ref::<ScriptAborted>()
+        .map(|aborted| text(aborted.to_string()));
+
+    // Output logged before the error, such as the lines before a script threw
+    let console = (!console.is_empty()).then(|| {
+        Column::new()
+            .push(text("Console").size(14))
+            .push(console_entries(console))
+            .padding([12, 0])
+    });
+
     Column::new()
         .push(container(error_icon).padding(10))
+        .push(aborted)
         .push(error_msg)
+        .push(console)
+        .spacing(4)
         .align_x(iced::Alignment::Center)
         .into()
 }
@@ -44,7 +44,7 @@ pub fn view(tab: &HttpTab) -> Element<ResponsePaneMsg> {
         ResponseState::Completed(ref result) => {
             completed::view(tab, result).map(ResponsePaneMsg::Completed)
         }
-        ResponseState::Failed(ref e) => failed::view(e.clone()),
+        ResponseState::Failed(ref e, ref console) => failed::view(e.clone(), console),
     };
 
     center(res).padding([4, 0]).into()
@@ -13,7 +13,7 @@ use strum::VariantArray;
 use crate::components::{
     LineEditorMsg, NerdIcon, context_menu, icon, icons, line_editor, menu_item,
 };
-use lib::curl::{generate_curl_command, parse_curl_command};
+use lib::curl::{generate_curl_command, parse_curl};
 use lib::http::collection::Collection;
 use lib::http::request::Method;
 
@@ -85,7 +85,11 @@ impl UrlBarMsg {
             UrlBarMsg::CopyCurl => {
                 let collection = state.common.collections.get(tab.collection_ref.0);
                 let env = collection.map(|c| c.env_chain()).unwrap_or_default();
-                let curl = generate_curl_command(&tab.request().to_request(), env);
+                let mut request = tab.request().to_request();
+                if let Some(col) = collection {
+                    request.auth = col.resolve_auth(tab.collection_ref.1, request.auth);
+                }
+                let curl = generate_curl_command(&request, env);
                 return clipboard::write(curl).discard();
             }
         }
@@ -99,8 +103,14 @@ fn parse_curl_paste(tab: &mut RequestPane) -> Result<(), anyhow::Error> {
         return Ok(());
     }
 
-    let parsed = parse_curl_command(&url)?;
-    tab.update_from_curl(parsed);
+    let parsed = parse_curl(&url)?;
+    if let Some(proxy) = parsed.proxy {
+        log::warn!(
+            "Proxy {} of the pasted curl command is not used, set it in the collection settings",
+            proxy.url
+        );
+    }
+    tab.update_from_curl(parsed.request);
 
     Ok(())
 }
@@ -8,6 +8,7 @@ pub mod cookie_store;
 pub mod history;
 pub mod http;
 pub mod perf;
+pub mod runner;
 
 #[derive(Debug, Clone)]
 pub enum PanelMsg {
@@ -16,6 +17,7 @@ pub enum PanelMsg {
     Cookies(cookie_store::CookieTabMsg),
     History(history::HistoryTabMsg),
     Perf(perf::PerfTabMsg),
+    Runner(runner::RunnerTabMsg),
 }
 
 impl PanelMsg {
@@ -26,6 +28,7 @@ impl PanelMsg {
             PanelMsg::Cookies(msg) => msg.update(state).map(PanelMsg::Cookies),
             PanelMsg::History(msg) => msg.update(state).map(PanelMsg::History),
             PanelMsg::Perf(msg) => msg.update(state).map(PanelMsg::Perf),
+            PanelMsg::Runner(msg) => msg.update(state).map(PanelMsg::Runner),
         }
     }
 }
@@ -37,9 +40,10 @@ pub fn view<'a>(state: &'a AppState, tab: &'a Tab) -> iced::Element<'a, PanelMsg
             let col = state.common.collections.get(tab.collection_key).unwrap();
             collection::view(tab, col).map(PanelMsg::Collection)
         }
-        Tab::CookieStore(tab) => cookie_store::view(tab).map(PanelMsg::Cookies),
+        Tab::CookieStore(tab) => cookie_store::view(state, tab).map(PanelMsg::Cookies),
         Tab::History(tab) => history::view(state, tab).map(PanelMsg::History),
         Tab::Perf(tab) => perf::view(state, tab).map(PanelMsg::Perf),
+        Tab::Runner(tab) => runner::view(tab).map(PanelMsg::Runner),
     };
 
     container::Container::new(req)
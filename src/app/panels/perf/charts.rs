@@ -0,0 +1,232 @@
+use iced::widget::canvas::{self, Canvas, Frame, Geometry, Path, Stroke};
+use iced::widget::{Column, Row, text};
+use iced::{
+    Alignment, Color, Element, Length, Point, Rectangle, Renderer, Theme, alignment, mouse,
+};
+use lib::perf::TimelinePoint;
+
+use crate::components::colors;
+
+const CHART_HEIGHT: f32 = 160.0;
+/// Space for the value labels on the left
+const LEFT_MARGIN: f32 = 52.0;
+/// Space for the time labels at the bottom
+const BOTTOM_MARGIN: f32 = 18.0;
+const TOP_MARGIN: f32 = 8.0;
+const GRID_LINES: usize = 4;
+const LABEL_SIZE: f32 = 11.0;
+
+struct Series {
+    label: &'static str,
+    color: Color,
+    /// One value per second, `None` leaves a gap in the line
+    values: Vec<Option<f64>>,
+}
+
+struct LineChart {
+    series: Vec<Series>,
+    seconds: usize,
+    format: fn(f64) -> String,
+}
+
+pub fn throughput_chart<'a, Message: 'a>(timeline: &[TimelinePoint]) -> Element<'a, Message> {
+    let requests = Series {
+        label: "Requests/s",
+        color: colors::INFO,
+        values: timeline.iter().map(|p| Some(p.requests as f64)).collect(),
+    };
+    let errors = Series {
+        label: "Errors/s",
+        color: colors::DANGER,
+        values: timeline
+            .iter()
+            .map(|p| Some(p.requests as f64 * p.error_rate))
+            .collect(),
+    };
+
+    chart_view("Throughput", vec![requests, errors], timeline.len(), |v| {
+        format!("{:.0}", v)
+    })
+}
+
+pub fn latency_chart<'a, Message: 'a>(timeline: &[TimelinePoint]) -> Element<'a, Message> {
+    let percentile_series =
+        |label, color, percentile: fn(&TimelinePoint) -> Option<std::time::Duration>| Series {
+            label,
+            color,
+            values: timeline
+                .iter()
+                .map(|p| percentile(p).map(|d| d.as_secs_f64() * 1000.0))
+                .collect(),
+        };
+
+    let series = vec![
+        percentile_series("p50", colors::SUCCESS, |p| p.p50),
+        percentile_series("p95", colors::WARNING, |p| p.p95),
+        percentile_series("p99", colors::DANGER, |p| p.p99),
+    ];
+
+    chart_view("Latency", series, timeline.len(), |v| {
+        if v < 1000.0 {
+            format!("{:.0}ms", v)
+        } else {
+            format!("{:.1}s", v / 1000.0)
+        }
+    })
+}
+
+fn chart_view<'a, Message: 'a>(
+    title: &'static str,
+    series: Vec<Series>,
+    seconds: usize,
+    format: fn(f64) -> String,
+) -> Element<'a, Message> {
+    let legend = series.iter().fold(
+        Row::new().push(text(title).size(16).color(colors::INFO)),
+        |row, series| {
+            row.push(
+                text(format!("— {}", series.label))
+                    .size(12)
+                    .color(series.color),
+            )
+        },
+    );
+
+    let chart = LineChart {
+        series,
+        seconds,
+        format,
+    };
+
+    Column::new()
+        .push(legend.spacing(12).align_y(Alignment::Center))
+        .push(
+            Canvas::new(chart)
+                .width(Length::Fill)
+                .height(Length::Fixed(CHART_HEIGHT)),
+        )
+        .spacing(4)
+        .into()
+}
+
+/// Rounds the largest value up to a multiple of a 1, 2 or 5 step per grid line
+fn axis_max(max: f64) -> f64 {
+    let step = (max / GRID_LINES as f64).max(1.0);
+    let magnitude = 10f64.powf(step.log10().floor());
+    let nice = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&m| m >= step)
+        .unwrap_or(10.0 * magnitude);
+    nice * GRID_LINES as f64
+}
+
+impl<Message> canvas::Program<Message> for LineChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let palette = theme.palette();
+        let label_color = palette.background.weak.text;
+        let grid = Stroke::default()
+            .with_color(palette.background.strong.color)
+            .with_width(1.0);
+
+        let plot = Rectangle {
+            x: LEFT_MARGIN,
+            y: TOP_MARGIN,
+            width: (bounds.width - LEFT_MARGIN).max(1.0),
+            height: (bounds.height - TOP_MARGIN - BOTTOM_MARGIN).max(1.0),
+        };
+
+        let max = self
+            .series
+            .iter()
+            .flat_map(|series| series.values.iter().flatten())
+            .fold(0.0f64, |max, &v| max.max(v));
+        let max = axis_max(max);
+
+        for line in 0..=GRID_LINES {
+            let value = max * line as f64 / GRID_LINES as f64;
+            let y = plot.y + plot.height * (1.0 - line as f32 / GRID_LINES as f32);
+            frame.stroke(
+                &Path::line(Point::new(plot.x, y), Point::new(plot.x + plot.width, y)),
+                grid,
+            );
+            frame.fill_text(canvas::Text {
+                content: (self.format)(value),
+                position: Point::new(plot.x - 6.0, y),
+                color: label_color,
+                size: LABEL_SIZE.into(),
+                align_x: alignment::Horizontal::Right.into(),
+                align_y: alignment::Vertical::Center,
+                ..canvas::Text::default()
+            });
+        }
+
+        let time_label = |content: String, x: f32, align: alignment::Horizontal| canvas::Text {
+            content,
+            position: Point::new(x, plot.y + plot.height + 4.0),
+            color: label_color,
+            size: LABEL_SIZE.into(),
+            align_x: align.into(),
+            ..canvas::Text::default()
+        };
+        frame.fill_text(time_label(
+            "0s".to_string(),
+            plot.x,
+            alignment::Horizontal::Left,
+        ));
+        frame.fill_text(time_label(
+            format!("{}s", self.seconds),
+            plot.x + plot.width,
+            alignment::Horizontal::Right,
+        ));
+
+        if self.seconds == 0 {
+            return vec![frame.into_geometry()];
+        }
+
+        // Each value is plotted in the middle of its second
+        let point = |second: usize, value: f64| {
+            Point::new(
+                plot.x + plot.width * (second as f32 + 0.5) / self.seconds as f32,
+                plot.y + plot.height * (1.0 - (value / max) as f32),
+            )
+        };
+
+        for series in &self.series {
+            let stroke = Stroke::default().with_color(series.color).with_width(2.0);
+            let line = Path::new(|builder| {
+                let mut previous = None;
+                for (second, value) in series.values.iter().enumerate() {
+                    match (previous, value) {
+                        (Some(_), Some(value)) => builder.line_to(point(second, *value)),
+                        (None, Some(value)) => builder.move_to(point(second, *value)),
+                        _ => {}
+                    }
+                    previous = *value;
+                }
+            });
+            frame.stroke(&line, stroke);
+
+            // Values between gaps have no line to show them
+            for (second, value) in series.values.iter().enumerate() {
+                let before = second.checked_sub(1).and_then(|s| series.values[s]);
+                let after = series.values.get(second + 1).copied().flatten();
+                if let (Some(value), None, None) = (value, before, after) {
+                    frame.fill(&Path::circle(point(second, *value), 2.5), series.color);
+                }
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
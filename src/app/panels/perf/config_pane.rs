@@ -1,21 +1,25 @@
-use lib::http::CollectionRequest;
+use lib::http::{Collection, CollectionRequest};
+use lib::perf::{Extraction, LoadProfile, LoadStage, PerfRun};
 use std::time::Duration;
 
 use iced::{
     Alignment, Border, Element, Length, Point, Rectangle, Task, padding,
-    widget::{Button, Column, Row, button, container, rule, space, text},
+    widget::{Button, Column, Row, TextInput, button, container, pick_list, rule, space, text},
 };
 
 use crate::{
     commands::perf::PerfResult,
-    components::{icon, icons, split::Direction, text_input},
+    components::{icon, icons, scrollable, split::Direction, text_input},
     ids::PERF_REQUEST_DROP_ZONE,
     state::{
         AppState, Tab,
-        tabs::perf_tab::{PerfState, PerfTab},
+        tabs::perf_tab::{PerfState, PerfStep, PerfTab},
     },
 };
-use crate::{commands::perf::start_benchmark, components::NerdIcon};
+use crate::{
+    commands::perf::{save_perf_run, start_benchmark},
+    components::NerdIcon,
+};
 
 #[derive(Debug, Clone)]
 pub enum ConfigMsg {
@@ -23,11 +27,28 @@ pub enum ConfigMsg {
     UpdateTestDuration(String),
     UpdateConcurrentWorkers(String),
     UpdateTimeout(String),
+    ChangeProfile(LoadProfile),
+    UpdateRate(String),
+    UpdateMaxRequests(String),
+    UpdateStageDuration(usize, String),
+    UpdateStageTarget(usize, String),
+    AddStage,
+    RemoveStage(usize),
+    UpdateThresholdInput(String),
+    AddThreshold,
+    RemoveThreshold(usize),
     StartTest,
     StopTest,
-    ClearRequest,
+    ClearSteps,
+    RemoveStep(usize),
+    MoveStepUp(usize),
+    AddExtraction(usize),
+    UpdateExtractionVariable(usize, usize, String),
+    UpdateExtractionPath(usize, usize, String),
+    RemoveExtraction(usize, usize),
     Reset,
     Benchmark(PerfResult),
+    RunSaved(Option<Box<PerfRun>>),
     Drop(Point, Rectangle, CollectionRequest),
     HandleZones(
         Vec<(iced::advanced::widget::Id, Rectangle)>,
@@ -52,7 +73,7 @@ impl ConfigMsg {
             ConfigMsg::HandleZones(zones, request) => {
                 if !zones.is_empty() {
                     // Request was dropped on the drop zone
-                    tab.set_request(request);
+                    tab.add_step(request);
                 }
                 Task::none()
             }
@@ -83,13 +104,130 @@ impl ConfigMsg {
                 }
                 Task::none()
             }
+            ConfigMsg::ChangeProfile(profile) => {
+                tab.config.profile = profile;
+                Task::none()
+            }
+            ConfigMsg::UpdateRate(val) => {
+                if let Ok(rate) = val.parse::<u32>() {
+                    tab.config.rate = rate.clamp(1, 100_000);
+                }
+                if val.is_empty() {
+                    tab.config.rate = 1;
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateMaxRequests(val) => {
+                if let Ok(max) = val.parse::<u64>() {
+                    tab.config.max_requests = Some(max.max(1));
+                }
+                if val.is_empty() {
+                    tab.config.max_requests = None;
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateStageDuration(idx, val) => {
+                if let Some(stage) = tab.config.stages.get_mut(idx) {
+                    if let Ok(secs) = val.parse::<u64>() {
+                        stage.duration = Duration::from_secs(secs);
+                    }
+                    if val.is_empty() {
+                        stage.duration = Duration::ZERO;
+                    }
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateStageTarget(idx, val) => {
+                if let Some(stage) = tab.config.stages.get_mut(idx) {
+                    if let Ok(target) = val.parse::<u32>() {
+                        stage.target = target.min(100_000);
+                    }
+                    if val.is_empty() {
+                        stage.target = 0;
+                    }
+                }
+                Task::none()
+            }
+            ConfigMsg::AddStage => {
+                let last = tab.config.stages.last().copied();
+                tab.config.stages.push(LoadStage {
+                    duration: Duration::from_secs(30),
+                    target: last.map_or(10, |stage| stage.target),
+                });
+                Task::none()
+            }
+            ConfigMsg::RemoveStage(idx) => {
+                if idx < tab.config.stages.len() {
+                    tab.config.stages.remove(idx);
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateThresholdInput(val) => {
+                tab.threshold_input = val;
+                tab.threshold_error = None;
+                Task::none()
+            }
+            ConfigMsg::AddThreshold => {
+                tab.add_threshold();
+                Task::none()
+            }
+            ConfigMsg::RemoveThreshold(idx) => {
+                if idx < tab.config.thresholds.len() {
+                    tab.config.thresholds.remove(idx);
+                }
+                Task::none()
+            }
             ConfigMsg::StartTest => start_benchmark(state).map(ConfigMsg::Benchmark),
             ConfigMsg::StopTest => {
                 tab.cancel_test();
                 Task::none()
             }
-            ConfigMsg::ClearRequest => {
-                tab.request = None;
+            ConfigMsg::ClearSteps => {
+                tab.steps.clear();
+                Task::none()
+            }
+            ConfigMsg::RemoveStep(idx) => {
+                if idx < tab.steps.len() {
+                    tab.steps.remove(idx);
+                }
+                Task::none()
+            }
+            ConfigMsg::MoveStepUp(idx) => {
+                if idx > 0 && idx < tab.steps.len() {
+                    tab.steps.swap(idx - 1, idx);
+                }
+                Task::none()
+            }
+            ConfigMsg::AddExtraction(idx) => {
+                if let Some(step) = tab.steps.get_mut(idx) {
+                    step.extractions.push(Extraction::default());
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateExtractionVariable(idx, ext, val) => {
+                if let Some(extraction) = extraction_mut(tab, idx, ext) {
+                    extraction.variable = val;
+                }
+                Task::none()
+            }
+            ConfigMsg::UpdateExtractionPath(idx, ext, val) => {
+                if let Some(extraction) = extraction_mut(tab, idx, ext) {
+                    extraction.path = val;
+                }
+                Task::none()
+            }
+            ConfigMsg::RemoveExtraction(idx, ext) => {
+                if let Some(step) = tab.steps.get_mut(idx)
+                    && ext < step.extractions.len()
+                {
+                    step.extractions.remove(ext);
+                }
+                Task::none()
+            }
+            ConfigMsg::RunSaved(run) => {
+                if let Some(run) = run {
+                    tab.runs.insert(0, *run);
+                }
                 Task::none()
             }
             ConfigMsg::Reset => {
@@ -103,6 +241,8 @@ impl ConfigMsg {
                     }
                     PerfResult::Completed(Ok(metrics)) => {
                         tab.complete_test(metrics);
+                        return save_perf_run(state)
+                            .map(|run| ConfigMsg::RunSaved(run.map(Box::new)));
                     }
                     PerfResult::Completed(Err(_)) => {
                         tab.fail_test();
@@ -114,38 +254,196 @@ impl ConfigMsg {
     }
 }
 
+fn extraction_mut(tab: &mut PerfTab, idx: usize, ext: usize) -> Option<&mut Extraction> {
+    tab.steps.get_mut(idx)?.extractions.get_mut(ext)
+}
+
+fn config_row<'a>(label: &'a str, input: TextInput<'a, ConfigMsg>) -> Row<'a, ConfigMsg> {
+    Row::new()
+        .push(text(label).width(Length::FillPortion(1)))
+        .push(space::horizontal())
+        .push(input.width(Length::FillPortion(1)))
+}
+
+fn stages_view<'a>(stages: &'a [LoadStage], target_label: &'a str) -> Column<'a, ConfigMsg> {
+    let header = Row::new()
+        .push(text("Stage Duration (seconds)").width(Length::FillPortion(1)))
+        .push(text(target_label).width(Length::FillPortion(1)))
+        .push(space::horizontal().width(Length::Fixed(28.)))
+        .spacing(8);
+
+    let rows = stages.iter().enumerate().map(|(idx, stage)| {
+        Row::new()
+            .push(
+                text_input(
+                    "seconds",
+                    &stage.duration.as_secs().to_string(),
+                    move |val| ConfigMsg::UpdateStageDuration(idx, val),
+                )
+                .width(Length::FillPortion(1)),
+            )
+            .push(
+                text_input("target", &stage.target.to_string(), move |val| {
+                    ConfigMsg::UpdateStageTarget(idx, val)
+                })
+                .width(Length::FillPortion(1)),
+            )
+            .push(
+                button(icon(icons::Delete).size(20))
+                    .padding([0, 4])
+                    .style(button::text)
+                    .on_press(ConfigMsg::RemoveStage(idx)),
+            )
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into()
+    });
+
+    let add_stage = button_with_icon(icons::Plus, "Add Stage", ConfigMsg::AddStage)
+        .style(button::secondary)
+        .padding([2, 8]);
+
+    Column::new()
+        .push(header)
+        .extend(rows)
+        .push(add_stage)
+        .spacing(4)
+}
+
+fn thresholds_view<'a>(tab: &'a PerfTab) -> Column<'a, ConfigMsg> {
+    let thresholds = tab
+        .config
+        .thresholds
+        .iter()
+        .enumerate()
+        .map(|(idx, threshold)| {
+            Row::new()
+                .push(text(threshold.to_string()).width(Length::Fill))
+                .push(
+                    button(icon(icons::Delete).size(20))
+                        .padding([0, 4])
+                        .style(button::text)
+                        .on_press(ConfigMsg::RemoveThreshold(idx)),
+                )
+                .align_y(Alignment::Center)
+                .into()
+        });
+
+    let input = Row::new()
+        .push(
+            text_input(
+                "p95 < 300ms, error_rate < 1%, rps > 200",
+                &tab.threshold_input,
+                ConfigMsg::UpdateThresholdInput,
+            )
+            .on_submit(ConfigMsg::AddThreshold)
+            .width(Length::Fill),
+        )
+        .push(
+            button_with_icon(icons::Plus, "Add", ConfigMsg::AddThreshold)
+                .style(button::secondary)
+                .padding([2, 8])
+                .on_press_maybe(
+                    (!tab.threshold_input.trim().is_empty()).then_some(ConfigMsg::AddThreshold),
+                ),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let error = tab
+        .threshold_error
+        .as_deref()
+        .map(|e| text(e).size(12).style(text::danger));
+
+    Column::new()
+        .push(text("Thresholds"))
+        .extend(thresholds)
+        .push(input)
+        .push(error)
+        .spacing(4)
+}
+
 fn config_view<'a>(tab: &'a PerfTab) -> Element<'a, ConfigMsg> {
-    let duration = text_input(
-        "seconds",
-        &tab.config.duration.as_secs().to_string(),
-        ConfigMsg::UpdateTestDuration,
-    );
-    let duration = Row::new()
-        .push(text("Duration (seconds)").width(Length::FillPortion(1)))
+    let config = &tab.config;
+
+    let profile = Row::new()
+        .push(text("Load Profile").width(Length::FillPortion(1)))
         .push(space::horizontal())
-        .push(duration.width(Length::FillPortion(1)));
+        .push(
+            pick_list(Some(config.profile), LoadProfile::ALL, |profile| {
+                profile.to_string()
+            })
+            .on_select(ConfigMsg::ChangeProfile)
+            .width(Length::FillPortion(1)),
+        )
+        .align_y(Alignment::Center);
 
-    let concurrency = text_input(
-        "users",
-        &tab.config.concurrency.to_string(),
-        ConfigMsg::UpdateConcurrentWorkers,
+    let duration = config_row(
+        "Duration (seconds)",
+        text_input(
+            "seconds",
+            &config.duration.as_secs().to_string(),
+            ConfigMsg::UpdateTestDuration,
+        ),
     );
-    let concurrency = Row::new()
-        .push(text("Virtual Users").width(Length::FillPortion(1)))
-        .push(space::horizontal())
-        .push(concurrency.width(Length::FillPortion(1)));
 
-    let timeout = text_input(
-        "millis",
-        &tab.config.timeout.as_millis().to_string(),
-        ConfigMsg::UpdateTimeout,
+    let concurrency_label = if config.profile.is_rate() {
+        "Max Concurrent Requests"
+    } else {
+        "Virtual Users"
+    };
+    let concurrency = config_row(
+        concurrency_label,
+        text_input(
+            "users",
+            &config.concurrency.to_string(),
+            ConfigMsg::UpdateConcurrentWorkers,
+        ),
+    );
+
+    // With several steps the rate profiles start whole iterations of the scenario
+    let (rate_label, rate_target) = if tab.steps.len() > 1 {
+        ("Iterations per Second", "Target Iterations per Second")
+    } else {
+        ("Requests per Second", "Target Requests per Second")
+    };
+    let rate = config_row(
+        rate_label,
+        text_input("rps", &config.rate.to_string(), ConfigMsg::UpdateRate),
     );
-    let timeout = Row::new()
-        .push(text("Timeout (millis)").width(Length::FillPortion(1)))
-        .push(space::horizontal())
-        .push(timeout.width(Length::FillPortion(1)));
 
-    let has_request = tab.request.is_some();
+    let load: Vec<Element<'a, ConfigMsg>> = match config.profile {
+        LoadProfile::ConstantUsers => vec![duration.into(), concurrency.into()],
+        LoadProfile::ConstantRate => vec![duration.into(), rate.into(), concurrency.into()],
+        LoadProfile::RampingUsers => vec![stages_view(&config.stages, "Target Users").into()],
+        LoadProfile::RampingRate => vec![
+            stages_view(&config.stages, rate_target).into(),
+            concurrency.into(),
+        ],
+    };
+
+    let max_requests = config_row(
+        "Max Requests",
+        text_input(
+            "unlimited",
+            &config
+                .max_requests
+                .map(|max| max.to_string())
+                .unwrap_or_default(),
+            ConfigMsg::UpdateMaxRequests,
+        ),
+    );
+
+    let timeout = config_row(
+        "Timeout (millis)",
+        text_input(
+            "millis",
+            &config.timeout.as_millis().to_string(),
+            ConfigMsg::UpdateTimeout,
+        ),
+    );
+
+    let has_request = !tab.steps.is_empty();
 
     let start_stop_button = match &tab.state {
         PerfState::Running => button_with_icon(icons::CloseBox, "Stop", ConfigMsg::StopTest)
@@ -166,30 +464,26 @@ fn config_view<'a>(tab: &'a PerfTab) -> Element<'a, ConfigMsg> {
         .padding(padding::top(16))
         .align_x(Alignment::Center);
 
-    Column::from_iter([
-        duration.into(),
-        concurrency.into(),
-        timeout.into(),
-        start_stop_button.into(),
-    ])
-    .spacing(8)
-    .width(Length::FillPortion(1))
-    .into()
+    Column::new()
+        .push(profile)
+        .extend(load)
+        .push(max_requests)
+        .push(timeout)
+        .push(thresholds_view(tab))
+        .push(start_stop_button)
+        .spacing(8)
+        .width(Length::FillPortion(1))
+        .into()
 }
 
 fn request_selector_view<'a>(tab: &'a PerfTab, state: &'a AppState) -> Element<'a, ConfigMsg> {
-    let request = tab
-        .request
-        .and_then(|request| state.common.collections.get_ref(request));
     let collection = tab
-        .request
-        .and_then(|request| state.common.collections.get(request.0));
+        .collection()
+        .and_then(|key| state.common.collections.get(key));
 
-    let reqcol = request.zip(collection);
-    let content = if let Some((reqref, col)) = reqcol {
-        selected_request_view(reqref, col)
-    } else {
-        empty_drop_zone()
+    let content: Element<'a, ConfigMsg> = match collection {
+        Some(collection) => scrollable(steps_view(tab, collection)).into(),
+        None => empty_drop_zone().into(),
     };
 
     container(content)
@@ -200,12 +494,68 @@ fn request_selector_view<'a>(tab: &'a PerfTab, state: &'a AppState) -> Element<'
         .into()
 }
 
-fn selected_request_view<'a>(
+fn steps_view<'a>(tab: &'a PerfTab, collection: &'a Collection) -> Column<'a, ConfigMsg> {
+    let collection_row = Row::new()
+        .push(icon(icons::Folder).size(16))
+        .push(
+            text(&collection.name)
+                .size(14)
+                .style(|theme: &iced::Theme| text::Style {
+                    color: Some(theme.palette().background.strong.text),
+                }),
+        )
+        .push(space::horizontal())
+        .push(
+            button(text("Clear").size(14))
+                .padding([2, 8])
+                .style(button::text)
+                .on_press(ConfigMsg::ClearSteps),
+        )
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    let steps = tab.steps.iter().enumerate().filter_map(|(idx, step)| {
+        let request = collection.get_ref(step.request.1)?;
+        Some(step_view(idx, step, request, collection).into())
+    });
+
+    let hint = text("Drag more requests here to add steps, each virtual user sends them in order")
+        .size(12)
+        .style(|theme: &iced::Theme| text::Style {
+            color: Some(theme.palette().background.weak.text),
+        });
+
+    Column::new()
+        .push(collection_row)
+        .extend(steps)
+        .push(hint)
+        .spacing(12)
+        .width(Length::Fill)
+}
+
+fn step_view<'a>(
+    idx: usize,
+    step: &'a PerfStep,
     request: &'a lib::http::RequestRef,
-    collection: &'a lib::http::Collection,
+    collection: &'a Collection,
 ) -> Column<'a, ConfigMsg> {
+    let path_display = request
+        .path
+        .strip_prefix(&collection.path)
+        .ok()
+        .and_then(|p| p.to_str())
+        .unwrap_or_else(|| request.path.to_str().unwrap_or(""));
+
+    let step_button = |ico, msg| {
+        button(icon(ico).size(18))
+            .padding([0, 4])
+            .style(button::text)
+            .on_press(msg)
+    };
+
     let request_badge = container(
         Row::new()
+            .push(text(format!("{}.", idx + 1)).size(16))
             .push(
                 icon(icons::API)
                     .size(16)
@@ -214,16 +564,21 @@ fn selected_request_view<'a>(
                     }),
             )
             .push(
-                text(&request.name)
-                    .size(16)
-                    .style(|theme: &iced::Theme| text::Style {
-                        color: Some(theme.palette().background.base.text),
-                    }),
+                Column::new().push(text(&request.name).size(16)).push(
+                    text(path_display)
+                        .size(12)
+                        .style(|theme: &iced::Theme| text::Style {
+                            color: Some(theme.palette().background.weak.text),
+                        }),
+                ),
             )
+            .push(space::horizontal())
+            .push((idx > 0).then(|| step_button(icons::ArrowUp, ConfigMsg::MoveStepUp(idx))))
+            .push(step_button(icons::Delete, ConfigMsg::RemoveStep(idx)))
             .spacing(8)
             .align_y(Alignment::Center),
     )
-    .padding([10, 14])
+    .padding([8, 12])
     .width(Length::Fill)
     .style(|theme: &iced::Theme| container::Style {
         background: Some(theme.palette().primary.weak.color.into()),
@@ -235,92 +590,51 @@ fn selected_request_view<'a>(
         ..Default::default()
     });
 
-    let collection_row = Row::new()
-        .push(
-            container(icon(icons::Folder).size(16))
-                .width(Length::Fixed(24.0))
-                .align_x(Alignment::Center)
-                .style(|theme: &iced::Theme| container::Style {
-                    text_color: Some(theme.palette().background.strong.text),
-                    ..Default::default()
-                }),
-        )
-        .push(
-            text("Collection:")
-                .size(14)
-                .style(|theme: &iced::Theme| text::Style {
-                    color: Some(theme.palette().background.strong.text),
-                }),
-        )
-        .push(space::horizontal().width(8))
-        .push(
-            text(&collection.name)
-                .size(14)
-                .style(|theme: &iced::Theme| text::Style {
-                    color: Some(theme.palette().background.base.text),
-                }),
-        )
-        .spacing(8)
-        .align_y(Alignment::Center);
-
-    let path_display = request
-        .path
-        .strip_prefix(&collection.path)
-        .ok()
-        .and_then(|p| p.to_str())
-        .unwrap_or_else(|| request.path.to_str().unwrap_or(""));
-
-    let path_row = Row::new()
-        .push(
-            container(icon(icons::Path).size(16))
-                .width(Length::Fixed(24.0))
-                .align_x(Alignment::Center)
-                .style(|theme: &iced::Theme| container::Style {
-                    text_color: Some(theme.palette().background.strong.text),
-                    ..Default::default()
-                }),
-        )
-        .push(
-            text("Path:")
-                .size(14)
-                .style(|theme: &iced::Theme| text::Style {
-                    color: Some(theme.palette().background.strong.text),
-                }),
-        )
-        .push(space::horizontal().width(40))
-        .push(
-            text(path_display)
-                .size(14)
-                .style(|theme: &iced::Theme| text::Style {
-                    color: Some(theme.palette().background.base.text),
-                }),
-        )
-        .spacing(8)
-        .align_y(Alignment::Center);
+    let extractions = step
+        .extractions
+        .iter()
+        .enumerate()
+        .map(|(ext, extraction)| {
+            Row::new()
+                .push(
+                    text_input("variable", &extraction.variable, move |val| {
+                        ConfigMsg::UpdateExtractionVariable(idx, ext, val)
+                    })
+                    .width(Length::FillPortion(1)),
+                )
+                .push(
+                    text_input("$.data.token", &extraction.path, move |val| {
+                        ConfigMsg::UpdateExtractionPath(idx, ext, val)
+                    })
+                    .width(Length::FillPortion(2)),
+                )
+                .push(step_button(
+                    icons::Delete,
+                    ConfigMsg::RemoveExtraction(idx, ext),
+                ))
+                .spacing(8)
+                .align_y(Alignment::Center)
+                .into()
+        });
 
-    let info_card = container(
-        Column::new()
-            .push(collection_row)
-            .push(path_row)
-            .spacing(10),
+    let add_extraction = button_with_icon(
+        icons::Plus,
+        "Extract Variable",
+        ConfigMsg::AddExtraction(idx),
     )
-    .padding(10)
-    .width(Length::Fill)
-    .style(|theme: &iced::Theme| container::Style {
-        background: None,
-        border: Border {
-            radius: 4.0.into(),
-            width: 1.0,
-            color: theme.palette().background.weak.color,
-        },
-        ..Default::default()
-    });
+    .style(button::secondary)
+    .padding([2, 8]);
 
     Column::new()
         .push(request_badge)
-        .push(info_card)
-        .spacing(12)
-        .width(Length::Fill)
+        .push(
+            Column::new()
+                .extend(extractions)
+                .push(add_extraction)
+                .spacing(4)
+                .padding(padding::left(24)),
+        )
+        .spacing(6)
 }
 
 fn empty_drop_zone<'a>() -> Column<'a, ConfigMsg> {
@@ -9,6 +9,7 @@ use iced::{
 use crate::components::split::vertical_split;
 use crate::state::{AppState, tabs::perf_tab::PerfTab};
 
+mod charts;
 pub mod config_pane;
 pub mod report_pane;
 
@@ -1,30 +1,141 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::charts::{latency_chart, throughput_chart};
+use crate::commands::dialog::create_file_dialog;
 use crate::components::scrollable;
-use crate::components::{colors, icon, icons};
+use crate::components::{bold, button_tab, button_tabs, colors, icon, icons, tooltip};
 use crate::state::{
-    AppState,
-    tabs::perf_tab::{PerfState, PerfTab},
+    AppState, Tab,
+    tabs::perf_tab::{PerfReportTab, PerfState, PerfTab},
 };
+use chrono::{DateTime, Local};
 use iced::{
-    Alignment, Element, Length, Task,
+    Alignment, Color, Element, Length, Task,
     widget::{
-        Column, container, progress_bar, row,
+        Column, button, checkbox, container, progress_bar, row,
         space::{self, horizontal},
-        text,
+        table, text,
     },
 };
-use lib::perf::PerfStats;
+use lib::perf::{
+    Delta, ExportFormat, LoadProfile, PerfConfig, PerfRun, PerfStats, RunComparison, TimelinePoint,
+    export_run,
+};
+use rfd::FileHandle;
 
 #[derive(Debug, Clone)]
-pub enum ReportMsg {}
+pub enum ReportMsg {
+    TabChange(PerfReportTab),
+    RunsLoaded(Vec<PerfRun>),
+    ToggleRun(i64),
+    DeleteRun(i64),
+    Export,
+    ExportFile(Option<Arc<FileHandle>>),
+    Exported(PathBuf, Result<(), String>),
+}
 
 impl ReportMsg {
-    pub fn update(self, _state: &mut crate::state::AppState) -> Task<Self> {
-        Task::none()
+    pub fn update(self, state: &mut AppState) -> Task<Self> {
+        let db = state.common.history_db.clone();
+        let Some(Tab::Perf(tab)) = state.active_tab_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            ReportMsg::TabChange(report_tab) => {
+                tab.report_tab = report_tab;
+                let (PerfReportTab::Runs, Some(db)) = (report_tab, db) else {
+                    return Task::none();
+                };
+                Task::future(async move {
+                    match db.get_perf_runs(Some(100)).await {
+                        Ok(runs) => ReportMsg::RunsLoaded(runs),
+                        Err(e) => {
+                            log::error!("Error loading perf runs: {e:?}");
+                            ReportMsg::RunsLoaded(vec![])
+                        }
+                    }
+                })
+            }
+            ReportMsg::RunsLoaded(runs) => {
+                tab.set_runs(runs);
+                Task::none()
+            }
+            ReportMsg::ToggleRun(id) => {
+                tab.toggle_run(id);
+                Task::none()
+            }
+            ReportMsg::DeleteRun(id) => {
+                tab.runs.retain(|run| run.id != id);
+                tab.selected_runs.retain(|&run| run != id);
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                Task::future(async move {
+                    if let Err(e) = db.delete_perf_run(id).await {
+                        log::error!("Error deleting perf run: {e:?}");
+                    }
+                })
+                .discard()
+            }
+            ReportMsg::Export => create_file_dialog("Export Run").map(ReportMsg::ExportFile),
+            ReportMsg::ExportFile(handle) => {
+                let selected = tab.selected_runs();
+                let (Some(handle), [run]) = (handle, selected.as_slice()) else {
+                    return Task::none();
+                };
+                let path = handle.path().to_path_buf();
+                let content = ExportFormat::from_path(&path).and_then(|f| export_run(run, f));
+                match content {
+                    Ok(content) => {
+                        Task::perform(tokio::fs::write(path.clone(), content), move |result| {
+                            ReportMsg::Exported(path, result.map_err(|e| e.to_string()))
+                        })
+                    }
+                    Err(e) => {
+                        tab.status = Some(format!("Export failed: {:#}", e));
+                        Task::none()
+                    }
+                }
+            }
+            ReportMsg::Exported(path, result) => {
+                tab.status = Some(match result {
+                    Ok(()) => format!("Exported run to {}", path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                });
+                Task::none()
+            }
+        }
     }
 }
 
 pub fn view<'a>(_state: &'a AppState, tab: &'a PerfTab) -> Element<'a, ReportMsg> {
-    let content = match tab.state {
+    let tabs = button_tabs(
+        tab.report_tab,
+        [
+            button_tab(PerfReportTab::Report, || text("Report")),
+            button_tab(PerfReportTab::Runs, || text("Saved Runs")),
+        ]
+        .into_iter(),
+        ReportMsg::TabChange,
+        None,
+    );
+
+    let content = match tab.report_tab {
+        PerfReportTab::Report => report_view(tab),
+        PerfReportTab::Runs => runs_view(tab),
+    };
+
+    Column::new()
+        .push(tabs)
+        .push(container(content).width(Length::Fill).height(Length::Fill))
+        .spacing(8)
+        .into()
+}
+
+fn report_view<'a>(tab: &'a PerfTab) -> Element<'a, ReportMsg> {
+    match tab.state {
         PerfState::Idle => empty_view(),
         PerfState::Running => running_view(tab),
         PerfState::Completed => {
@@ -42,14 +153,198 @@ pub fn view<'a>(_state: &'a AppState, tab: &'a PerfTab) -> Element<'a, ReportMsg
                 cancelled_view()
             }
         }
+    }
+}
+
+fn runs_view<'a>(tab: &'a PerfTab) -> Element<'a, ReportMsg> {
+    let selected = tab.selected_runs();
+
+    let export = tooltip(
+        "Export the selected run as JSON, CSV or HTML",
+        button(icon(icons::Download).size(20))
+            .padding([0, 4])
+            .style(button::text)
+            .on_press_maybe((selected.len() == 1).then_some(ReportMsg::Export)),
+    );
+    let toolbar = row![
+        text("Select a run to export, or two runs to compare them").style(weak_text),
+        horizontal(),
+        tab.status.as_deref().map(text),
+        export,
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center);
+
+    let columns = [
+        table::column(text(""), |run: &PerfRun| {
+            let id = run.id;
+            checkbox(tab.selected_runs.contains(&id)).on_toggle(move |_| ReportMsg::ToggleRun(id))
+        })
+        .align_y(Alignment::Center),
+        table::column(bold("When"), |run: &PerfRun| {
+            let local_time: DateTime<Local> = run.timestamp.into();
+            text(local_time.format("%m/%d %H:%M:%S").to_string())
+        })
+        .width(Length::FillPortion(2))
+        .align_y(Alignment::Center),
+        table::column(bold("Requests"), |run: &PerfRun| {
+            let collection = run.collection_name.as_deref().unwrap_or_default();
+            Column::new()
+                .push(text(run.name()))
+                .push(text(collection).size(12).style(weak_text))
+        })
+        .width(Length::FillPortion(4))
+        .align_y(Alignment::Center),
+        table::column(bold("RPS"), |run: &PerfRun| {
+            text(format!("{:.1}", run.stats.requests_per_second))
+        })
+        .width(Length::FillPortion(1))
+        .align_y(Alignment::Center),
+        table::column(bold("p95"), |run: &PerfRun| {
+            text(format_duration(run.stats.p95))
+        })
+        .width(Length::FillPortion(1))
+        .align_y(Alignment::Center),
+        table::column(bold("Errors"), |run: &PerfRun| {
+            text(format!("{:.1}%", run.stats.error_rate() * 100.0))
+        })
+        .width(Length::FillPortion(1))
+        .align_y(Alignment::Center),
+        table::column(text(""), |run: &PerfRun| {
+            tooltip(
+                "Delete run",
+                button(icon(icons::Delete).size(20))
+                    .padding([0, 4])
+                    .style(button::text)
+                    .on_press(ReportMsg::DeleteRun(run.id)),
+            )
+        })
+        .align_y(Alignment::Center),
+    ];
+
+    let runs: Element<'a, ReportMsg> = if tab.runs.is_empty() {
+        text("No saved runs, completed benchmarks are saved here").into()
+    } else {
+        container(table(columns, &tab.runs).padding_x(8).padding_y(4))
+            .style(container::bordered_box)
+            .into()
+    };
+
+    let comparison = match selected.as_slice() {
+        [a, b] => {
+            let (before, after) = if a.timestamp <= b.timestamp {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            Some(comparison_view(before, after))
+        }
+        _ => None,
     };
 
-    container(content)
-        .width(Length::Fill)
-        .height(Length::Fill)
+    scrollable(
+        Column::new()
+            .push(toolbar)
+            .push(runs)
+            .push(comparison)
+            .spacing(12),
+    )
+    .into()
+}
+
+/// Metrics of two runs side by side, with the change from the earlier to the later run
+fn comparison_view<'a>(before: &PerfRun, after: &PerfRun) -> Element<'a, ReportMsg> {
+    let comparison = RunComparison::new(&before.stats, &after.stats);
+    let when = |run: &PerfRun| {
+        let local_time: DateTime<Local> = run.timestamp.into();
+        local_time.format("%m/%d %H:%M:%S").to_string()
+    };
+
+    let header = row![
+        text("Metric")
+            .width(Length::FillPortion(2))
+            .style(weak_text),
+        text(when(before))
+            .width(Length::FillPortion(2))
+            .style(weak_text),
+        text(when(after))
+            .width(Length::FillPortion(2))
+            .style(weak_text),
+        text("Change")
+            .width(Length::FillPortion(2))
+            .style(weak_text),
+    ]
+    .padding(4)
+    .spacing(8);
+
+    let millis = |v: f64| format!("{:.0}ms", v);
+    let metrics = [
+        (
+            "Requests/sec",
+            Some(comparison.requests_per_second),
+            true,
+            (|v| format!("{:.1}", v)) as fn(f64) -> String,
+        ),
+        ("Error rate", Some(comparison.error_rate), false, |v| {
+            format!("{:.1}%", v)
+        }),
+        ("Average", comparison.avg, false, millis),
+        ("p50", comparison.p50, false, millis),
+        ("p95", comparison.p95, false, millis),
+        ("p99", comparison.p99, false, millis),
+    ];
+
+    let rows = metrics
+        .into_iter()
+        .map(|(label, delta, higher_is_better, format)| {
+            let cell = |value: Option<String>| {
+                text(value.unwrap_or_else(|| "N/A".to_string())).width(Length::FillPortion(2))
+            };
+            row![
+                text(label).width(Length::FillPortion(2)),
+                cell(delta.map(|d| format(d.before))),
+                cell(delta.map(|d| format(d.after))),
+                cell(delta.map(|d| delta_label(d, format)))
+                    .color(delta.map_or(colors::DARK_GREY, |d| delta_color(d, higher_is_better))),
+            ]
+            .padding(4)
+            .spacing(8)
+            .into()
+        });
+
+    Column::new()
+        .push(text("Comparison").size(20).color(colors::INFO))
+        .push(header)
+        .extend(rows)
+        .spacing(4)
         .into()
 }
 
+fn delta_label(delta: Delta, format: fn(f64) -> String) -> String {
+    let sign = if delta.change() > 0.0 { "+" } else { "" };
+    match delta.percent() {
+        Some(percent) => format!("{sign}{} ({sign}{:.1}%)", format(delta.change()), percent),
+        None => format!("{sign}{}", format(delta.change())),
+    }
+}
+
+fn delta_color(delta: Delta, higher_is_better: bool) -> Color {
+    let change = delta.change();
+    if change == 0.0 {
+        colors::DARK_GREY
+    } else if (change > 0.0) == higher_is_better {
+        colors::SUCCESS
+    } else {
+        colors::DANGER
+    }
+}
+
+fn weak_text(theme: &iced::Theme) -> text::Style {
+    text::Style {
+        color: Some(theme.palette().background.weak.text),
+    }
+}
+
 fn empty_view<'a>() -> Element<'a, ReportMsg> {
     container(
         Column::new()
@@ -87,7 +382,7 @@ fn empty_view<'a>() -> Element<'a, ReportMsg> {
 }
 
 fn running_view<'a>(tab: &'a PerfTab) -> Element<'a, ReportMsg> {
-    let test_duration_secs = tab.config.duration.as_secs();
+    let test_duration_secs = tab.config.total_duration().as_secs().max(1);
 
     let mut content = Column::new().push(space::vertical().height(12));
 
@@ -120,7 +415,7 @@ fn running_view<'a>(tab: &'a PerfTab) -> Element<'a, ReportMsg> {
                     .style(base_text),
                 text(format!("({:.0}%)", progress * 100.0)).color(colors::WARNING),
                 horizontal(),
-                text(format!("Virtual Users: {}", tab.config.concurrency)).style(base_text),
+                text(load_label(&tab.config)).style(base_text),
             ]
             .spacing(8)
             .align_y(Alignment::Center),
@@ -184,7 +479,14 @@ fn running_view<'a>(tab: &'a PerfTab) -> Element<'a, ReportMsg> {
                 ]
                 .spacing(16),
             )
+            .push(space::vertical().height(20));
+
+        // The bucket of the current second is still filling up
+        let completed = (stats.total_duration.as_secs() as usize).min(stats.timeline.len());
+        content = content
+            .push(charts_view(&stats.timeline[..completed]))
             .push(space::vertical().height(20))
+            .push(steps_view(stats))
             .push(
                 Column::new()
                     .push(text("Statistics").size(16).color(colors::INFO))
@@ -401,7 +703,11 @@ fn cancelled_with_stats_view(stats: &PerfStats) -> Element<'static, ReportMsg> {
                     format_duration(stats.max),
                 ))
                 .spacing(4),
-        );
+        )
+        .push(space::vertical().height(16))
+        .push(thresholds_view(stats))
+        .push(charts_view(&stats.timeline))
+        .push(steps_view(stats));
 
     if !stats.status_codes.is_empty() {
         let mut status_column = Column::new()
@@ -411,13 +717,11 @@ fn cancelled_with_stats_view(stats: &PerfStats) -> Element<'static, ReportMsg> {
 
         let mut status_codes: Vec<_> = stats.status_codes.iter().collect();
         status_codes.sort_by_key(|(code, _)| *code);
+        let responses: u64 = stats.status_codes.values().sum();
 
         for (code, count) in status_codes {
-            let percentage = if stats.successful_requests > 0 {
-                format!(
-                    " ({:.1}%)",
-                    (*count as f64 / stats.successful_requests as f64) * 100.0
-                )
+            let percentage = if responses > 0 {
+                format!(" ({:.1}%)", (*count as f64 / responses as f64) * 100.0)
             } else {
                 String::new()
             };
@@ -554,7 +858,11 @@ fn completed_view(stats: &PerfStats) -> Element<'static, ReportMsg> {
                     format_duration(stats.max),
                 ))
                 .spacing(4),
-        );
+        )
+        .push(space::vertical().height(16))
+        .push(thresholds_view(stats))
+        .push(charts_view(&stats.timeline))
+        .push(steps_view(stats));
 
     if !stats.status_codes.is_empty() {
         let mut status_column = Column::new()
@@ -564,13 +872,11 @@ fn completed_view(stats: &PerfStats) -> Element<'static, ReportMsg> {
 
         let mut status_codes: Vec<_> = stats.status_codes.iter().collect();
         status_codes.sort_by_key(|(code, _)| *code);
+        let responses: u64 = stats.status_codes.values().sum();
 
         for (code, count) in status_codes {
-            let percentage = if stats.successful_requests > 0 {
-                format!(
-                    " ({:.1}%)",
-                    (*count as f64 / stats.successful_requests as f64) * 100.0
-                )
+            let percentage = if responses > 0 {
+                format!(" ({:.1}%)", (*count as f64 / responses as f64) * 100.0)
             } else {
                 String::new()
             };
@@ -632,6 +938,118 @@ fn completed_view(stats: &PerfStats) -> Element<'static, ReportMsg> {
     scrollable(content).into()
 }
 
+fn charts_view(timeline: &[TimelinePoint]) -> Element<'static, ReportMsg> {
+    Column::new()
+        .push(throughput_chart(timeline))
+        .push(space::vertical().height(12))
+        .push(latency_chart(timeline))
+        .into()
+}
+
+/// Whether the run met each threshold, nothing when there are none
+fn thresholds_view(stats: &PerfStats) -> Option<Element<'static, ReportMsg>> {
+    if stats.thresholds.is_empty() {
+        return None;
+    }
+
+    let (title, title_color) = if stats.thresholds_passed() {
+        ("Thresholds Passed", colors::SUCCESS)
+    } else {
+        ("Thresholds Failed", colors::DANGER)
+    };
+
+    let rows = stats.thresholds.iter().map(|result| {
+        let (status, color) = if result.passed {
+            ("Pass", colors::SUCCESS)
+        } else {
+            ("Fail", colors::DANGER)
+        };
+        row![
+            text(result.threshold.to_string()).width(Length::FillPortion(2)),
+            text(result.actual_label()).width(Length::FillPortion(1)),
+            text(status).width(Length::FillPortion(1)).color(color),
+        ]
+        .padding(4)
+        .spacing(8)
+        .into()
+    });
+
+    Some(
+        Column::new()
+            .push(text(title).size(20).color(title_color))
+            .push(space::vertical().height(4))
+            .extend(rows)
+            .push(space::vertical().height(16))
+            .spacing(4)
+            .into(),
+    )
+}
+
+/// Requests and latencies of each step, only shown for scenarios with several steps
+fn steps_view(stats: &PerfStats) -> Option<Element<'static, ReportMsg>> {
+    if stats.steps.len() < 2 {
+        return None;
+    }
+
+    let header_style = |theme: &iced::Theme| text::Style {
+        color: Some(theme.palette().background.weak.text),
+    };
+    let header = row![
+        text("Step")
+            .width(Length::FillPortion(3))
+            .style(header_style),
+        text("Requests")
+            .width(Length::FillPortion(1))
+            .style(header_style),
+        text("Failed")
+            .width(Length::FillPortion(1))
+            .style(header_style),
+        text("p50")
+            .width(Length::FillPortion(1))
+            .style(header_style),
+        text("p95")
+            .width(Length::FillPortion(1))
+            .style(header_style),
+        text("p99")
+            .width(Length::FillPortion(1))
+            .style(header_style),
+    ]
+    .padding(4)
+    .spacing(8);
+
+    let rows = stats.steps.iter().enumerate().map(|(idx, step)| {
+        let failed_color = if step.failed_requests > 0 {
+            colors::DANGER
+        } else {
+            colors::SUCCESS
+        };
+        row![
+            text(format!("{}. {}", idx + 1, step.name)).width(Length::FillPortion(3)),
+            text(step.total_requests.to_string()).width(Length::FillPortion(1)),
+            text(step.failed_requests.to_string())
+                .width(Length::FillPortion(1))
+                .color(failed_color),
+            text(format_duration(step.p50)).width(Length::FillPortion(1)),
+            text(format_duration(step.p95)).width(Length::FillPortion(1)),
+            text(format_duration(step.p99)).width(Length::FillPortion(1)),
+        ]
+        .padding(4)
+        .spacing(8)
+        .into()
+    });
+
+    Some(
+        Column::new()
+            .push(space::vertical().height(16))
+            .push(text("Steps").size(20).color(colors::INFO))
+            .push(space::vertical().height(4))
+            .push(header)
+            .extend(rows)
+            .spacing(4)
+            .into(),
+    )
+}
+
 fn stat_row_owned(label: String, value: String) -> Element<'static, ReportMsg> {
     row![
         text(label)
@@ -651,6 +1069,14 @@ fn stat_row_owned(label: String, value: String) -> Element<'static, ReportMsg> {
     .into()
 }
 
+fn load_label(config: &PerfConfig) -> String {
+    match config.profile {
+        LoadProfile::ConstantUsers => format!("Virtual Users: {}", config.concurrency),
+        LoadProfile::ConstantRate => format!("Rate: {}/s", config.rate),
+        LoadProfile::RampingUsers | LoadProfile::RampingRate => config.profile.to_string(),
+    }
+}
+
 fn format_duration(duration: Option<std::time::Duration>) -> String {
     duration
         .map(|d| {
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use iced::{
+    Alignment, Element, Length, Task, padding,
+    widget::{Column, Row, button, container, space, text},
+};
+use lib::assertions::runner::MatcherResult;
+use lib::runner::{RunOutcome, RunResult};
+use lib::utils::fmt_duration;
+use rfd::FileHandle;
+
+use crate::commands::dialog::open_file_dialog_with_filter;
+use crate::commands::runner::{RunnerResult, start_run};
+use crate::components::{colors, icon, icons, scrollable};
+use crate::state::tabs::runner_tab::{RunnerState, RunnerTab};
+use crate::state::{AppState, Tab};
+
+#[derive(Debug, Clone)]
+pub enum RunnerTabMsg {
+    Start,
+    Stop,
+    Run(RunnerResult),
+    SelectData,
+    DataSelected(Option<Arc<FileHandle>>),
+    ClearData,
+}
+
+impl RunnerTabMsg {
+    pub fn update(self, state: &mut AppState) -> Task<Self> {
+        let Some(Tab::Runner(tab)) = state.active_tab_mut() else {
+            return Task::none();
+        };
+
+        match self {
+            RunnerTabMsg::Start => return start_run(state).map(RunnerTabMsg::Run),
+            RunnerTabMsg::Stop => tab.cancel_run(),
+            RunnerTabMsg::Run(RunnerResult::Progress(result)) => tab.add_result(result),
+            RunnerTabMsg::Run(RunnerResult::Completed(Ok(summary))) => tab.complete_run(summary),
+            RunnerTabMsg::Run(RunnerResult::Completed(Err(e))) => tab.fail_run(e),
+            RunnerTabMsg::SelectData => {
+                return open_file_dialog_with_filter("Select Iteration Data", &["csv", "json"])
+                    .map(RunnerTabMsg::DataSelected);
+            }
+            RunnerTabMsg::DataSelected(handle) => {
+                if let Some(handle) = handle {
+                    tab.data = Some(handle.path().to_path_buf());
+                }
+            }
+            RunnerTabMsg::ClearData => tab.data = None,
+        }
+        Task::none()
+    }
+}
+
+fn summary_view<'a>(tab: &'a RunnerTab) -> Element<'a, RunnerTabMsg> {
+    let action = match tab.state {
+        RunnerState::Running => button(text("Stop"))
+            .style(button::danger)
+            .on_press(RunnerTabMsg::Stop),
+        RunnerState::Idle => button(text("Run"))
+            .style(button::primary)
+            .on_press(RunnerTabMsg::Start),
+        RunnerState::Completed | RunnerState::Failed | RunnerState::Cancelled => {
+            button(text("Run Again"))
+                .style(button::primary)
+                .on_press(RunnerTabMsg::Start)
+        }
+    };
+
+    let status = match tab.state {
+        RunnerState::Idle => "Ready",
+        RunnerState::Running => "Running",
+        RunnerState::Completed => "Completed",
+        RunnerState::Failed => "Failed",
+        RunnerState::Cancelled => "Cancelled",
+    };
+
+    let summary = &tab.summary;
+    let mut row = Row::new()
+        .push(action.padding([4, 12]))
+        .push(text(status))
+        .push(space::horizontal())
+        .push(text(format!("Passed: {}", summary.passed)).color(colors::SUCCESS))
+        .push(text(format!("Failed: {}", summary.failed)).color(colors::DANGER))
+        .push(text(format!("Total: {}", summary.total())));
+
+    if tab.state == RunnerState::Completed {
+        row = row.push(text(format!("Time: {}", fmt_duration(summary.duration))));
+    }
+
+    row.spacing(16).align_y(Alignment::Center).into()
+}
+
+fn data_view<'a>(tab: &'a RunnerTab) -> Element<'a, RunnerTabMsg> {
+    let idle = tab.state != RunnerState::Running;
+    let file = tab
+        .data
+        .as_ref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "No data file, requests run once".to_string());
+
+    let mut row = Row::new()
+        .push(text("Iteration Data:"))
+        .push(text(file))
+        .push(
+            button(text("Select"))
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press_maybe(idle.then_some(RunnerTabMsg::SelectData)),
+        );
+
+    if tab.data.is_some() {
+        row = row.push(
+            button(text("Clear"))
+                .padding([2, 8])
+                .style(button::secondary)
+                .on_press_maybe(idle.then_some(RunnerTabMsg::ClearData)),
+        );
+    }
+
+    row.spacing(8).align_y(Alignment::Center).into()
+}
+
+fn result_view<'a>(result: &'a RunResult, iterations: bool) -> Element<'a, RunnerTabMsg> {
+    let (ico, color) = if result.passed() {
+        (icons::CheckBold, colors::SUCCESS)
+    } else {
+        (icons::Error, colors::DANGER)
+    };
+
+    let mut header = Row::new().push(icon(ico).color(color));
+    if iterations {
+        header = header.push(text(format!("#{}", result.iteration + 1)));
+    }
+    let mut header = header.push(text(&result.name)).push(space::horizontal());
+
+    let mut details = Column::new().spacing(2).padding(padding::left(28));
+    match &result.outcome {
+        RunOutcome::Completed {
+            response,
+            assertions,
+        } => {
+            header = header.push(text(response.status.to_string()));
+            for outcome in assertions {
+                for condition in outcome.results.iter() {
+                    if let MatcherResult::Failed(description) = &condition.result {
+                        details = details.push(
+                            text(format!(
+                                "{} {}: {}",
+                                outcome.name, condition.name, description.summary
+                            ))
+                            .color(colors::DANGER),
+                        );
+                    }
+                }
+            }
+        }
+        RunOutcome::Failed(error) => {
+            details = details.push(text(error).color(colors::DANGER));
+        }
+    }
+
+    let header = header
+        .push(text(fmt_duration(result.duration)))
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+    Column::new().push(header).push(details).spacing(4).into()
+}
+
+pub fn view<'a>(tab: &'a RunnerTab) -> Element<'a, RunnerTabMsg> {
+    let results: Element<'a, RunnerTabMsg> = if let Some(error) = &tab.error {
+        text(error).color(colors::DANGER).into()
+    } else if tab.results.is_empty() && tab.state != RunnerState::Running {
+        text("Run the requests to see results here").into()
+    } else {
+        scrollable(
+            Column::from_iter(
+                tab.results
+                    .iter()
+                    .map(|result| result_view(result, tab.data.is_some())),
+            )
+            .spacing(8)
+            .padding(padding::right(12)),
+        )
+        .into()
+    };
+
+    Column::new()
+        .push(summary_view(tab))
+        .push(data_view(tab))
+        .push(
+            container(results)
+                .style(container::bordered_box)
+                .padding(8)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        )
+        .spacing(8)
+        .padding([8, 0])
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 
-use crate::components::{button_tab, button_tabs};
-use iced::widget::{Column, Row, pick_list, space, text};
+use crate::components::{ProxyEditorMsg, button_tab, button_tabs, proxy_editor};
+use iced::widget::{Column, Row, pick_list, space, text, toggler};
 use iced::{Element, Task, Theme};
+use lib::persistence::settings::{AppSettings, save_settings};
 
 use crate::state::AppState;
 use crate::state::popups::{AppSettingTabs, AppSettingsState, Popup};
@@ -11,6 +12,8 @@ use crate::state::popups::{AppSettingTabs, AppSettingsState, Popup};
 pub enum Message {
     TabChange(AppSettingTabs),
     ChangeTheme(Theme),
+    ToggleCookies(bool),
+    UpdateProxy(ProxyEditorMsg),
     Done,
 }
 
@@ -30,6 +33,18 @@ impl Message {
             Message::ChangeTheme(theme) => {
                 state.theme = theme;
             }
+            Message::ToggleCookies(enabled) => {
+                let common = &mut state.common;
+                common.settings.cookies_enabled = enabled;
+                common.clients.set_cookies_enabled(enabled);
+                return save_settings_cmd(common.settings.clone());
+            }
+            Message::UpdateProxy(msg) => {
+                let common = &mut state.common;
+                msg.update(&mut common.settings.proxy);
+                common.clients.set_proxy(common.settings.proxy.clone());
+                return save_settings_cmd(common.settings.clone());
+            }
         }
         Task::none()
     }
@@ -46,12 +61,19 @@ pub fn done(_data: &AppSettingsState) -> Option<Message> {
 pub(crate) fn view<'a>(state: &'a AppState, data: &'a AppSettingsState) -> Element<'a, Message> {
     let tab_bar = button_tabs(
         data.active_tab,
-        [button_tab(AppSettingTabs::General, move || text("General"))].into_iter(),
+        [
+            button_tab(AppSettingTabs::General, move || text("General")),
+            button_tab(AppSettingTabs::Proxy, move || text("Proxy")),
+        ]
+        .into_iter(),
         Message::TabChange,
         None,
     );
     let content = match data.active_tab {
         AppSettingTabs::General => general_tab(state),
+        AppSettingTabs::Proxy => {
+            proxy_editor(&state.common.settings.proxy, false).map(Message::UpdateProxy)
+        }
     };
 
     Column::new()
@@ -74,5 +96,24 @@ fn general_tab(state: &AppState) -> Element<Message> {
         )
         .align_y(iced::Alignment::Center);
 
-    Column::new().push(theme).spacing(8).into()
+    let cookies = Row::new()
+        .push(text("Cookies"))
+        .push(space::horizontal())
+        .push(
+            toggler(state.common.settings.cookies_enabled)
+                .on_toggle(Message::ToggleCookies)
+                .size(20),
+        )
+        .align_y(iced::Alignment::Center);
+
+    Column::new().push(theme).push(cookies).spacing(8).into()
+}
+
+fn save_settings_cmd(settings: AppSettings) -> Task<Message> {
+    Task::future(async move {
+        if let Err(e) = save_settings(settings).await {
+            log::error!("Error saving settings: {e:?}");
+        }
+    })
+    .discard()
 }
@@ -10,15 +10,18 @@ use rfd::{AsyncFileDialog, FileHandle};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
+use lib::assertions::{self, runner::AssertionOutcome, runner::script_outcome};
 use lib::client::send_request;
 use lib::http::collection::{Collection, Entry, FolderId, RequestId, RequestRef};
-use lib::http::request::Request;
-use lib::http::{CollectionKey, CollectionRequest, Environment, EnvironmentKey, KeyValList};
+use lib::http::request::{Auth, Request};
+use lib::http::{CollectionKey, CollectionRequest, Environment, EnvironmentKey};
 use lib::persistence::collections::{
     self, encode_collection, import_postman_collection, open_collection, save_collection,
 };
 use lib::persistence::history::HistoryDatabase;
 use lib::persistence::request::{encode_request, read_request, save_req_to_file};
+use lib::scripting::console::ConsoleEntry;
+use lib::scripting::send::ScriptContext;
 use lib::transformers::request::transform_request;
 
 use crate::state::response::ResponseState;
@@ -28,108 +31,112 @@ use crate::state::{AppState, CommonState, HttpTab, RequestDirtyState, Tab, TabKe
 
 #[derive(Debug, Clone)]
 pub enum ResponseResult {
-    Completed(lib::client::Response),
-    Error(Arc<anyhow::Error>),
+    Completed(
+        lib::client::Response,
+        Vec<AssertionOutcome>,
+        Vec<ConsoleEntry>,
+    ),
+    /// The error along with the script output logged before it
+    Error(Arc<anyhow::Error>, Vec<ConsoleEntry>),
 }
 
 pub fn send_request_cmd(state: &mut CommonState, tab: &mut HttpTab) -> Task<ResponseResult> {
     let collection = state.collections.get(tab.collection_ref.0);
 
-    let mut request = tab.request().to_request();
-    if let Some(col) = collection {
-        let mut headers = KeyValList::clone(&col.headers);
-        headers.extend(request.headers);
-        request.headers = headers;
-    }
-
-    // Load pre-request script content if script name is specified
-    let pre_request_script_path = if let Some(script_name) = &request.pre_request {
-        collection.and_then(|col| col.get_script_path(script_name))
-    } else {
-        None
-    };
-
-    // Load post-request script content if script name is specified
-    let post_request_script_path = if let Some(script_name) = &request.post_request {
-        collection.and_then(|col| col.get_script_path(script_name))
-    } else {
-        None
-    };
+    let request = tab.request().to_request();
+    let inherited = collection.map(|col| col.resolve_auth(tab.collection_ref.1, Auth::Inherit));
 
     let env = collection.map(|c| c.env_chain()).unwrap_or_default();
-    let disable_ssl = collection.map(|c| c.disable_ssl).unwrap_or_default();
-    let client = if disable_ssl {
-        state.client_no_ssl.clone()
-    } else {
-        state.client.clone()
+    let auth_state = collection
+        .map(|c| Arc::clone(&c.auth_state))
+        .unwrap_or_default();
+    let script_context = collection
+        .map(ScriptContext::for_collection)
+        .unwrap_or_default();
+    let post_script_context = script_context.clone();
+    let console = script_context.console().clone();
+    let runtime_vars = collection
+        .map(|c| Arc::clone(&c.runtime_vars))
+        .unwrap_or_default();
+    let pre_request_vars = Arc::clone(&runtime_vars);
+    let client = match state.clients.get(collection) {
+        Ok(client) => client,
+        Err(e) => return Task::done(ResponseResult::Error(Arc::new(e), Vec::new())),
     };
 
     let history_db = state.history_db.clone();
     let collection_name = collection.map(|c| c.name.clone());
-    let request_for_history = request.clone();
 
     let client_for_send = client.clone();
     let req_fut = async move {
-        // Load pre-request script content from file if path is available
-        if let Some(script_path) = pre_request_script_path {
-            match tokio::fs::read_to_string(script_path).await {
-                Ok(script_content) => {
-                    request.pre_request = Some(script_content);
-                }
-                Err(e) => {
-                    log::error!("Failed to load pre-request script: {}", e);
-                    request.pre_request = None;
-                }
-            }
-        }
-
-        transform_request(client, request, env).await
+        // Collection headers, inherited auth and the script contents, same as the runners
+        let mut request = script_context.prepare_request(request, inherited).await?;
+        let post_request = request.post_request.take();
+        let request_for_history = request.clone();
+        let request_assertions = request.assertions.clone();
+
+        let prepared =
+            transform_request(client, request, env, &auth_state, &script_context).await?;
+        pre_request_vars.extend(prepared.variables.clone());
+        Ok((
+            prepared,
+            post_request,
+            request_for_history,
+            request_assertions,
+        ))
     }
-    .and_then(move |req| send_request(client_for_send, req))
-    .and_then(move |response| async move {
-        // Load and execute post-request script if present
-        if let Some(script_path) = post_request_script_path {
-            match tokio::fs::read_to_string(script_path).await {
-                Ok(script_content) if !script_content.trim().is_empty() => {
-                    use lib::scripting::runner::run_post_request_script;
-                    match run_post_request_script(&script_content, &response, None) {
-                        Ok(_variables) => {
-                            log::info!("Post-request script executed successfully");
-                            // Variables could be stored/used for next request
-                        }
-                        Err(e) => {
-                            log::warn!("Post-request script execution failed: {}", e);
-                            // Continue even if post-request script fails
-                        }
+    .and_then(
+        move |(req, post_request, request_for_history, request_assertions)| async move {
+            let response = send_request(client_for_send, req).await?;
+
+            // Execute post-request script if present
+            let mut tests = None;
+            if let Some(script_content) = post_request.filter(|s| !s.trim().is_empty()) {
+                use lib::scripting::runner::run_post_request_script;
+                let result = match run_post_request_script(
+                    &script_content,
+                    &response,
+                    None,
+                    &post_script_context,
+                ) {
+                    Ok((variables, tests)) => {
+                        log::info!("Post-request script executed successfully");
+                        runtime_vars.extend(variables.all());
+                        Ok(tests)
                     }
-                }
-                Err(e) => {
-                    log::error!("Failed to load post-request script: {}", e);
-                }
-                _ => {}
+                    Err(e) => {
+                        log::warn!("Post-request script execution failed: {}", e);
+                        // Continue even if post-request script fails
+                        Err(e)
+                    }
+                };
+                tests = script_outcome(result);
             }
-        }
 
-        if let Some(db) = history_db
-            && let Err(e) = save_request_to_history(
-                &db,
-                &request_for_history,
-                &response,
-                collection_name.as_deref(),
-            )
-            .await
-        {
-            log::error!("Failed to save request to history: {e}");
-        }
-        Ok(response)
-    });
+            if let Some(db) = history_db
+                && let Err(e) = save_request_to_history(
+                    &db,
+                    &request_for_history,
+                    &response,
+                    collection_name.as_deref(),
+                )
+                .await
+            {
+                log::error!("Failed to save request to history: {e}");
+            }
+
+            let mut outcomes = assertions::run(&response, &request_assertions);
+            outcomes.extend(tests);
+            Ok((response, outcomes, post_script_context.console().entries()))
+        },
+    );
 
     tab.cancel_tasks();
     tab.response.state = ResponseState::Executing;
 
     let (task, handle) = Task::perform(req_fut, move |r| match r {
-        Ok(res) => ResponseResult::Completed(res),
-        Err(e) => ResponseResult::Error(Arc::new(e)),
+        Ok((res, assertions, console)) => ResponseResult::Completed(res, assertions, console),
+        Err(e) => ResponseResult::Error(Arc::new(e), console.entries()),
     })
     .abortable();
     tab.add_task(handle);
@@ -530,8 +537,11 @@ pub fn save_collection_cmd(collection: &mut Collection, tab: &mut CollectionTab)
         .as_ref()
         .and_then(|name| collection.environments.find_by_name(name));
     collection.headers = Arc::new(to_core_kv_list(&tab.headers));
+    collection.auth = tab.auth.to_auth();
     collection.disable_ssl = tab.disable_ssl;
     collection.timeout = tab.timeout;
+    collection.abort_on_script_error = tab.abort_on_script_error;
+    collection.proxy = tab.proxy_override.then(|| tab.proxy.clone());
 
     let encoded = encode_collection(collection);
     Task::perform(
@@ -1,8 +1,11 @@
 use iced::Task;
+use lib::cookies::CookieJar;
 use lib::http::collection::Collection;
-use lib::persistence::collections;
 use lib::persistence::history::{HistoryDatabase, HistoryEntrySummary, get_history_db_path};
+use lib::persistence::settings::{AppSettings, load_settings};
+use lib::persistence::{collections, cookies};
 use log::info;
+use std::sync::Arc;
 use std::time::Instant;
 
 use crate::debug::DELAY;
@@ -17,6 +20,7 @@ use self::builders::{check_dirty_requests_cmd, load_collections_cmd};
 pub mod builders;
 pub mod dialog;
 pub mod perf;
+pub mod runner;
 
 #[derive(Debug, Clone)]
 pub struct JobState {
@@ -36,6 +40,7 @@ pub enum BackgroundTask {
     SearchHistory,
     SaveWindowState,
     SaveSessionState,
+    SaveCookieJars,
 }
 
 fn remove_task(state: &mut AppState, task: BackgroundTask) {
@@ -79,6 +84,8 @@ fn schedule_task(state: &mut AppState, task: BackgroundTask, delay: u64) -> bool
 pub enum TaskMsg {
     CollectionsLoaded(Vec<Collection>),
     SessionLoaded(Option<SessionState>),
+    SettingsLoaded(AppSettings),
+    DefaultJarLoaded(Option<Arc<CookieJar>>),
     Completed(BackgroundTask),
     UpdateDirtyTabs(Vec<(TabKey, RequestDirtyState)>),
     HistoryInitialized(Option<HistoryDatabase>),
@@ -98,6 +105,17 @@ impl TaskMsg {
                     state.restore_session(session)
                 }
             }
+            TaskMsg::SettingsLoaded(settings) => {
+                let common = &mut state.common;
+                common.clients.set_cookies_enabled(settings.cookies_enabled);
+                common.clients.set_proxy(settings.proxy.clone());
+                common.settings = settings;
+            }
+            TaskMsg::DefaultJarLoaded(jar) => {
+                if let Some(jar) = jar {
+                    state.common.clients.set_default_jar(jar);
+                }
+            }
             TaskMsg::Completed(task) => {
                 task_done(state, task);
             }
@@ -314,12 +332,39 @@ fn save_session_state(state: &mut AppState) -> Task<TaskMsg> {
     })
 }
 
+fn save_cookie_jars(state: &mut AppState) -> Task<TaskMsg> {
+    let task = BackgroundTask::SaveCookieJars;
+    if !schedule_task(state, task, DELAY * 5) {
+        return Task::none();
+    }
+
+    // Only jars changed since they were last saved are written, the unsaved requests' jar too
+    let common = &state.common;
+    let jars = common
+        .collections
+        .iter()
+        .map(|(_, col)| (Some(col.path.clone()), Arc::clone(&col.cookies)))
+        .chain([(None, Arc::clone(&common.clients.default_jar))])
+        .filter(|(_, jar)| jar.is_dirty())
+        .collect::<Vec<_>>();
+
+    Task::future(async move {
+        for (path, jar) in jars {
+            if let Err(e) = cookies::save_cookie_jar(path, jar).await {
+                log::error!("Error saving cookie jar: {e:?}");
+            }
+        }
+        TaskMsg::Completed(BackgroundTask::SaveCookieJars)
+    })
+}
+
 pub fn background(state: &mut AppState) -> Task<TaskMsg> {
     Task::batch([
         save_open_collections(state),
         save_environments(state),
         check_dirty_requests(state),
         save_session_state(state),
+        save_cookie_jars(state),
         load_history(state),
         search_history(state),
         save_collections(state),
@@ -358,6 +403,20 @@ pub async fn load_session_state_cmd() -> Option<SessionState> {
     }
 }
 
+pub async fn load_settings_cmd() -> AppSettings {
+    load_settings().await.unwrap_or_else(|e| {
+        log::error!("Failed to load settings: {e:?}");
+        AppSettings::default()
+    })
+}
+
+pub async fn load_default_jar_cmd() -> Option<Arc<CookieJar>> {
+    cookies::load_cookie_jar(None)
+        .await
+        .inspect_err(|e| log::error!("Error loading cookie jar: {e:?}"))
+        .ok()
+}
+
 pub fn init_command() -> Task<AppMsg> {
     Task::batch([
         Task::perform(load_collections_cmd(), TaskMsg::CollectionsLoaded)
@@ -367,5 +426,7 @@ pub fn init_command() -> Task<AppMsg> {
             ))
             .map(AppMsg::Command),
         Task::perform(init_history_db_cmd(), TaskMsg::HistoryInitialized).map(AppMsg::Command),
+        Task::perform(load_settings_cmd(), TaskMsg::SettingsLoaded).map(AppMsg::Command),
+        Task::perform(load_default_jar_cmd(), TaskMsg::DefaultJarLoaded).map(AppMsg::Command),
     ])
 }
@@ -1,12 +1,15 @@
+use anyhow::Context;
 use iced::Task;
 use iced::task::{Straw, sipper};
-use lib::http::EnvironmentChain;
+use lib::http::collection::Collection;
+use lib::http::request::Auth;
+use lib::scripting::send::ScriptContext;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
 use crate::state::{AppState, Tab};
-use lib::perf::{PerfConfig, PerfMetrics, PerfRunner};
-use lib::persistence::request::read_request;
+use lib::perf::{Extraction, PerfConfig, PerfMetrics, PerfRun, PerfRunner, ScenarioStep};
 
 #[derive(Debug, Clone)]
 pub enum PerfResult {
@@ -20,24 +23,44 @@ pub enum BenchmarkError {
     Cancelled,
 }
 
+/// Request of a scenario step, read from disk when the benchmark starts
+#[derive(Debug, Clone)]
+pub struct StepSource {
+    name: String,
+    path: PathBuf,
+    inherited_auth: Auth,
+    extractions: Vec<Extraction>,
+}
+
+async fn load_step(source: StepSource, context: &ScriptContext) -> anyhow::Result<ScenarioStep> {
+    let request = context
+        .load_request(&source.path, Some(source.inherited_auth))
+        .await
+        .with_context(|| format!("Failed to load request {}", source.name))?;
+
+    Ok(ScenarioStep::new(source.name, request).with_extractions(source.extractions))
+}
+
 pub fn benchmark(
-    request_path: PathBuf,
+    steps: Vec<StepSource>,
     client: reqwest::Client,
     config: PerfConfig,
-    env_chain: EnvironmentChain,
-) -> impl Straw<PerfMetrics, PerfMetrics, BenchmarkError> {
+    collection: &Collection,
+) -> impl Straw<PerfMetrics, PerfMetrics, BenchmarkError> + use<> {
+    let env_chain = collection.env_chain();
+    let context = ScriptContext::for_collection(collection);
+    let runner = PerfRunner::new(client, config)
+        .with_auth_state(Arc::clone(&collection.auth_state))
+        .with_script_context(context.clone());
+
     sipper(move |mut progress| async move {
-        let request = match read_request(&request_path).await {
-            Ok(req) => req,
-            Err(e) => {
-                return Err(BenchmarkError::Error(format!(
-                    "Failed to load request: {}",
-                    e
-                )));
+        let mut scenario = Vec::with_capacity(steps.len());
+        for source in steps {
+            match load_step(source, &context).await {
+                Ok(step) => scenario.push(step),
+                Err(e) => return Err(BenchmarkError::Error(format!("{:#}", e))),
             }
-        };
-
-        let runner = PerfRunner::new(client, config);
+        }
 
         let (sender, mut receiver) = mpsc::channel(100);
         let handle = tokio::spawn(async move {
@@ -46,7 +69,7 @@ pub fn benchmark(
             }
         });
 
-        let result = runner.run(request, env_chain, sender).await;
+        let result = runner.run(scenario, env_chain, sender).await;
 
         handle.abort();
 
@@ -58,55 +81,96 @@ pub fn benchmark(
 }
 
 pub fn start_benchmark(state: &mut AppState) -> Task<PerfResult> {
-    let collection_request = match state.active_tab_mut() {
-        Some(Tab::Perf(tab)) => tab.request,
+    let steps = match state.active_tab_mut() {
+        Some(Tab::Perf(tab)) => tab.steps.clone(),
         _ => return Task::none(),
     };
 
-    let Some(collection_request) = collection_request else {
+    let Some(first) = steps.first() else {
         return Task::none();
     };
 
-    let Some(collection) = state.common.collections.get(collection_request.0) else {
+    let Some(collection) = state.common.collections.get(first.request.0) else {
         return Task::done(PerfResult::Completed(Err(BenchmarkError::Error(
             "Collection not found".to_string(),
         ))));
     };
 
-    let Some(request_ref) = state.common.collections.get_ref(collection_request) else {
-        return Task::done(PerfResult::Completed(Err(BenchmarkError::Error(
-            "Request not found".to_string(),
-        ))));
-    };
-
-    let request_path = request_ref.path.clone();
-    let env_chain = collection.env_chain();
-    let disable_ssl = collection.disable_ssl;
+    let mut sources = Vec::with_capacity(steps.len());
+    for step in steps {
+        let Some(request_ref) = collection.get_ref(step.request.1) else {
+            return Task::done(PerfResult::Completed(Err(BenchmarkError::Error(
+                "Request not found".to_string(),
+            ))));
+        };
+        sources.push(StepSource {
+            name: request_ref.name.clone(),
+            path: request_ref.path.clone(),
+            inherited_auth: collection.resolve_auth(step.request.1, Auth::Inherit),
+            extractions: step.extractions,
+        });
+    }
 
-    let config = match state.active_tab_mut() {
-        Some(Tab::Perf(tab)) => {
-            tab.start_test();
-            tab.config.clone()
+    let client = match state.common.clients.get(Some(collection)) {
+        Ok(client) => client,
+        Err(e) => {
+            let error = BenchmarkError::Error(format!("{:#}", e));
+            return Task::done(PerfResult::Completed(Err(error)));
         }
-        _ => return Task::none(),
     };
 
-    let client = if disable_ssl {
-        state.common.client_no_ssl.clone()
-    } else {
-        state.common.client.clone()
+    let config = match state.active_tab() {
+        Some(Tab::Perf(tab)) => tab.config.clone(),
+        _ => return Task::none(),
     };
+    let benchmark = benchmark(sources, client, config, collection);
 
-    let (task, handle) = Task::sip(
-        benchmark(request_path, client, config, env_chain),
-        PerfResult::Progress,
-        PerfResult::Completed,
-    )
-    .abortable();
+    let (task, handle) =
+        Task::sip(benchmark, PerfResult::Progress, PerfResult::Completed).abortable();
 
     if let Some(Tab::Perf(tab)) = state.active_tab_mut() {
+        tab.start_test();
         tab.add_task(handle);
     }
 
     task
 }
+
+/// Saves the completed run of the active perf tab to the history database
+pub fn save_perf_run(state: &AppState) -> Task<Option<PerfRun>> {
+    let Some(Tab::Perf(tab)) = state.active_tab() else {
+        return Task::none();
+    };
+    let (Some(db), Some(stats)) = (state.common.history_db.clone(), tab.stats.clone()) else {
+        return Task::none();
+    };
+
+    let collection = tab
+        .collection()
+        .and_then(|key| state.common.collections.get(key));
+    let requests = tab
+        .steps
+        .iter()
+        .filter_map(|step| collection?.get_ref(step.request.1))
+        .map(|request| request.name.clone())
+        .collect();
+    let mut run = PerfRun::new(
+        collection.map(|c| c.name.clone()),
+        requests,
+        tab.config.clone(),
+        stats,
+    );
+
+    Task::future(async move {
+        match db.save_perf_run(&run).await {
+            Ok(id) => {
+                run.id = id;
+                Some(run)
+            }
+            Err(e) => {
+                log::error!("Failed to save perf run: {e:?}");
+                None
+            }
+        }
+    })
+}
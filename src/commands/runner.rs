@@ -0,0 +1,77 @@
+use iced::Task;
+use iced::task::{Straw, sipper};
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+use crate::state::{AppState, Tab};
+use lib::runner::{CollectionRunner, RunResult, RunSummary, load_iterations};
+
+#[derive(Debug, Clone)]
+pub enum RunnerResult {
+    Progress(RunResult),
+    Completed(Result<RunSummary, String>),
+}
+
+fn run_collection(
+    runner: CollectionRunner,
+    data: Option<PathBuf>,
+) -> impl Straw<RunSummary, RunResult, String> {
+    sipper(move |mut progress| async move {
+        let runner = match data {
+            Some(path) => {
+                let rows = load_iterations(&path).await.map_err(|e| e.to_string())?;
+                runner.with_iterations(rows)
+            }
+            None => runner,
+        };
+
+        let (sender, mut receiver) = mpsc::channel(16);
+        let handle = tokio::spawn(async move {
+            while let Some(result) = receiver.recv().await {
+                let _ = progress.send(result).await;
+            }
+        });
+
+        let summary = runner.run(sender).await;
+        let _ = handle.await;
+
+        Ok(summary)
+    })
+}
+
+pub fn start_run(state: &mut AppState) -> Task<RunnerResult> {
+    let (collection_key, target, data) = match state.active_tab() {
+        Some(Tab::Runner(tab)) => (tab.collection, tab.target, tab.data.clone()),
+        _ => return Task::none(),
+    };
+
+    let Some(collection) = state.common.collections.get(collection_key) else {
+        return Task::done(RunnerResult::Completed(Err(
+            "Collection not found".to_string()
+        )));
+    };
+
+    let client = match state.common.clients.get(Some(collection)) {
+        Ok(client) => client,
+        Err(e) => return Task::done(RunnerResult::Completed(Err(format!("{:#}", e)))),
+    };
+
+    let runner = match CollectionRunner::new(client, collection, target) {
+        Ok(runner) => runner,
+        Err(e) => return Task::done(RunnerResult::Completed(Err(e.to_string()))),
+    };
+
+    let (task, handle) = Task::sip(
+        run_collection(runner, data),
+        RunnerResult::Progress,
+        RunnerResult::Completed,
+    )
+    .abortable();
+
+    if let Some(Tab::Runner(tab)) = state.active_tab_mut() {
+        tab.start_run();
+        tab.add_task(handle);
+    }
+
+    task
+}
@@ -76,4 +76,6 @@ pub mod icons {
     pub const OpenSideBar: NerdIcon = NerdIcon('');
     pub const CloseSideBar: NerdIcon = NerdIcon('');
     pub const Speedometer: NerdIcon = NerdIcon('󰓅');
+    pub const Play: NerdIcon = NerdIcon('󰐊');
+    pub const ArrowUp: NerdIcon = NerdIcon('󰁝');
 }
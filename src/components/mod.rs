@@ -12,6 +12,7 @@ mod lines;
 mod min_dimension;
 mod modal;
 mod multi_file_picker;
+mod proxy_editor;
 mod script_view;
 
 pub mod colors;
@@ -35,6 +36,7 @@ pub use modal::modal;
 pub use multi_file_picker::{
     FilePickerAction, FilePickerUpdateMsg, KeyFile, KeyFileList, multi_file_picker,
 };
+pub use proxy_editor::{ProxyEditorMsg, proxy_editor};
 pub use script_view::{
     ScriptViewConfig, script_editor, script_list_view, script_placeholder, script_selector,
 };
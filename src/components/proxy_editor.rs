@@ -0,0 +1,105 @@
+use iced::widget::{Column, Row, TextInput, pick_list, space, text};
+use iced::{Alignment, Element, Length};
+use lib::proxy::{ProxyMode, ProxySettings, system_proxy};
+
+use super::text_input;
+
+#[derive(Debug, Clone)]
+pub enum ProxyEditorMsg {
+    Mode(ProxyMode),
+    Url(String),
+    Username(String),
+    Password(String),
+    NoProxy(String),
+}
+
+impl ProxyEditorMsg {
+    pub fn update(self, proxy: &mut ProxySettings) {
+        match self {
+            ProxyEditorMsg::Mode(mode) => proxy.mode = mode,
+            ProxyEditorMsg::Url(url) => proxy.url = url,
+            ProxyEditorMsg::Username(username) => proxy.username = username,
+            ProxyEditorMsg::Password(password) => proxy.password = password,
+            ProxyEditorMsg::NoProxy(no_proxy) => proxy.no_proxy = no_proxy,
+        }
+    }
+}
+
+fn field<'a>(label: &'a str, input: TextInput<'a, ProxyEditorMsg>) -> Row<'a, ProxyEditorMsg> {
+    Row::new()
+        .push(text(label).width(Length::FillPortion(1)))
+        .push(input.padding([2, 4]).width(Length::FillPortion(2)))
+        .spacing(4)
+        .align_y(Alignment::Center)
+}
+
+/// Editor of the proxy settings, collection proxies take the password from a variable as it is
+/// not saved in the collection file
+pub fn proxy_editor<'a>(proxy: &'a ProxySettings, collection: bool) -> Element<'a, ProxyEditorMsg> {
+    let mode = Row::new()
+        .push(text("Proxy"))
+        .push(space::horizontal())
+        .push(
+            pick_list(Some(proxy.mode), ProxyMode::ALL, |mode| mode.to_string())
+                .on_select(ProxyEditorMsg::Mode)
+                .padding([2, 6]),
+        )
+        .align_y(Alignment::Center);
+
+    let details: Option<Element<'a, ProxyEditorMsg>> = match proxy.mode {
+        ProxyMode::None => None,
+        ProxyMode::System => {
+            let detected = match system_proxy() {
+                Some(url) => format!("Using {} from the environment", url),
+                None => "No proxy set in the environment".to_string(),
+            };
+            Some(text(detected).size(14).into())
+        }
+        ProxyMode::Manual => {
+            let url = field(
+                "URL",
+                text_input("http://localhost:8080", &proxy.url, ProxyEditorMsg::Url),
+            );
+            let username = field(
+                "Username",
+                text_input("Optional", &proxy.username, ProxyEditorMsg::Username),
+            );
+            let password = if collection {
+                text_input(
+                    "{{proxy_password}}",
+                    &proxy.password,
+                    ProxyEditorMsg::Password,
+                )
+            } else {
+                text_input("Optional", &proxy.password, ProxyEditorMsg::Password).secure(true)
+            };
+            let password = field("Password", password);
+            let password_hint: Option<Element<'a, ProxyEditorMsg>> = collection.then(|| {
+                text("Use a variable for the password, it is not saved in the collection")
+                    .size(14)
+                    .into()
+            });
+            let no_proxy = field(
+                "No Proxy",
+                text_input(
+                    "localhost, .example.com, 10.0.0.0/8",
+                    &proxy.no_proxy,
+                    ProxyEditorMsg::NoProxy,
+                ),
+            );
+
+            Some(
+                Column::new()
+                    .push(url)
+                    .push(username)
+                    .push(password)
+                    .push(password_hint)
+                    .push(no_proxy)
+                    .spacing(4)
+                    .into(),
+            )
+        }
+    };
+
+    Column::new().push(mode).push(details).spacing(8).into()
+}
@@ -55,6 +55,27 @@ impl EnvironmentsEditor {
         }
     }
 
+    pub fn set_value(&mut self, env_key: EnvironmentKey, name: &str, value: &str) {
+        if !self.environments.contains_key(&env_key) {
+            return;
+        }
+
+        let index = match self.variables.iter().position(|v| v.name.text() == name) {
+            Some(index) => index,
+            None => {
+                self.add_variable();
+                let index = self.variables.len() - 1;
+                self.variables[index].name = editor::Content::with_text(name);
+                index
+            }
+        };
+
+        self.variables[index]
+            .values
+            .insert(env_key, editor::Content::with_text(value));
+        self.edited = true;
+    }
+
     pub fn get_envs_for_save(&mut self) -> HashMap<EnvironmentKey, Environment> {
         self.edited = false;
         let mut envs = HashMap::new();
@@ -3,17 +3,17 @@ use iced_auto_updater_plugin::{AutoUpdaterPlugin, ReleaseInfo, UpdaterConfig};
 use iced_plugins::{PluginHandle, PluginManager, PluginManagerBuilder, PluginMessage};
 use iced_window_state_plugin::{AppName, WindowStatePlugin};
 use indexmap::IndexMap;
-use reqwest_cookie_store::CookieStoreRwLock;
 use tabs::collection_tab::CollectionTab;
 use tabs::cookies_tab::CookiesTab;
 use tabs::history_tab::HistoryTab;
 use tabs::perf_tab::PerfTab;
+use tabs::runner_tab::RunnerTab;
 
-use lib::client::{create_client, create_cookie_store};
+use lib::client::HttpClients;
 use lib::http::{CollectionRequest, Collections};
 use lib::persistence::history::HistoryDatabase;
+use lib::persistence::settings::AppSettings;
 use lib::{APP_NAME, APP_VERSION};
-use std::sync::Arc;
 pub use tabs::http_tab::*;
 
 use crate::app::AppMsg;
@@ -41,6 +41,7 @@ pub enum Tab {
     CookieStore(CookiesTab),
     History(HistoryTab),
     Perf(Box<PerfTab>),
+    Runner(Box<RunnerTab>),
 }
 
 impl Tab {
@@ -52,6 +53,7 @@ impl Tab {
                 | (Tab::CookieStore(_), Tab::CookieStore(_))
                 | (Tab::History(_), Tab::History(_))
                 | (Tab::Perf(_), Tab::Perf(_))
+                | (Tab::Runner(_), Tab::Runner(_))
         )
     }
 }
@@ -59,11 +61,10 @@ impl Tab {
 #[derive(Debug)]
 pub struct CommonState {
     pub collections: Collections,
-    pub client: reqwest::Client,
-    pub client_no_ssl: reqwest::Client,
+    pub clients: HttpClients,
+    pub settings: AppSettings,
     pub popup: Option<Popup>,
     pub background_tasks: Vec<JobState>,
-    pub cookie_store: Arc<CookieStoreRwLock>,
     pub history_db: Option<HistoryDatabase>,
 }
 
@@ -178,16 +179,15 @@ pub fn install_plugins() -> (Plugins, Task<PluginMessage>) {
 
 impl AppState {
     pub fn new(plugins: Plugins) -> Self {
-        let store = create_cookie_store();
+        let settings = AppSettings::default();
         Self {
             plugins,
             active_tab: TabKey::ZERO,
             tabs: IndexMap::new(),
             tab_history: indexmap::IndexSet::new(),
             common: CommonState {
-                client: create_client(false, Arc::clone(&store)),
-                client_no_ssl: create_client(true, Arc::clone(&store)),
-                cookie_store: store,
+                clients: HttpClients::new(settings.cookies_enabled, settings.proxy.clone()),
+                settings,
                 collections: Collections::default(),
                 popup: None,
                 background_tasks: Vec::new(),
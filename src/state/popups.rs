@@ -54,6 +54,7 @@ pub struct PopupNameState {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppSettingTabs {
     General,
+    Proxy,
 }
 
 #[derive(Debug)]
@@ -8,7 +8,10 @@ use crate::components::{KeyFileList, KeyValList};
 use crate::components::{KeyValUpdateMsg, KeyValue};
 use crate::state::utils::{key_value_from_text, key_value_to_text};
 use iced::advanced::widget;
-use lib::http::request::{self, Auth, JwtAlgorithm, Method, Request, RequestBody};
+use lib::assertions::Assertions;
+use lib::http::request::{
+    self, Auth, JwtAlgorithm, Method, OAuth2, OAuth2Grant, Request, RequestBody,
+};
 use reqwest::Url;
 use serde_json::Value;
 use strum::{Display, EnumString, IntoStaticStr, VariantNames};
@@ -130,6 +133,59 @@ impl From<JwtAlgorithm> for JwtAlgo {
     }
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    VariantNames,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum OAuth2GrantType {
+    #[default]
+    #[strum(serialize = "Client Credentials")]
+    ClientCredentials,
+    #[strum(serialize = "Password")]
+    Password,
+    #[strum(serialize = "Authorization Code")]
+    AuthorizationCode,
+    #[strum(serialize = "Refresh Token")]
+    RefreshToken,
+}
+
+impl OAuth2GrantType {
+    pub fn as_str(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl From<OAuth2GrantType> for OAuth2Grant {
+    fn from(val: OAuth2GrantType) -> Self {
+        match val {
+            OAuth2GrantType::ClientCredentials => OAuth2Grant::ClientCredentials,
+            OAuth2GrantType::Password => OAuth2Grant::Password,
+            OAuth2GrantType::AuthorizationCode => OAuth2Grant::AuthorizationCode,
+            OAuth2GrantType::RefreshToken => OAuth2Grant::RefreshToken,
+        }
+    }
+}
+
+impl From<OAuth2Grant> for OAuth2GrantType {
+    fn from(val: OAuth2Grant) -> Self {
+        match val {
+            OAuth2Grant::ClientCredentials => OAuth2GrantType::ClientCredentials,
+            OAuth2Grant::Password => OAuth2GrantType::Password,
+            OAuth2Grant::AuthorizationCode => OAuth2GrantType::AuthorizationCode,
+            OAuth2Grant::RefreshToken => OAuth2GrantType::RefreshToken,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ReqTabId {
     #[default]
@@ -152,6 +208,8 @@ pub enum RawAuthType {
     #[default]
     #[strum(serialize = "None")]
     None,
+    #[strum(serialize = "Inherit")]
+    Inherit,
     #[strum(serialize = "Basic Auth")]
     Basic {
         username: Content,
@@ -159,6 +217,11 @@ pub enum RawAuthType {
     },
     #[strum(serialize = "Bearer Token")]
     Bearer { token: Content },
+    #[strum(serialize = "Digest Auth")]
+    Digest {
+        username: Content,
+        password: Content,
+    },
     #[strum(serialize = "API Key")]
     APIKey {
         key: Content,
@@ -172,12 +235,35 @@ pub enum RawAuthType {
         payload: Content,
         add_to: AuthIn,
     },
+    #[strum(serialize = "OAuth 2.0")]
+    OAuth2 {
+        grant: OAuth2GrantType,
+        token_url: Content,
+        auth_url: Content,
+        redirect_url: Content,
+        client_id: Content,
+        client_secret: Content,
+        scope: Content,
+        username: Content,
+        password: Content,
+        refresh_token: Content,
+        use_pkce: bool,
+    },
+    #[strum(serialize = "AWS Signature")]
+    AwsSigV4 {
+        access_key: Content,
+        secret_key: Content,
+        session_token: Content,
+        region: Content,
+        service: Content,
+    },
 }
 
 impl RawAuthType {
-    fn to_auth(&self) -> Auth {
+    pub fn to_auth(&self) -> Auth {
         match self {
             RawAuthType::None => Auth::None,
+            RawAuthType::Inherit => Auth::Inherit,
             RawAuthType::Basic { username, password } => Auth::Basic {
                 username: username.text().trim().to_string(),
                 password: password.text().trim().to_string(),
@@ -185,6 +271,10 @@ impl RawAuthType {
             RawAuthType::Bearer { token } => Auth::Bearer {
                 token: token.text().trim().to_string(),
             },
+            RawAuthType::Digest { username, password } => Auth::Digest {
+                username: username.text().trim().to_string(),
+                password: password.text().trim().to_string(),
+            },
             RawAuthType::APIKey { key, value, add_to } => Auth::APIKey {
                 key: key.text().trim().to_string(),
                 value: value.text().trim().to_string(),
@@ -201,12 +291,51 @@ impl RawAuthType {
                 payload: payload.text().trim().to_string(),
                 add_to: add_to.into(),
             },
+            RawAuthType::OAuth2 {
+                grant,
+                token_url,
+                auth_url,
+                redirect_url,
+                client_id,
+                client_secret,
+                scope,
+                username,
+                password,
+                refresh_token,
+                use_pkce,
+            } => Auth::OAuth2(OAuth2 {
+                grant: (*grant).into(),
+                token_url: token_url.text().trim().to_string(),
+                auth_url: auth_url.text().trim().to_string(),
+                redirect_url: redirect_url.text().trim().to_string(),
+                client_id: client_id.text().trim().to_string(),
+                client_secret: client_secret.text().trim().to_string(),
+                scope: scope.text().trim().to_string(),
+                username: username.text().trim().to_string(),
+                password: password.text().trim().to_string(),
+                refresh_token: refresh_token.text().trim().to_string(),
+                use_pkce: *use_pkce,
+            }),
+            RawAuthType::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => Auth::AwsSigV4 {
+                access_key: access_key.text().trim().to_string(),
+                secret_key: secret_key.text().trim().to_string(),
+                session_token: session_token.text().trim().to_string(),
+                region: region.text().trim().to_string(),
+                service: service.text().trim().to_string(),
+            },
         }
     }
 
-    fn from_auth(auth: Auth) -> RawAuthType {
+    pub fn from_auth(auth: Auth) -> RawAuthType {
         match auth {
             Auth::None => RawAuthType::None,
+            Auth::Inherit => RawAuthType::Inherit,
             Auth::Basic { username, password } => RawAuthType::Basic {
                 username: Content::with_text(&username),
                 password: Content::with_text(&password),
@@ -214,6 +343,10 @@ impl RawAuthType {
             Auth::Bearer { token } => RawAuthType::Bearer {
                 token: Content::with_text(&token),
             },
+            Auth::Digest { username, password } => RawAuthType::Digest {
+                username: Content::with_text(&username),
+                password: Content::with_text(&password),
+            },
             Auth::APIKey { key, value, add_to } => RawAuthType::APIKey {
                 key: Content::with_text(&key),
                 value: Content::with_text(&value),
@@ -230,6 +363,32 @@ impl RawAuthType {
                 payload: Content::with_text(&payload),
                 add_to: add_to.into(),
             },
+            Auth::OAuth2(config) => RawAuthType::OAuth2 {
+                grant: config.grant.into(),
+                token_url: Content::with_text(&config.token_url),
+                auth_url: Content::with_text(&config.auth_url),
+                redirect_url: Content::with_text(&config.redirect_url),
+                client_id: Content::with_text(&config.client_id),
+                client_secret: Content::with_text(&config.client_secret),
+                scope: Content::with_text(&config.scope),
+                username: Content::with_text(&config.username),
+                password: Content::with_text(&config.password),
+                refresh_token: Content::with_text(&config.refresh_token),
+                use_pkce: config.use_pkce,
+            },
+            Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => RawAuthType::AwsSigV4 {
+                access_key: Content::with_text(&access_key),
+                secret_key: Content::with_text(&secret_key),
+                session_token: Content::with_text(&session_token),
+                region: Content::with_text(&region),
+                service: Content::with_text(&service),
+            },
         }
     }
 
@@ -377,6 +536,9 @@ pub struct RequestPane {
     pub script_content: Option<Content>,
     pub script_edited: bool,
     pub script_type: ScriptType,
+    pub abort_on_script_error: Option<bool>,
+    /// Assertions from the request file, not editable in the app yet
+    pub assertions: Assertions,
 }
 
 impl RequestPane {
@@ -388,10 +550,6 @@ impl RequestPane {
         self.body_cache.insert(old_body.as_str(), old_body);
     }
 
-    pub fn change_auth_type(&mut self, auth_type: &str) {
-        self.auth = RawAuthType::from_str(auth_type).unwrap_or(RawAuthType::None);
-    }
-
     pub fn to_request(&self) -> Request {
         Request {
             description: "Http request".to_string(),
@@ -402,9 +560,10 @@ impl RequestPane {
             auth: self.auth.to_auth(),
             query_params: self.query_params.to_core_kv_list(),
             path_params: to_core_kv_list(&self.path_params),
-            assertions: Default::default(),
+            assertions: self.assertions.clone(),
             pre_request: self.pre_request.clone(),
             post_request: self.post_request.clone(),
+            abort_on_script_error: self.abort_on_script_error,
         }
     }
 
@@ -425,6 +584,8 @@ impl RequestPane {
             script_content: None,
             script_edited: false,
             script_type: ScriptType::PreRequest,
+            abort_on_script_error: request.abort_on_script_error,
+            assertions: request.assertions,
         }
     }
 
@@ -1,4 +1,6 @@
+use lib::assertions::runner::{AssertionOutcome, MatcherResult};
 use lib::client;
+use lib::scripting::console::ConsoleEntry;
 use std::sync::Arc;
 
 use crate::components::editor::{self, Content};
@@ -11,6 +13,8 @@ pub enum ResponseTabId {
     BodyPreview,
     BodyRaw,
     Headers,
+    Console,
+    Tests,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -27,6 +31,10 @@ pub struct CompletedResponse {
     pub filtered_content: Option<editor::Content>,
     pub json_path_filter: Option<editor::Content>,
     pub value: Option<Value>,
+    /// Results of the request assertions and the post-request script tests
+    pub assertions: Vec<AssertionOutcome>,
+    /// Output and errors of the pre and post-request scripts
+    pub console: Vec<ConsoleEntry>,
 }
 
 impl CompletedResponse {
@@ -76,7 +84,16 @@ impl CompletedResponse {
         }
     }
 
-    pub fn new(res: client::Response) -> Self {
+    /// Number of passed and total conditions across all assertions
+    pub fn assertion_counts(&self) -> (usize, usize) {
+        let results = self.assertions.iter().flat_map(|a| a.results.iter());
+        results.fold((0, 0), |(passed, total), cond| match cond.result {
+            MatcherResult::Passed => (passed + 1, total + 1),
+            MatcherResult::Failed(_) => (passed, total + 1),
+        })
+    }
+
+    pub fn new(res: client::Response, assertions: Vec<AssertionOutcome>) -> Self {
         let (raw, pretty, value) = pretty_body(&res.body.data);
         Self {
             result: res,
@@ -85,8 +102,15 @@ impl CompletedResponse {
             value,
             filtered_content: None,
             json_path_filter: None,
+            assertions,
+            console: Vec::new(),
         }
     }
+
+    pub fn with_console(mut self, console: Vec<ConsoleEntry>) -> Self {
+        self.console = console;
+        self
+    }
 }
 
 fn pretty_body(body: &[u8]) -> (String, Option<String>, Option<Value>) {
@@ -106,7 +130,8 @@ pub enum ResponseState {
     Idle,
     Executing,
     Completed(Box<CompletedResponse>),
-    Failed(Arc<anyhow::Error>),
+    /// The error and the script output logged before it
+    Failed(Arc<anyhow::Error>, Vec<ConsoleEntry>),
 }
 
 #[derive(Debug)]
@@ -1,7 +1,7 @@
 use anyhow::{Context, Result};
-use lib::http::request::{Auth, AuthIn, JwtAlgorithm, Method, Request, RequestBody};
+use lib::http::request::{Auth, AuthIn, JwtAlgorithm, Method, OAuth2, Request, RequestBody};
 use lib::http::{self, CollectionKey, CollectionRequest, KeyFileList, KeyValList, RequestId};
-use lib::perf::PerfConfig;
+use lib::perf::{Extraction, PerfConfig};
 use lib::persistence::collections::project_dirs;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -13,7 +13,7 @@ use super::tabs::cookies_tab::CookiesTab;
 use super::tabs::history_tab::HistoryTab;
 use super::{AppState, HttpTab, PaneConfig, Tab, TabKey};
 use crate::components::split::Direction;
-use crate::state::tabs::perf_tab::PerfTab;
+use crate::state::tabs::perf_tab::{PerfStep, PerfTab};
 
 const SESSION_STATE_FILE: &str = "session_state.json";
 
@@ -79,6 +79,7 @@ fn deserialize_kf_list(list: Vec<SerializableKeyFile>) -> KeyFileList {
 #[serde(rename_all = "snake_case")]
 pub enum SerializableAuth {
     None,
+    Inherit,
     Basic {
         username: String,
         password: String,
@@ -86,6 +87,10 @@ pub enum SerializableAuth {
     Bearer {
         token: String,
     },
+    Digest {
+        username: String,
+        password: String,
+    },
     APIKey {
         key: String,
         value: String,
@@ -97,14 +102,26 @@ pub enum SerializableAuth {
         payload: String,
         add_to: AuthIn,
     },
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2),
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4 {
+        access_key: String,
+        secret_key: String,
+        session_token: String,
+        region: String,
+        service: String,
+    },
 }
 
 impl From<Auth> for SerializableAuth {
     fn from(auth: Auth) -> Self {
         match auth {
             Auth::None => SerializableAuth::None,
+            Auth::Inherit => SerializableAuth::Inherit,
             Auth::Basic { username, password } => SerializableAuth::Basic { username, password },
             Auth::Bearer { token } => SerializableAuth::Bearer { token },
+            Auth::Digest { username, password } => SerializableAuth::Digest { username, password },
             Auth::APIKey { key, value, add_to } => SerializableAuth::APIKey { key, value, add_to },
             Auth::JWTBearer {
                 algorithm,
@@ -117,6 +134,20 @@ impl From<Auth> for SerializableAuth {
                 payload,
                 add_to,
             },
+            Auth::OAuth2(config) => SerializableAuth::OAuth2(config),
+            Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => SerializableAuth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            },
         }
     }
 }
@@ -125,8 +156,10 @@ impl From<SerializableAuth> for Auth {
     fn from(auth: SerializableAuth) -> Self {
         match auth {
             SerializableAuth::None => Auth::None,
+            SerializableAuth::Inherit => Auth::Inherit,
             SerializableAuth::Basic { username, password } => Auth::Basic { username, password },
             SerializableAuth::Bearer { token } => Auth::Bearer { token },
+            SerializableAuth::Digest { username, password } => Auth::Digest { username, password },
             SerializableAuth::APIKey { key, value, add_to } => Auth::APIKey { key, value, add_to },
             SerializableAuth::JWTBearer {
                 algorithm,
@@ -139,6 +172,20 @@ impl From<SerializableAuth> for Auth {
                 payload,
                 add_to,
             },
+            SerializableAuth::OAuth2(config) => Auth::OAuth2(config),
+            SerializableAuth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => Auth::AwsSigV4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            },
         }
     }
 }
@@ -217,11 +264,22 @@ pub struct SerializableCollectionTab {
     pub collection_key: CollectionKey,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializablePerfStep {
+    pub request: (CollectionKey, RequestId),
+    #[serde(default)]
+    pub extractions: Vec<Extraction>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializablePerfTab {
     pub split_at: f32,
     pub config: PerfConfig,
+    /// Single request of the sessions saved before scenarios
+    #[serde(default, skip_serializing)]
     pub request: Option<(CollectionKey, RequestId)>,
+    #[serde(default)]
+    pub steps: Vec<SerializablePerfStep>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -345,8 +403,17 @@ impl SessionState {
                     Tab::Perf(perf_tab) => Some(SerializableTab::Perf(SerializablePerfTab {
                         split_at: perf_tab.split_at,
                         config: perf_tab.config.clone(),
-                        request: perf_tab.request.map(|request| (request.0, request.1)),
+                        request: None,
+                        steps: perf_tab
+                            .steps
+                            .iter()
+                            .map(|step| SerializablePerfStep {
+                                request: (step.request.0, step.request.1),
+                                extractions: step.extractions.clone(),
+                            })
+                            .collect(),
                     })),
+                    Tab::Runner(_) => None,
                 };
                 serializable_tab.map(|tab| (*key, tab))
             })
@@ -400,6 +467,7 @@ impl AppState {
                         assertions: Default::default(),
                         pre_request: http_tab.request.pre_request,
                         post_request: None,
+                        abort_on_script_error: None,
                     };
 
                     let collection_ref = if let Some(collection_ref) = http_tab.collection_ref {
@@ -431,9 +499,19 @@ impl AppState {
                     let mut tab = PerfTab::new();
                     tab.set_split_at(session.split_at);
                     tab.config = session.config;
-                    tab.request = session
+                    tab.steps = session
                         .request
-                        .map(|(col_key, req_id)| CollectionRequest(col_key, req_id));
+                        .map(|request| SerializablePerfStep {
+                            request,
+                            extractions: Vec::new(),
+                        })
+                        .into_iter()
+                        .chain(session.steps)
+                        .map(|step| PerfStep {
+                            request: CollectionRequest(step.request.0, step.request.1),
+                            extractions: step.extractions,
+                        })
+                        .collect();
                     Tab::Perf(Box::new(tab))
                 }
             };
@@ -1,10 +1,12 @@
 use lib::http::{CollectionKey, collection::Collection};
+use lib::proxy::ProxySettings;
 use std::time::Duration;
 
 use crate::components::KeyValList;
 
 use crate::components::editor;
 use crate::state::environment::EnvironmentsEditor;
+use crate::state::request::RawAuthType;
 use crate::state::{environment::environment_keyvals, utils::from_core_kv_list};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
@@ -23,9 +25,14 @@ pub struct CollectionTab {
     pub tab: CollectionTabId,
     pub env_editor: EnvironmentsEditor,
     pub headers: KeyValList,
+    pub auth: RawAuthType,
     pub disable_ssl: bool,
     pub timeout: Duration,
     pub timeout_str: String,
+    pub abort_on_script_error: bool,
+    /// Use `proxy` instead of the proxy of the app settings
+    pub proxy_override: bool,
+    pub proxy: ProxySettings,
     pub edited: bool,
     pub selected_script: Option<String>,
     pub script_content: editor::Content,
@@ -46,10 +53,14 @@ impl CollectionTab {
             default_env,
             collection_key: key,
             headers: from_core_kv_list(&col.headers, false),
+            auth: RawAuthType::from_auth(col.auth.clone()),
             env_editor: environment_keyvals(&col.environments),
             disable_ssl: col.disable_ssl,
             timeout: col.timeout,
             timeout_str: col.timeout.as_millis().to_string(),
+            abort_on_script_error: col.abort_on_script_error,
+            proxy_override: col.proxy.is_some(),
+            proxy: col.proxy.clone().unwrap_or_default(),
             edited: false,
             selected_script: None,
             script_content: editor::Content::new(),
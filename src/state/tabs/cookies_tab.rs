@@ -1,32 +1,140 @@
+use std::path::Path;
 use std::sync::Arc;
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use cookie_store::Cookie;
-use reqwest_cookie_store::CookieStoreRwLock;
+use lib::cookies::{
+    CookieFields, CookieFormat, CookieJar, export_cookies, import_cookies, save_cookie,
+};
+use lib::http::CollectionKey;
 
 use crate::components::editor;
 use crate::state::CommonState;
 
+/// Format of the expiry field in the cookie editor, in UTC
+pub const EXPIRY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Default)]
+pub struct CookieEditor {
+    /// Cookie being edited, `None` when adding a new cookie
+    pub original: Option<CookieFields>,
+    pub fields: CookieFields,
+    /// Expiry as typed in the editor, empty for session cookies
+    pub expires: String,
+    pub error: Option<String>,
+}
+
+impl CookieEditor {
+    pub fn new(original: Option<CookieFields>) -> Self {
+        let fields = original.clone().unwrap_or_else(|| CookieFields {
+            path: "/".to_string(),
+            ..CookieFields::default()
+        });
+        let expires = fields
+            .expires
+            .map(|expires| expires.format(EXPIRY_FORMAT).to_string())
+            .unwrap_or_default();
+
+        Self {
+            original,
+            fields,
+            expires,
+            error: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CookiesTab {
-    pub store: Arc<CookieStoreRwLock>,
+    /// Collection of the shown cookie jar, `None` for requests not saved in a collection
+    pub jar: Option<CollectionKey>,
+    pub store: Arc<CookieJar>,
     pub search_query: editor::Content,
     pub search_query_text: String,
     pub filtered_cookies: Vec<Cookie<'static>>,
+    /// Outcome of the last import or export
+    pub status: Option<String>,
+    pub editor: Option<CookieEditor>,
 }
 
 impl CookiesTab {
     pub fn new(state: &CommonState) -> Self {
-        let store = Arc::clone(&state.cookie_store);
+        let jar = state.collections.iter().next().map(|(key, _)| key);
+        let store = Self::jar_store(state, jar);
         let filtered_cookies = Self::get_all_cookies(&store);
         Self {
+            jar,
             store,
             search_query: editor::Content::new(),
             search_query_text: String::new(),
             filtered_cookies,
+            status: None,
+            editor: None,
+        }
+    }
+
+    fn jar_store(state: &CommonState, jar: Option<CollectionKey>) -> Arc<CookieJar> {
+        let store = jar
+            .and_then(|key| state.collections.get(key))
+            .map_or(&state.clients.default_jar, |col| &col.cookies);
+        Arc::clone(store)
+    }
+
+    pub fn select_jar(&mut self, state: &CommonState, jar: Option<CollectionKey>) {
+        self.jar = jar;
+        self.store = Self::jar_store(state, jar);
+        self.status = None;
+        self.editor = None;
+        self.update_filtered_cookies();
+    }
+
+    /// Writes the cookie in the editor to the jar and closes the editor
+    pub fn save_editor(&mut self) {
+        let Some(editor) = self.editor.as_mut() else {
+            return;
+        };
+        let result = Self::parse_expiry(&editor.expires).and_then(|expires| {
+            editor.fields.expires = expires;
+            let mut store = self.store.write().expect("Lock");
+            save_cookie(&mut store, &editor.fields, editor.original.as_ref())
+        });
+
+        match result {
+            Ok(()) => {
+                self.editor = None;
+                self.update_filtered_cookies();
+            }
+            Err(e) => editor.error = Some(format!("{:#}", e)),
+        }
+    }
+
+    fn parse_expiry(expires: &str) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let expires = expires.trim();
+        if expires.is_empty() {
+            return Ok(None);
         }
+        let expires = NaiveDateTime::parse_from_str(expires, EXPIRY_FORMAT)
+            .map_err(|_| anyhow::anyhow!("Expiry must be formatted as YYYY-MM-DD HH:MM:SS"))?;
+        Ok(Some(expires.and_utc()))
+    }
+
+    /// Writes the cookies of the jar in the format matching the file extension
+    pub fn export(&self, path: &Path) -> anyhow::Result<String> {
+        let format = CookieFormat::from_path(path);
+        export_cookies(&self.store.read().expect("Lock"), format)
+    }
+
+    /// Adds the cookies to the jar, the format is picked from the file extension
+    pub fn import(&mut self, path: &Path, content: &str) -> anyhow::Result<usize> {
+        let format = CookieFormat::from_path(path);
+        let mut store = self.store.write().expect("Lock");
+        let count = import_cookies(&mut store, content, format)?;
+        drop(store);
+        self.update_filtered_cookies();
+        Ok(count)
     }
 
-    fn get_all_cookies(store: &Arc<CookieStoreRwLock>) -> Vec<Cookie<'static>> {
+    fn get_all_cookies(store: &Arc<CookieJar>) -> Vec<Cookie<'static>> {
         store
             .read()
             .expect("Lock")
@@ -55,7 +55,8 @@ impl HttpTab {
         req_ref: CollectionRequest,
     ) -> Box<Self> {
         let mut tab = Self::new(name, request, req_ref);
-        tab.response.state = ResponseState::Completed(Box::new(CompletedResponse::new(response)));
+        tab.response.state =
+            ResponseState::Completed(Box::new(CompletedResponse::new(response, Vec::new())));
         tab
     }
 
@@ -99,12 +100,12 @@ impl HttpTab {
     pub fn update_response(&mut self, result: ResponseResult) {
         self.cancel_tasks();
         match result {
-            ResponseResult::Completed(res) => {
-                self.response.state =
-                    ResponseState::Completed(Box::new(CompletedResponse::new(res)));
+            ResponseResult::Completed(res, assertions, console) => {
+                let completed = CompletedResponse::new(res, assertions).with_console(console);
+                self.response.state = ResponseState::Completed(Box::new(completed));
             }
-            ResponseResult::Error(e) => {
-                self.response.state = ResponseState::Failed(e);
+            ResponseResult::Error(e, console) => {
+                self.response.state = ResponseState::Failed(e, console);
             }
         }
     }
@@ -3,3 +3,4 @@ pub mod cookies_tab;
 pub mod history_tab;
 pub mod http_tab;
 pub mod perf_tab;
+pub mod runner_tab;
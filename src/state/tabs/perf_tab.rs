@@ -1,6 +1,6 @@
 use iced::task::Handle;
-use lib::http::CollectionRequest;
-use lib::perf::{PerfConfig, PerfMetrics, PerfStats};
+use lib::http::{CollectionKey, CollectionRequest};
+use lib::perf::{Extraction, PerfConfig, PerfMetrics, PerfRun, PerfStats};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PerfState {
@@ -11,9 +11,32 @@ pub enum PerfState {
     Cancelled,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfReportTab {
+    Report,
+    Runs,
+}
+
+/// Request sent at one step of the scenario
+#[derive(Debug, Clone)]
+pub struct PerfStep {
+    pub request: CollectionRequest,
+    pub extractions: Vec<Extraction>,
+}
+
+impl PerfStep {
+    pub fn new(request: CollectionRequest) -> Self {
+        Self {
+            request,
+            extractions: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PerfTab {
-    pub request: Option<CollectionRequest>,
+    /// Requests each virtual user sends in order, all from the same collection
+    pub steps: Vec<PerfStep>,
     pub config: PerfConfig,
     pub state: PerfState,
     pub metrics: Option<PerfMetrics>,
@@ -21,12 +44,21 @@ pub struct PerfTab {
     pub progress: u64,
     pub split_at: f32,
     pub cancel: Option<Handle>,
+    pub report_tab: PerfReportTab,
+    /// Saved runs, newest first
+    pub runs: Vec<PerfRun>,
+    /// Runs picked to compare or export, at most two
+    pub selected_runs: Vec<i64>,
+    pub status: Option<String>,
+    /// Threshold being typed, added to the config once it parses
+    pub threshold_input: String,
+    pub threshold_error: Option<String>,
 }
 
 impl PerfTab {
     pub fn new() -> Self {
         Self {
-            request: None,
+            steps: Vec::new(),
             config: PerfConfig::default(),
             state: PerfState::Idle,
             metrics: None,
@@ -34,6 +66,12 @@ impl PerfTab {
             progress: 0,
             split_at: 0.45,
             cancel: None,
+            report_tab: PerfReportTab::Report,
+            runs: Vec::new(),
+            selected_runs: Vec::new(),
+            status: None,
+            threshold_input: String::new(),
+            threshold_error: None,
         }
     }
 
@@ -41,8 +79,16 @@ impl PerfTab {
         self.split_at = at.clamp(0.25, 0.70);
     }
 
-    pub fn set_request(&mut self, request: CollectionRequest) {
-        self.request = Some(request);
+    /// Appends a step, starting over when the request is from another collection
+    pub fn add_step(&mut self, request: CollectionRequest) {
+        if self.collection().is_some_and(|key| key != request.0) {
+            self.steps.clear();
+        }
+        self.steps.push(PerfStep::new(request));
+    }
+
+    pub fn collection(&self) -> Option<CollectionKey> {
+        self.steps.first().map(|step| step.request.0)
     }
 
     pub fn start_test(&mut self) {
@@ -64,6 +110,42 @@ impl PerfTab {
         self.state = PerfState::Completed;
     }
 
+    /// Selects or unselects the run, replacing the oldest selection past two runs
+    pub fn toggle_run(&mut self, id: i64) {
+        if let Some(idx) = self.selected_runs.iter().position(|&run| run == id) {
+            self.selected_runs.remove(idx);
+        } else {
+            self.selected_runs.push(id);
+            if self.selected_runs.len() > 2 {
+                self.selected_runs.remove(0);
+            }
+        }
+    }
+
+    pub fn set_runs(&mut self, runs: Vec<PerfRun>) {
+        self.selected_runs
+            .retain(|id| runs.iter().any(|run| run.id == *id));
+        self.runs = runs;
+    }
+
+    pub fn selected_runs(&self) -> Vec<&PerfRun> {
+        self.selected_runs
+            .iter()
+            .filter_map(|id| self.runs.iter().find(|run| run.id == *id))
+            .collect()
+    }
+
+    pub fn add_threshold(&mut self) {
+        match self.threshold_input.parse() {
+            Ok(threshold) => {
+                self.config.thresholds.push(threshold);
+                self.threshold_input.clear();
+                self.threshold_error = None;
+            }
+            Err(e) => self.threshold_error = Some(format!("{:#}", e)),
+        }
+    }
+
     pub fn fail_test(&mut self) {
         self.state = PerfState::Failed;
     }
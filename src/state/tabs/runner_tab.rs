@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use iced::task::Handle;
+use lib::http::CollectionKey;
+use lib::runner::{RunResult, RunSummary, RunTarget};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnerState {
+    Idle,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub struct RunnerTab {
+    pub name: String,
+    pub collection: CollectionKey,
+    pub target: RunTarget,
+    pub data: Option<PathBuf>,
+    pub state: RunnerState,
+    pub results: Vec<RunResult>,
+    pub summary: RunSummary,
+    pub error: Option<String>,
+    pub cancel: Option<Handle>,
+}
+
+impl RunnerTab {
+    pub fn new(name: String, collection: CollectionKey, target: RunTarget) -> Self {
+        Self {
+            name,
+            collection,
+            target,
+            data: None,
+            state: RunnerState::Idle,
+            results: Vec::new(),
+            summary: RunSummary::default(),
+            error: None,
+            cancel: None,
+        }
+    }
+
+    pub fn start_run(&mut self) {
+        self.reset();
+        self.state = RunnerState::Running;
+    }
+
+    pub fn add_result(&mut self, result: RunResult) {
+        self.summary.record(&result);
+        self.results.push(result);
+    }
+
+    pub fn complete_run(&mut self, summary: RunSummary) {
+        self.cancel.take();
+        self.summary = summary;
+        self.state = RunnerState::Completed;
+    }
+
+    pub fn fail_run(&mut self, error: String) {
+        self.cancel.take();
+        self.error = Some(error);
+        self.state = RunnerState::Failed;
+    }
+
+    pub fn cancel_run(&mut self) {
+        self.cancel.take();
+        self.state = RunnerState::Cancelled;
+    }
+
+    pub fn reset(&mut self) {
+        self.cancel.take();
+        self.state = RunnerState::Idle;
+        self.results.clear();
+        self.summary = RunSummary::default();
+        self.error = None;
+    }
+
+    pub fn add_task(&mut self, task: Handle) {
+        self.cancel = Some(task.abort_on_drop());
+    }
+}